@@ -0,0 +1,287 @@
+/*
+* Module: bcrypt
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use blowfish::Blowfish;
+use constant_time_eq;
+use Error;
+use rand::Rng;
+
+mod pbkdf;
+
+pub use self::pbkdf::{bcrypt_pbkdf, PBKDF_KEY_SIZE_MAX};
+
+/// Size of the salt in bytes.
+pub const SALT_SIZE: usize = 16;
+/// The smallest accepted cost (log2 of the number of rounds).
+pub const COST_MIN: u32 = 4;
+/// The biggest accepted cost.
+pub const COST_MAX: u32 = 31;
+/// The longest accepted password in bytes, longer ones would be truncated
+/// by the key schedule (18 words of the P-array).
+pub const PASSWORD_SIZE_MAX: usize = 72;
+
+const HASH_SIZE: usize = 23;	// encrypted bytes stored in the hash string
+const SALT_CHARS: usize = 22;
+const HASH_CHARS: usize = 31;
+const PREFIX_SIZE: usize = 7;	// "$2b$10$"
+
+// encrypted 64 times with the expanded state
+const CTEXT: &[u8; 24] = b"OrpheanBeholderScryDoubt";
+
+const BASE64: &[u8; 64] = b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Version (prefix) of the modular crypt string.
+/// For passwords of at most PASSWORD_SIZE_MAX bytes all versions give
+/// the same hash, they differ only in bugs of historical implementations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+	/// $2a$
+	V2a,
+	/// $2b$ (OpenBSD since 2014)
+	V2b,
+	/// $2y$ (crypt_blowfish, PHP)
+	V2y,
+}
+
+impl Version {
+	fn prefix(self) -> &'static str {
+		match self {
+			Version::V2a => "$2a$",
+			Version::V2b => "$2b$",
+			Version::V2y => "$2y$",
+		}
+	}
+}
+
+/// Returns bcrypt hash ($2b$) of 'password' with random generated salt.
+pub fn hash(password: &[u8], cost: u32) -> Result<String, Error> {
+	let mut salt = [0u8; SALT_SIZE];
+	rand::thread_rng().fill(&mut salt);
+	hash_with_salt(password, cost, &salt, Version::V2b)
+}
+
+/// Returns bcrypt hash of 'password' with passed 'salt' in the form
+/// $2b$cc$ssssssssssssssssssssssHHHHHHHHHHHHHHHHHHHHHHHHHHHHHHH,
+/// where 'cc' is the cost, 's' the salt and 'H' the hash (bcrypt's base64).
+pub fn hash_with_salt(password: &[u8], cost: u32, salt: &[u8], version: Version) -> Result<String, Error> {
+	if salt.len() != SALT_SIZE {
+		return Err(Error::InvalidSaltLength{len: salt.len(), expected: SALT_SIZE})
+	}
+	let hash = bcrypt(password, cost, salt)?;
+
+	let mut retv = format!("{}{:02}$", version.prefix(), cost);
+	retv.push_str(&encode_base64(salt));
+	retv.push_str(&encode_base64(&hash[..HASH_SIZE]));
+	Ok(retv)
+}
+
+/// Checks in constant time if 'hash' is the bcrypt hash of 'password'.
+pub fn verify(password: &[u8], hash: &str) -> Result<(), Error> {
+	let (_, cost, salt, expected) = parse(hash)?;
+	let computed = bcrypt(password, cost, &salt)?;
+	if !constant_time_eq(&computed[..HASH_SIZE], &expected) {
+		return Err(Error::AuthenticationFailed)
+	}
+	Ok(())
+}
+
+/// Splits the modular crypt string into version, cost, salt and hash.
+fn parse(hash: &str) -> Result<(Version, u32, [u8; SALT_SIZE], [u8; HASH_SIZE]), Error> {
+	let hash = hash.as_bytes();
+	if hash.len() != PREFIX_SIZE + SALT_CHARS + HASH_CHARS || hash[6] != b'$' {
+		return Err(Error::InvalidHashString)
+	}
+	let version = match &hash[..4] {
+		b"$2a$" => Version::V2a,
+		b"$2b$" => Version::V2b,
+		b"$2y$" => Version::V2y,
+		_ => return Err(Error::InvalidHashString),
+	};
+	if !hash[4].is_ascii_digit() || !hash[5].is_ascii_digit() {
+		return Err(Error::InvalidHashString)
+	}
+	let cost = u32::from(hash[4] - b'0') * 10 + u32::from(hash[5] - b'0');
+
+	let mut salt = [0u8; SALT_SIZE];
+	decode_base64(&hash[PREFIX_SIZE..(PREFIX_SIZE + SALT_CHARS)], &mut salt)?;
+	let mut expected = [0u8; HASH_SIZE];
+	decode_base64(&hash[(PREFIX_SIZE + SALT_CHARS)..], &mut expected)?;
+	Ok((version, cost, salt, expected))
+}
+
+/// Computes raw bcrypt hash (24 bytes) of 'password'.
+/// The password is used with the terminating zero byte, as in C.
+fn bcrypt(password: &[u8], cost: u32, salt: &[u8]) -> Result<[u8; 24], Error> {
+	if password.len() > PASSWORD_SIZE_MAX {
+		return Err(Error::InvalidPasswordLength{len: password.len(), max: PASSWORD_SIZE_MAX})
+	}
+	if !(COST_MIN..=COST_MAX).contains(&cost) {
+		return Err(Error::InvalidCost{cost, min: COST_MIN, max: COST_MAX})
+	}
+
+	let mut key = password.to_vec();
+	key.push(0);
+	let bf = Blowfish::eks_setup(&key, salt, cost);
+
+	let mut cdata = [0u32; 6];
+	for (word, bytes) in cdata.iter_mut().zip(CTEXT.chunks(4)) {
+		*word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+	}
+	for pair in cdata.chunks_mut(2) {
+		for _ in 0..64 {
+			let b = bf.encrypt(pair[0], pair[1]);
+			pair[0] = b.0;
+			pair[1] = b.1;
+		}
+	}
+
+	let mut retv = [0u8; 24];
+	for (bytes, word) in retv.chunks_mut(4).zip(cdata.iter()) {
+		bytes.copy_from_slice(&word.to_be_bytes());
+	}
+	Ok(retv)
+}
+
+/// Encodes 'data' with bcrypt's base64 alphabet, without padding.
+fn encode_base64(data: &[u8]) -> String {
+	let mut retv = String::new();
+	for chunk in data.chunks(3) {
+		let mut group = [0u8; 3];
+		group[..chunk.len()].copy_from_slice(chunk);
+		let n = u32::from(group[0]) << 16 | u32::from(group[1]) << 8 | u32::from(group[2]);
+		for i in 0..(chunk.len() + 1) {
+			retv.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+		}
+	}
+	retv
+}
+
+/// Decodes bcrypt's base64 'text' filling 'out', the superfluous
+/// low bits of the last character must be zero (as in OpenBSD).
+fn decode_base64(text: &[u8], out: &mut [u8]) -> Result<(), Error> {
+	let mut n = 0u32;
+	let mut bits = 0;
+	let mut i = 0;
+	for c in text {
+		let v = match BASE64.iter().position(|x| x == c) {
+			Some(v) => v as u32,
+			None => return Err(Error::InvalidHashString),
+		};
+		n = (n << 6) | v;
+		bits += 6;
+		if bits >= 8 {
+			bits -= 8;
+			if i < out.len() {
+				out[i] = (n >> bits) as u8;
+				i += 1;
+			}
+		}
+	}
+	if n & ((1 << bits) - 1) != 0 {
+		return Err(Error::InvalidHashString)
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// OpenBSD (regress/lib/libc/crypt) and jBCrypt vectors,
+	// verified with libxcrypt
+	const VECTORS: [(&str, &str); 6] = [
+		("", "$2a$06$DCq7YPn5Rq63x1Lad4cll.TV4S6ytwfsfvkgY8jIucDrjc8deX1s."),
+		("", "$2a$08$HqWuK6/Ng6sg9gQzbLrgb.Tl.ZHfXLhvt/SgVyWhQqgqcZ7ZuUtye"),
+		("a", "$2a$06$m0CrhHm10qJ3lXRY.5zDGO3rS2KdeeWLuGmsfGlMfOxih58VYVfxe"),
+		("abc", "$2a$06$If6bvum7DFjUnE9p2uDeDu0YHzrHM6tf.iqN8.yx.jNN1ILEf7h0i"),
+		("abcdefghijklmnopqrstuvwxyz", "$2a$06$.rCVZVOThsIa97pEDOxvGuRRgzG64bvtJ0938xuqzv18d3ZpQhstC"),
+		("~!@#$%^&*()      ~!@#$%^&*()PNBFRD", "$2a$06$fPIsBO8qRqkjj273rfaOI.HtSV9jLDpTbZn782DC6/t7qT67P6FfO"),
+	];
+
+	#[test]
+	fn test_vectors() {
+		for &(password, expected) in VECTORS.iter() {
+			let (version, cost, salt, _) = parse(expected).unwrap();
+			assert_eq!(hash_with_salt(password.as_bytes(), cost, &salt, version).unwrap(), expected);
+			assert_eq!(verify(password.as_bytes(), expected), Ok(()));
+			assert_eq!(verify(b"x", expected), Err(Error::AuthenticationFailed));
+		}
+	}
+
+	#[test]
+	fn test_versions() {
+		let (_, _, salt, _) = parse("$2b$04$abcdefghijklmnopqrstuuCFaEytnzrfaPZJKbS76hh9vqd9r8v2S").unwrap();
+		let hash = "CFaEytnzrfaPZJKbS76hh9vqd9r8v2S";
+		for &(version, prefix) in &[(Version::V2a, "$2a$"), (Version::V2b, "$2b$"), (Version::V2y, "$2y$")] {
+			let expected = format!("{}04$abcdefghijklmnopqrstuu{}", prefix, hash);
+			assert_eq!(hash_with_salt(b"U*U", 4, &salt, version).unwrap(), expected);
+			assert_eq!(verify(b"U*U", &expected), Ok(()));
+		}
+	}
+
+	#[test]
+	fn test_password_limit() {
+		let password = [b'x'; PASSWORD_SIZE_MAX + 1];
+		let expected = "$2b$04$abcdefghijklmnopqrstuubzadhGtS2zEF.gu0yd0opP6cVzb.e0i";
+		assert_eq!(verify(&password[..PASSWORD_SIZE_MAX], expected), Ok(()));
+		assert_eq!(verify(&password, expected), Err(Error::InvalidPasswordLength{len: 73, max: 72}));
+		assert_eq!(hash(&password, 4), Err(Error::InvalidPasswordLength{len: 73, max: 72}));
+	}
+
+	#[test]
+	fn test_hash() {
+		let hashed = hash(b"Yamato & Musashi", 5).unwrap();
+		assert!(hashed.starts_with("$2b$05$"));
+		assert_eq!(hashed.len(), 60);
+		assert_eq!(verify(b"Yamato & Musashi", &hashed), Ok(()));
+		assert_eq!(verify(b"Yamato & Musashi ", &hashed), Err(Error::AuthenticationFailed));
+		assert_ne!(hash(b"Yamato & Musashi", 5).unwrap(), hashed);
+	}
+
+	#[test]
+	fn test_errors() {
+		let salt = [0u8; SALT_SIZE];
+		assert_eq!(hash(b"abc", 3), Err(Error::InvalidCost{cost: 3, min: 4, max: 31}));
+		assert_eq!(hash(b"abc", 32), Err(Error::InvalidCost{cost: 32, min: 4, max: 31}));
+		assert_eq!(hash_with_salt(b"abc", 4, &salt[1..], Version::V2b),
+		           Err(Error::InvalidSaltLength{len: 15, expected: 16}));
+
+		let valid = VECTORS[2].1;
+		assert_eq!(verify(b"a", &valid[1..]), Err(Error::InvalidHashString));
+		assert_eq!(verify(b"a", &valid.replace("$2a$", "$2x$")), Err(Error::InvalidHashString));
+		assert_eq!(verify(b"a", &valid.replace("$06$", "$0x$")), Err(Error::InvalidHashString));
+		assert_eq!(verify(b"a", &valid.replace("$06$", "$03$")), Err(Error::InvalidCost{cost: 3, min: 4, max: 31}));
+		assert_eq!(verify(b"a", &valid.replace("m0C", "m!C")), Err(Error::InvalidHashString));
+
+		// the same salt and hash with non-zero unused low bits of the last character
+		assert_eq!(verify(b"a", &valid.replace("zDGO", "zDGP")), Err(Error::InvalidHashString));
+		assert_eq!(verify(b"a", &valid.replace("Vfxe", "Vfxf")), Err(Error::InvalidHashString));
+	}
+}
@@ -0,0 +1,168 @@
+/*
+* Module: bcrypt::pbkdf
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use blowfish::Blowfish;
+use sha512;
+use Error;
+
+/// The longest derived key in bytes.
+pub const PBKDF_KEY_SIZE_MAX: usize = HASH_SIZE * HASH_SIZE;
+
+const HASH_SIZE: usize = 32;	// output of bcrypt_hash, 8 u32
+
+// encrypted 64 times with the expanded state
+const CTEXT: &[u8; HASH_SIZE] = b"OxychromaticBlowfishSwatDynamite";
+
+/// Derives key of 'key_len' bytes from 'password' and 'salt' as OpenSSH
+/// does for its private keys (bcrypt_pbkdf of OpenBSD).
+/// It is PBKDF2 with bcrypt_hash of SHA-512 digests as the pseudorandom
+/// function, the bytes of the output blocks are interleaved in the key.
+pub fn bcrypt_pbkdf(password: &[u8], salt: &[u8], rounds: u32, key_len: usize) -> Result<Vec<u8>, Error> {
+	// handle caller mistakes
+	if password.is_empty() || salt.is_empty() {
+		return Err(Error::EmptyInput)
+	}
+	if rounds < 1 {
+		return Err(Error::InvalidRounds{rounds, min: 1, max: u32::MAX})
+	}
+	if !(1..=PBKDF_KEY_SIZE_MAX).contains(&key_len) {
+		return Err(Error::InvalidKeyLength{len: key_len, min: 1, max: PBKDF_KEY_SIZE_MAX})
+	}
+
+	let stride = key_len.div_ceil(HASH_SIZE);
+	let amount = key_len.div_ceil(stride);
+	let sha2pass = sha512::digest(password);
+	let mut key = vec![0u8; key_len];
+
+	for count in 1..=stride {
+		// first round, the salt is salt || count
+		let mut hash = sha512::new();
+		hash.update(salt);
+		hash.update(&(count as u32).to_be_bytes());
+		let mut tmp = bcrypt_hash(&sha2pass, &hash.finalize());
+		let mut out = tmp;
+
+		// next rounds, the salt is the previous output
+		for _ in 1..rounds {
+			tmp = bcrypt_hash(&sha2pass, &sha512::digest(&tmp));
+			for (o, t) in out.iter_mut().zip(tmp.iter()) {
+				*o ^= *t;
+			}
+		}
+
+		// the output block is spread over the whole key
+		for (i, b) in out.iter().take(amount).enumerate() {
+			let dest = i * stride + (count - 1);
+			if dest >= key_len {
+				break
+			}
+			key[dest] = *b;
+		}
+	}
+	Ok(key)
+}
+
+/// bcrypt_hash: like bcrypt with 64 rounds of the key schedule
+/// and the SHA-512 digests of the password and the salt.
+fn bcrypt_hash(sha2pass: &[u8], sha2salt: &[u8]) -> [u8; HASH_SIZE] {
+	let mut bf = Blowfish::initial_state();
+	bf.expand_state(sha2pass, sha2salt);
+	for _ in 0..64 {
+		bf.expand_state(sha2salt, &[]);
+		bf.expand_state(sha2pass, &[]);
+	}
+
+	let mut cdata = [0u32; HASH_SIZE / 4];
+	for (word, bytes) in cdata.iter_mut().zip(CTEXT.chunks(4)) {
+		*word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+	}
+	for _ in 0..64 {
+		for pair in cdata.chunks_mut(2) {
+			let b = bf.encrypt(pair[0], pair[1]);
+			pair[0] = b.0;
+			pair[1] = b.1;
+		}
+	}
+
+	// words are stored in little-endian order
+	let mut retv = [0u8; HASH_SIZE];
+	for (bytes, word) in retv.chunks_mut(4).zip(cdata.iter()) {
+		bytes.copy_from_slice(&word.to_le_bytes());
+	}
+	retv
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// password, salt, rounds, key
+	type Vector<'a> = (&'a [u8], &'a [u8], u32, &'a [u8]);
+
+	#[test]
+	fn test_vectors() {
+		// OpenBSD regress/lib/libutil/bcrypt_pbkdf
+		let vectors: [Vector; 8] = [
+			(b"password", b"salt", 4, &[
+				0x5b, 0xbf, 0x0c, 0xc2, 0x93, 0x58, 0x7f, 0x1c, 0x36, 0x35, 0x55, 0x5c, 0x27, 0x79, 0x65, 0x98,
+				0xd4, 0x7e, 0x57, 0x90, 0x71, 0xbf, 0x42, 0x7e, 0x9d, 0x8f, 0xbe, 0x84, 0x2a, 0xba, 0x34, 0xd9]),
+			(b"password", b"\0", 4, &[
+				0xc1, 0x2b, 0x56, 0x62, 0x35, 0xee, 0xe0, 0x4c, 0x21, 0x25, 0x98, 0x97, 0x0a, 0x57, 0x9a, 0x67]),
+			(b"\0", b"salt", 4, &[
+				0x60, 0x51, 0xbe, 0x18, 0xc2, 0xf4, 0xf8, 0x2c, 0xbf, 0x0e, 0xfe, 0xe5, 0x47, 0x1b, 0x4b, 0xb9]),
+			(b"password\0", b"salt\0", 4, &[
+				0x74, 0x10, 0xe4, 0x4c, 0xf4, 0xfa, 0x07, 0xbf, 0xaa, 0xc8, 0xa9, 0x28, 0xb1, 0x72, 0x7f, 0xac,
+				0x00, 0x13, 0x75, 0xe7, 0xbf, 0x73, 0x84, 0x37, 0x0f, 0x48, 0xef, 0xd1, 0x21, 0x74, 0x30, 0x50]),
+			(b"pass\0wor", b"sa\0l", 4, &[
+				0xc2, 0xbf, 0xfd, 0x9d, 0xb3, 0x8f, 0x65, 0x69, 0xef, 0xef, 0x43, 0x72, 0xf4, 0xde, 0x83, 0xc0]),
+			(b"pass\0word", b"sa\0lt", 4, &[
+				0x4b, 0xa4, 0xac, 0x39, 0x25, 0xc0, 0xe8, 0xd7, 0xf0, 0xcd, 0xb6, 0xbb, 0x16, 0x84, 0xa5, 0x6f]),
+			(b"password", b"salt", 8, &[
+				0xe1, 0x36, 0x7e, 0xc5, 0x15, 0x1a, 0x33, 0xfa, 0xac, 0x4c, 0xc1, 0xc1, 0x44, 0xcd, 0x23, 0xfa,
+				0x15, 0xd5, 0x54, 0x84, 0x93, 0xec, 0xc9, 0x9b, 0x9b, 0x5d, 0x9c, 0x0d, 0x3b, 0x27, 0xbe, 0xc7,
+				0x62, 0x27, 0xea, 0x66, 0x08, 0x8b, 0x84, 0x9b, 0x20, 0xab, 0x7a, 0xa4, 0x78, 0x01, 0x02, 0x46,
+				0xe7, 0x4b, 0xba, 0x51, 0x72, 0x3f, 0xef, 0xa9, 0xf9, 0x47, 0x4d, 0x65, 0x08, 0x84, 0x5e, 0x8d]),
+			(b"password", b"salt", 42, &[
+				0x83, 0x3c, 0xf0, 0xdc, 0xf5, 0x6d, 0xb6, 0x56, 0x08, 0xe8, 0xf0, 0xdc, 0x0c, 0xe8, 0x82, 0xbd]),
+		];
+		for &(password, salt, rounds, expected) in vectors.iter() {
+			assert_eq!(bcrypt_pbkdf(password, salt, rounds, expected.len()).unwrap(), expected);
+		}
+	}
+
+	#[test]
+	fn test_errors() {
+		assert_eq!(bcrypt_pbkdf(b"", b"salt", 4, 16), Err(Error::EmptyInput));
+		assert_eq!(bcrypt_pbkdf(b"password", b"", 4, 16), Err(Error::EmptyInput));
+		assert_eq!(bcrypt_pbkdf(b"password", b"salt", 0, 16), Err(Error::InvalidRounds{rounds: 0, min: 1, max: u32::MAX}));
+		assert_eq!(bcrypt_pbkdf(b"password", b"salt", 4, 0), Err(Error::InvalidKeyLength{len: 0, min: 1, max: 1024}));
+		assert_eq!(bcrypt_pbkdf(b"password", b"salt", 4, 1025), Err(Error::InvalidKeyLength{len: 1025, min: 1, max: 1024}));
+	}
+}
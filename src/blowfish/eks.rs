@@ -0,0 +1,115 @@
+/*
+* Module: blowfish::eks
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use super::{Blowfish, ByteOrder, ORIG_P, ORIG_S, P_SIZE_MAX, ROUNDS};
+
+impl Blowfish {
+	/// Returns Blowfish with the initial P-array and S-boxes (digits of pi),
+	/// before any key expansion.
+	pub(crate) fn initial_state() -> Blowfish {
+		Blowfish::initial_state_with(ByteOrder::Big, ROUNDS)
+	}
+
+	/// Returns the initial state for 'rounds' rounds, the P-array
+	/// is continued into the S-boxes when longer than the standard one.
+	pub(super) fn initial_state_with(order: ByteOrder, rounds: usize) -> Blowfish {
+		let mut p = [0u32; P_SIZE_MAX];
+		let digits = ORIG_P.iter().chain(ORIG_S.iter().flat_map(|s| s.iter()));
+		for (p, d) in p[..(rounds + 2)].iter_mut().zip(digits) {
+			*p = *d;
+		}
+		Blowfish{p, s: ORIG_S, order, rounds}
+	}
+
+	/// Expensive key schedule of bcrypt (EksBlowfishSetup): the key and
+	/// 'salt' are expanded into the initial state, then the key and the salt
+	/// are expanded alternately 2^'cost' times. 'salt' must not be empty.
+	pub(crate) fn eks_setup(key: &[u8], salt: &[u8], cost: u32) -> Blowfish {
+		let mut bf = Blowfish::initial_state();
+		bf.expand_state(key, salt);
+		for _ in 0..(1u64 << cost) {
+			bf.expand_state(key, &[]);
+			bf.expand_state(salt, &[]);
+		}
+		bf
+	}
+
+	/// ExpandKey: xors the P-array with 'key' and replaces the P-array and
+	/// the S-boxes with the encryption chain of 'data' (zeros if empty).
+	/// The last right word is dropped when the P-array length is odd.
+	pub(crate) fn expand_state(&mut self, key: &[u8], data: &[u8]) {
+		let n = self.rounds + 2;
+		let mut j = 0;
+		for p in self.p[..n].iter_mut() {
+			*p ^= stream2word(key, &mut j);
+		}
+
+		let mut j = 0;
+		let (mut xl, mut xr) = (0u32, 0u32);
+		for i in (0..n).step_by(2) {
+			if !data.is_empty() {
+				xl ^= stream2word(data, &mut j);
+				xr ^= stream2word(data, &mut j);
+			}
+			let b = self.encrypt(xl, xr);
+			xl = b.0; xr = b.1;
+			self.p[i] = xl;
+			if i + 1 < n {
+				self.p[i + 1] = xr;
+			}
+		}
+		for i in 0..4 {
+			for k in (0..256).step_by(2) {
+				if !data.is_empty() {
+					xl ^= stream2word(data, &mut j);
+					xr ^= stream2word(data, &mut j);
+				}
+				let b = self.encrypt(xl, xr);
+				xl = b.0; xr = b.1;
+				self.s[i][k] = xl;
+				self.s[i][k + 1] = xr;
+			}
+		}
+	}
+}
+
+/// Returns big-endian u32 read from 'data' at 'pos' (cyclically),
+/// 'pos' is moved past the read bytes.
+fn stream2word(data: &[u8], pos: &mut usize) -> u32 {
+	let mut word = 0u32;
+	for _ in 0..4 {
+		if *pos >= data.len() {
+			*pos = 0;
+		}
+		word = (word << 8) | u32::from(data[*pos]);
+		*pos += 1;
+	}
+	word
+}
@@ -28,332 +28,237 @@
 * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
-use padding;
-use padding_index;
 use bytes2block;
 use block2bytes;
-use words2bytes;
-use rand::Rng;
+use BlockCipher;
+use Error;
+use Padding;
+use modes;
+use std::mem;
+
+mod eks;
 
 pub struct Blowfish {
-    p: [u32; 18],
+    p: [u32; P_SIZE_MAX],
     s: [[u32; 256]; 4],
+    order: ByteOrder,
+    rounds: usize,
+}
+
+/// Byte order of the two u32 words of the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+	/// Little-endian words, the byte order used by this crate so far.
+	Little,
+	/// Big-endian words of the reference implementation
+	/// (B. Schneier's and Eric Young's test vectors, OpenSSL).
+	Big,
 }
 
 const BLOCK_SIZE: usize = 8;
+const KEY_SIZE_MIN: usize = 4;
+const KEY_SIZE_MAX: usize = 56;
+const ROUNDS: usize = 16;
+
+/// The longest key accepted by 'with_rounds' (the reference implementation).
+pub const KEY_SIZE_MAX_EXTENDED: usize = 72;
+/// The least number of rounds accepted by 'with_rounds'.
+pub const ROUNDS_MIN: u32 = 1;
+/// The greatest number of rounds accepted by 'with_rounds'.
+pub const ROUNDS_MAX: u32 = 64;
+const P_SIZE_MAX: usize = ROUNDS_MAX as usize + 2;
 
 /// new creates new value of Blowfish type
+/// with little-endian words in the block
 /// PARAMETERS:
 ///   key - bytes vector of the key
-pub fn new(key: &[u8]) -> Result<Blowfish, String> {
+pub fn new(key: &[u8]) -> Result<Blowfish, Error> {
+	with_byte_order(key, ByteOrder::Little)
+}
+
+/// Creates new Blowfish object for 'key' reading and writing
+/// words of the blocks in byte order 'order'.
+pub fn with_byte_order(key: &[u8], order: ByteOrder) -> Result<Blowfish, Error> {
    let key_len = key.len();
-   if key_len < 4 || key_len > 56 {
-      return Err("invalid key size".to_string());
+   if !(KEY_SIZE_MIN..=KEY_SIZE_MAX).contains(&key_len) {
+      return Err(Error::InvalidKeyLength{len: key_len, min: KEY_SIZE_MIN, max: KEY_SIZE_MAX});
    }
 
 
-   let mut bf = Blowfish{p: [0u32; 18], s: [[0u32; 256]; 4]};
-   
-   for i in 0..4 {
-   	for j in 0..256 {
-      	bf.s[i][j] = ORIG_S[i][j];
-      }
-   }
+   Ok(schedule(key, order, ROUNDS))
+}
 
-	let mut k = 0;
-	for i in 0..18 {
-		let mut data = 0u32;
-		for _j in 0..4 {
-			data = (data << 8) | (key[k] as u32);
-         k += 1;
-         if k >= key_len {
-         	k = 0;
-      	}
-   	}
-  	 	bf.p[i] = ORIG_P[i] ^ data;
+/// Creates new Blowfish object with 'rounds' rounds (P-array of 'rounds' + 2
+/// words) for 'key' of any length in 1..72 bytes, as the reference
+/// implementation of B. Schneier accepts. Keys longer than 56 bytes and
+/// round counts other than 16 are not part of the standard, they are meant
+/// for research and compatibility only.
+pub fn with_rounds(key: &[u8], order: ByteOrder, rounds: u32) -> Result<Blowfish, Error> {
+	let key_len = key.len();
+	if !(1..=KEY_SIZE_MAX_EXTENDED).contains(&key_len) {
+		return Err(Error::InvalidKeyLength{len: key_len, min: 1, max: KEY_SIZE_MAX_EXTENDED});
 	}
-
-	let mut xl = 0u32;
-	let mut xr = 0u32;
-
-    // P
-	 let mut b = bf.encrypt(xl, xr);
-	 xl = b.0; xr = b.1;
-	 bf.p[0] = xl;
-    bf.p[1] = xr;
-	 b = bf.encrypt(xl, xr);
-	 xl = b.0; xr = b.1;
-	 bf.p[2] = xl;
-	 bf.p[3] = xr;
-	 b = bf.encrypt(xl, xr);
-	 xl = b.0; xr = b.1;
-	 bf.p[4] = xl;
-	 bf.p[5] = xr;
-	 b = bf.encrypt(xl, xr);
-	 xl = b.0; xr = b.1;
-	 bf.p[6] = xl;
-    bf.p[7] = xr;
-	 b = bf.encrypt(xl, xr);
-	 xl = b.0; xr = b.1;
-	 bf.p[8] = xl;
-    bf.p[9] = xr;
-	 b = bf.encrypt(xl, xr);
-	 xl = b.0; xr = b.1;
-	 bf.p[10] = xl;
-    bf.p[11] = xr;
-	 b = bf.encrypt(xl, xr);
-    xl = b.0; xr = b.1;
-	 bf.p[12] = xl;
-    bf.p[13] = xr;
-	 b = bf.encrypt(xl, xr);
-	 xl = b.0; xr = b.1;
-	 bf.p[14] = xl;
-    bf.p[15] = xr;
-	 b = bf.encrypt(xl, xr);
-    xl = b.0; xr = b.1;
-	 bf.p[16] = xl;
-    bf.p[17] = xr;
-	 
-	 // S
-    let mut i = 0;
-    while i < 4 {
-		 let mut j = 0;
-		 while j < 256 {
-			 b = bf.encrypt(xl, xr);
-			 xl = b.0; xr = b.1;
-			 bf.s[i][j] = xl;
-			 bf.s[i][j + 1] = xr;
-			 j += 2;
-		 }
-		i += 1;
+	if !(ROUNDS_MIN..=ROUNDS_MAX).contains(&rounds) {
+		return Err(Error::InvalidRounds{rounds, min: ROUNDS_MIN, max: ROUNDS_MAX});
 	}
+	Ok(schedule(key, order, rounds as usize))
+}
 
-   Ok(bf)
+/// Key schedule: the P-array (digits of pi, continued into the S-boxes
+/// when longer than the standard one) is xored with the key, then P and S
+/// are replaced with the encryption chain of the zero block.
+fn schedule(key: &[u8], order: ByteOrder, rounds: usize) -> Blowfish {
+	let mut bf = Blowfish::initial_state_with(order, rounds);
+	bf.expand_state(key, &[]);
+	bf
 }
 
 impl Blowfish {
 	
 	/// CBC : encrypts vector of bytes in CBC mode
 	/// using passed IV vector.
-	pub fn encrypt_cbc_iv(&self, input: &Vec<u8>, iv: &Vec<u8>) -> Result<Vec<u8>, String> {
-		// handle caller mistakes
-		if iv.len() != BLOCK_SIZE {
-			return Err("invalid IV size".to_string());
-		}
-		if input.len() == 0 {
-			return Err("nothing to encrypt".to_string());
-		}
-		
-		// create plain text buffer from 'input',
-		// with padding if needed
-		let plain = {
-         let mut buffer: Vec<u8> = Vec::new();
-         buffer.extend(input);
-         let n = buffer.len() % BLOCK_SIZE;
-         if n != 0 {
-            buffer.extend(padding(BLOCK_SIZE - n));
-         }
-         buffer
-		};
-      let nbytes = plain.len();
-		
-		// create cipher text buffer
-		// the first block initialised with 'iv' content
-		let mut cipher = {
-			let mut buffer = Vec::new();
-			buffer.resize(nbytes + BLOCK_SIZE, 0);
-         buffer[0..BLOCK_SIZE].copy_from_slice(iv);
-			buffer
-		};
-		
-		let mut i = 0usize;
-		let mut x = bytes2block(&cipher[..]); // get values from iv
-
-		while i < nbytes {
-			let t = bytes2block(&plain[i..]);
-			x = self.encrypt(t.0 ^ x.0, t.1 ^ x.1);
-			block2bytes(x, &mut cipher[(i + BLOCK_SIZE)..]);
-			i += BLOCK_SIZE;
-		}
-		
-		// Ok
-		Ok(cipher)
+	pub fn encrypt_cbc_iv(&self, input: &[u8], iv: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc_iv(self, input, iv, padding)
 	}
 	
-	pub fn encrypt_cbc(&self, input: &Vec<u8>) -> Result<Vec<u8>, String> {
-		self.encrypt_cbc_iv(input, {
-			let mut buffer = [0u8; BLOCK_SIZE];
-			rand::thread_rng().fill(&mut buffer);
-			&buffer.to_vec()
-		})
+	pub fn encrypt_cbc(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc(self, input, padding)
 	}
 	
 	/// CBC : decrypts vector of bytes in CBC mode
-	pub fn decrypt_cbc(&self, cipher: &Vec<u8>) -> Result<Vec<u8>, String> {
- 		let nbytes = cipher.len();
-		if nbytes <= BLOCK_SIZE {
-			return  Err("cipher data size is to short".to_string());
-		}
-		let mut plain: Vec<u8> = Vec::new();
-      plain.resize(nbytes - BLOCK_SIZE, 0);
-
- 		let mut p = bytes2block(&cipher[..]);
-      let mut i = BLOCK_SIZE;
-		while i < nbytes {
-			let x = bytes2block(&cipher[i..]);
- 			let t = x;
-			let c = self.decrypt(x.0, x.1);
- 			words2bytes(c.0 ^ p.0, c.1 ^ p.1, &mut plain[(i - BLOCK_SIZE)..]);
-			p = t;
-			i += BLOCK_SIZE;
-		}
-
-		match padding_index(&plain) {
-			Some(idx) => {
-            let retv = plain[..idx].to_vec();
-				Ok(retv)
-			},
-			_ => {
-				Ok(plain)
-			}
-		}
+	pub fn decrypt_cbc(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_cbc(self, cipher, padding)
 	}
 	
 	/// ECB : encrypts vector of bytes in ECB mode.
-	pub fn encrypt_ecb(&self, input: &Vec<u8>) -> Result<Vec<u8>, String> {
-		if input.len() == 0 {
-			return Err("plain text size is 0".to_string());
-		}
-		
-		let plain = {
-			let mut buffer = Vec::new();
-			buffer.extend(input);
-			let n = buffer.len() % BLOCK_SIZE;
-			if n != 0 {
-				buffer.extend(padding(BLOCK_SIZE - n));
-			}
-			buffer
-		};
-		let nbytes = plain.len();
-		
-		let mut cipher = Vec::with_capacity(nbytes);
-		cipher.resize(nbytes, 0);
-		
-		let mut i = 0usize;
-		while i < nbytes {
-			let mut x = bytes2block(&plain[i..]);
-			x = self.encrypt(x.0, x.1);
-			block2bytes(x, &mut cipher[i..]);
-			i += BLOCK_SIZE;
-		}
-		Ok(cipher)
+	pub fn encrypt_ecb(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ecb(self, input, padding)
 	}
 	
 	/// ECB : decrypts vector of bytes in ECB mode
-	pub fn decrypt_ecb(&self, cipher: &Vec<u8>) -> Result<Vec<u8>, String> {
-		let nbytes = cipher.len();
-		if nbytes == 0 {
-			return  Err("cipher text size is 0".to_string());
-		}
-		
-		let mut plain = Vec::with_capacity(nbytes);
-		plain.resize(nbytes, 0);
-		
-		let mut i = 0usize;
-		let mut x: (u32, u32);
-		while i < nbytes {
-			x = bytes2block(&cipher[i..]);
-			x = self.decrypt(x.0, x.1);
-			block2bytes(x, &mut plain[i..]);
-			i += BLOCK_SIZE;
-		}
-		
-		match padding_index(&plain) {
-			Some(idx) => {
-				Ok(plain[..idx].to_vec())
-			},
-			_ => {
-				Ok(plain)
-			}
-		}
+	pub fn decrypt_ecb(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ecb(self, cipher, padding)
+	}
+
+	/// CTR : encrypts vector of bytes in CTR mode
+	/// using passed IV vector (initial counter block).
+	pub fn encrypt_ctr_iv(&self, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr_iv(self, input, iv)
+	}
+
+	pub fn encrypt_ctr(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr(self, input)
+	}
+
+	/// CTR : decrypts vector of bytes in CTR mode
+	pub fn decrypt_ctr(&self, cipher: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ctr(self, cipher)
 	}
 
 	/// Encrypts one block (two u32 words)
 	pub fn encrypt(&self, mut xl: u32, mut xr: u32) -> (u32, u32) {
-		xl = xl ^ self.p[0];
-      xr = self.f(xl) ^ xr; 
-      xr = xr ^ self.p[1];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[2];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[3];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[4];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[5];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[6];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[7];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[8];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[9];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[10];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[11];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[12];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[13];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[14];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[15];
-      xl = self.f(xr) ^ xl;
+		if self.rounds != ROUNDS {
+			return self.encrypt_rounds(xl, xr);
+		}
+		xl ^= self.p[0];
+      xr ^= self.f(xl); 
+      xr ^= self.p[1];
+      xl ^= self.f(xr);
+      xl ^= self.p[2];
+      xr ^= self.f(xl);
+      xr ^= self.p[3];
+      xl ^= self.f(xr);
+      xl ^= self.p[4];
+      xr ^= self.f(xl);
+      xr ^= self.p[5];
+      xl ^= self.f(xr);
+      xl ^= self.p[6];
+      xr ^= self.f(xl);
+      xr ^= self.p[7];
+      xl ^= self.f(xr);
+      xl ^= self.p[8];
+      xr ^= self.f(xl);
+      xr ^= self.p[9];
+      xl ^= self.f(xr);
+      xl ^= self.p[10];
+      xr ^= self.f(xl);
+      xr ^= self.p[11];
+      xl ^= self.f(xr);
+      xl ^= self.p[12];
+      xr ^= self.f(xl);
+      xr ^= self.p[13];
+      xl ^= self.f(xr);
+      xl ^= self.p[14];
+      xr ^= self.f(xl);
+      xr ^= self.p[15];
+      xl ^= self.f(xr);
 
       ((xr ^ self.p[17]), (xl ^ self.p[16]))
 	}
 
 	/// Decrypts one block (two u32 words)
 	pub fn decrypt(&self, mut xl: u32, mut xr: u32) -> (u32, u32) {
- 		xl = xl ^ self.p[17];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[16];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[15];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[14];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[13];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[12];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[11];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[10];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[9];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[8];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[7];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[6];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[5];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[4];
-      xl = self.f(xr) ^ xl;
-      xl = xl ^ self.p[3];
-      xr = self.f(xl) ^ xr;
-      xr = xr ^ self.p[2];
-      xl = self.f(xr) ^ xl;
+		if self.rounds != ROUNDS {
+			return self.decrypt_rounds(xl, xr);
+		}
+ 		xl ^= self.p[17];
+      xr ^= self.f(xl);
+      xr ^= self.p[16];
+      xl ^= self.f(xr);
+      xl ^= self.p[15];
+      xr ^= self.f(xl);
+      xr ^= self.p[14];
+      xl ^= self.f(xr);
+      xl ^= self.p[13];
+      xr ^= self.f(xl);
+      xr ^= self.p[12];
+      xl ^= self.f(xr);
+      xl ^= self.p[11];
+      xr ^= self.f(xl);
+      xr ^= self.p[10];
+      xl ^= self.f(xr);
+      xl ^= self.p[9];
+      xr ^= self.f(xl);
+      xr ^= self.p[8];
+      xl ^= self.f(xr);
+      xl ^= self.p[7];
+      xr ^= self.f(xl);
+      xr ^= self.p[6];
+      xl ^= self.f(xr);
+      xl ^= self.p[5];
+      xr ^= self.f(xl);
+      xr ^= self.p[4];
+      xl ^= self.f(xr);
+      xl ^= self.p[3];
+      xr ^= self.f(xl);
+      xr ^= self.p[2];
+      xl ^= self.f(xr);
  
       ((xr ^ self.p[0]), (xl ^ self.p[1]))
  	}
 
+	/// Encrypts one block with any number of rounds.
+	fn encrypt_rounds(&self, mut xl: u32, mut xr: u32) -> (u32, u32) {
+		let rounds = self.rounds;
+		for i in 0..rounds {
+			xl ^= self.p[i];
+			xr ^= self.f(xl);
+			mem::swap(&mut xl, &mut xr);
+		}
+		(xr ^ self.p[rounds + 1], xl ^ self.p[rounds])
+	}
+
+	/// Decrypts one block with any number of rounds.
+	fn decrypt_rounds(&self, mut xl: u32, mut xr: u32) -> (u32, u32) {
+		let rounds = self.rounds;
+		for i in (2..rounds + 2).rev() {
+			xl ^= self.p[i];
+			xr ^= self.f(xl);
+			mem::swap(&mut xl, &mut xr);
+		}
+		(xr ^ self.p[0], xl ^ self.p[1])
+	}
+
 	// f
    #[inline]
 	fn f(&self, mut x: u32) -> u32 {
@@ -366,10 +271,130 @@ impl Blowfish {
 	}
 }
 
+
+impl BlockCipher for Blowfish {
+	const BLOCK_SIZE: usize = BLOCK_SIZE;
+	const KEY_SIZE_MIN: usize = KEY_SIZE_MIN;
+	const KEY_SIZE_MAX: usize = KEY_SIZE_MAX;
+
+	fn new(key: &[u8]) -> Result<Blowfish, Error> {
+		new(key)
+	}
+
+	fn encrypt_block(&self, block: &mut [u8]) {
+		let x = self.load(block);
+		self.store(self.encrypt(x.0, x.1), block);
+	}
+
+	fn decrypt_block(&self, block: &mut [u8]) {
+		let x = self.load(block);
+		self.store(self.decrypt(x.0, x.1), block);
+	}
+}
+
+impl Blowfish {
+	/// Converts block of bytes to two u32 words in the byte order of the cipher.
+	fn load(&self, data: &[u8]) -> (u32, u32) {
+		match self.order {
+			ByteOrder::Little => bytes2block(data),
+			ByteOrder::Big => (
+				u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+				u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+			),
+		}
+	}
+
+	/// Stores two u32 words as bytes in the byte order of the cipher.
+	fn store(&self, x: (u32, u32), data: &mut [u8]) {
+		match self.order {
+			ByteOrder::Little => block2bytes(x, data),
+			ByteOrder::Big => {
+				data[..4].copy_from_slice(&x.0.to_be_bytes());
+				data[4..8].copy_from_slice(&x.1.to_be_bytes());
+			},
+		}
+	}
+}
+
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
 	use super::*;
 	use std::time::{Instant};
+
+	// Eric Young's test set (key, plain text, cipher text), big-endian words
+	const ECB_VECTORS: [([u8; 8], [u8; 8], [u8; 8]); 34] = [
+		([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x4e, 0xf9, 0x97, 0x45, 0x61, 0x98, 0xdd, 0x78]),
+		([0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], [0x51, 0x86, 0x6f, 0xd5, 0xb8, 0x5e, 0xcb, 0x8a]),
+		([0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01], [0x7d, 0x85, 0x6f, 0x9a, 0x61, 0x30, 0x63, 0xf2]),
+		([0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11], [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11], [0x24, 0x66, 0xdd, 0x87, 0x8b, 0x96, 0x3c, 0x9d]),
+		([0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef], [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11], [0x61, 0xf9, 0xc3, 0x80, 0x22, 0x81, 0xb0, 0x96]),
+		([0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11], [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef], [0x7d, 0x0c, 0xc6, 0x30, 0xaf, 0xda, 0x1e, 0xc7]),
+		([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x4e, 0xf9, 0x97, 0x45, 0x61, 0x98, 0xdd, 0x78]),
+		([0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10], [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef], [0x0a, 0xce, 0xab, 0x0f, 0xc6, 0xa0, 0xa2, 0x8d]),
+		([0x7c, 0xa1, 0x10, 0x45, 0x4a, 0x1a, 0x6e, 0x57], [0x01, 0xa1, 0xd6, 0xd0, 0x39, 0x77, 0x67, 0x42], [0x59, 0xc6, 0x82, 0x45, 0xeb, 0x05, 0x28, 0x2b]),
+		([0x01, 0x31, 0xd9, 0x61, 0x9d, 0xc1, 0x37, 0x6e], [0x5c, 0xd5, 0x4c, 0xa8, 0x3d, 0xef, 0x57, 0xda], [0xb1, 0xb8, 0xcc, 0x0b, 0x25, 0x0f, 0x09, 0xa0]),
+		([0x07, 0xa1, 0x13, 0x3e, 0x4a, 0x0b, 0x26, 0x86], [0x02, 0x48, 0xd4, 0x38, 0x06, 0xf6, 0x71, 0x72], [0x17, 0x30, 0xe5, 0x77, 0x8b, 0xea, 0x1d, 0xa4]),
+		([0x38, 0x49, 0x67, 0x4c, 0x26, 0x02, 0x31, 0x9e], [0x51, 0x45, 0x4b, 0x58, 0x2d, 0xdf, 0x44, 0x0a], [0xa2, 0x5e, 0x78, 0x56, 0xcf, 0x26, 0x51, 0xeb]),
+		([0x04, 0xb9, 0x15, 0xba, 0x43, 0xfe, 0xb5, 0xb6], [0x42, 0xfd, 0x44, 0x30, 0x59, 0x57, 0x7f, 0xa2], [0x35, 0x38, 0x82, 0xb1, 0x09, 0xce, 0x8f, 0x1a]),
+		([0x01, 0x13, 0xb9, 0x70, 0xfd, 0x34, 0xf2, 0xce], [0x05, 0x9b, 0x5e, 0x08, 0x51, 0xcf, 0x14, 0x3a], [0x48, 0xf4, 0xd0, 0x88, 0x4c, 0x37, 0x99, 0x18]),
+		([0x01, 0x70, 0xf1, 0x75, 0x46, 0x8f, 0xb5, 0xe6], [0x07, 0x56, 0xd8, 0xe0, 0x77, 0x47, 0x61, 0xd2], [0x43, 0x21, 0x93, 0xb7, 0x89, 0x51, 0xfc, 0x98]),
+		([0x43, 0x29, 0x7f, 0xad, 0x38, 0xe3, 0x73, 0xfe], [0x76, 0x25, 0x14, 0xb8, 0x29, 0xbf, 0x48, 0x6a], [0x13, 0xf0, 0x41, 0x54, 0xd6, 0x9d, 0x1a, 0xe5]),
+		([0x07, 0xa7, 0x13, 0x70, 0x45, 0xda, 0x2a, 0x16], [0x3b, 0xdd, 0x11, 0x90, 0x49, 0x37, 0x28, 0x02], [0x2e, 0xed, 0xda, 0x93, 0xff, 0xd3, 0x9c, 0x79]),
+		([0x04, 0x68, 0x91, 0x04, 0xc2, 0xfd, 0x3b, 0x2f], [0x26, 0x95, 0x5f, 0x68, 0x35, 0xaf, 0x60, 0x9a], [0xd8, 0x87, 0xe0, 0x39, 0x3c, 0x2d, 0xa6, 0xe3]),
+		([0x37, 0xd0, 0x6b, 0xb5, 0x16, 0xcb, 0x75, 0x46], [0x16, 0x4d, 0x5e, 0x40, 0x4f, 0x27, 0x52, 0x32], [0x5f, 0x99, 0xd0, 0x4f, 0x5b, 0x16, 0x39, 0x69]),
+		([0x1f, 0x08, 0x26, 0x0d, 0x1a, 0xc2, 0x46, 0x5e], [0x6b, 0x05, 0x6e, 0x18, 0x75, 0x9f, 0x5c, 0xca], [0x4a, 0x05, 0x7a, 0x3b, 0x24, 0xd3, 0x97, 0x7b]),
+		([0x58, 0x40, 0x23, 0x64, 0x1a, 0xba, 0x61, 0x76], [0x00, 0x4b, 0xd6, 0xef, 0x09, 0x17, 0x60, 0x62], [0x45, 0x20, 0x31, 0xc1, 0xe4, 0xfa, 0xda, 0x8e]),
+		([0x02, 0x58, 0x16, 0x16, 0x46, 0x29, 0xb0, 0x07], [0x48, 0x0d, 0x39, 0x00, 0x6e, 0xe7, 0x62, 0xf2], [0x75, 0x55, 0xae, 0x39, 0xf5, 0x9b, 0x87, 0xbd]),
+		([0x49, 0x79, 0x3e, 0xbc, 0x79, 0xb3, 0x25, 0x8f], [0x43, 0x75, 0x40, 0xc8, 0x69, 0x8f, 0x3c, 0xfa], [0x53, 0xc5, 0x5f, 0x9c, 0xb4, 0x9f, 0xc0, 0x19]),
+		([0x4f, 0xb0, 0x5e, 0x15, 0x15, 0xab, 0x73, 0xa7], [0x07, 0x2d, 0x43, 0xa0, 0x77, 0x07, 0x52, 0x92], [0x7a, 0x8e, 0x7b, 0xfa, 0x93, 0x7e, 0x89, 0xa3]),
+		([0x49, 0xe9, 0x5d, 0x6d, 0x4c, 0xa2, 0x29, 0xbf], [0x02, 0xfe, 0x55, 0x77, 0x81, 0x17, 0xf1, 0x2a], [0xcf, 0x9c, 0x5d, 0x7a, 0x49, 0x86, 0xad, 0xb5]),
+		([0x01, 0x83, 0x10, 0xdc, 0x40, 0x9b, 0x26, 0xd6], [0x1d, 0x9d, 0x5c, 0x50, 0x18, 0xf7, 0x28, 0xc2], [0xd1, 0xab, 0xb2, 0x90, 0x65, 0x8b, 0xc7, 0x78]),
+		([0x1c, 0x58, 0x7f, 0x1c, 0x13, 0x92, 0x4f, 0xef], [0x30, 0x55, 0x32, 0x28, 0x6d, 0x6f, 0x29, 0x5a], [0x55, 0xcb, 0x37, 0x74, 0xd1, 0x3e, 0xf2, 0x01]),
+		([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef], [0xfa, 0x34, 0xec, 0x48, 0x47, 0xb2, 0x68, 0xb2]),
+		([0x1f, 0x1f, 0x1f, 0x1f, 0x0e, 0x0e, 0x0e, 0x0e], [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef], [0xa7, 0x90, 0x79, 0x51, 0x08, 0xea, 0x3c, 0xae]),
+		([0xe0, 0xfe, 0xe0, 0xfe, 0xf1, 0xfe, 0xf1, 0xfe], [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef], [0xc3, 0x9e, 0x07, 0x2d, 0x9f, 0xac, 0x63, 0x1d]),
+		([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], [0x01, 0x49, 0x33, 0xe0, 0xcd, 0xaf, 0xf6, 0xe4]),
+		([0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0xf2, 0x1e, 0x9a, 0x77, 0xb7, 0x1c, 0x49, 0xbc]),
+		([0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef], [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x24, 0x59, 0x46, 0x88, 0x57, 0x54, 0x36, 0x9a]),
+		([0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10], [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], [0x6b, 0x5c, 0x5a, 0x9c, 0x5d, 0x9e, 0x0a, 0x5a]),
+	];
+
+	// Eric Young's set_key test: the prefixes of SET_KEY encrypt FEDCBA9876543210,
+	// continued past 24 bytes up to the reference limit of 72 bytes (libgcrypt)
+	const SET_KEY: [u8; 24] = [
+		0xf0, 0xe1, 0xd2, 0xc3, 0xb4, 0xa5, 0x96, 0x87, 0x78, 0x69, 0x5a, 0x4b,
+		0x3c, 0x2d, 0x1e, 0x0f, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+	];
+	const SET_KEY_VECTORS: [(usize, [u8; 8]); 27] = [
+		( 1, [0xf9, 0xad, 0x59, 0x7c, 0x49, 0xdb, 0x00, 0x5e]),
+		( 2, [0xe9, 0x1d, 0x21, 0xc1, 0xd9, 0x61, 0xa6, 0xd6]),
+		( 3, [0xe9, 0xc2, 0xb7, 0x0a, 0x1b, 0xc6, 0x5c, 0xf3]),
+		( 4, [0xbe, 0x1e, 0x63, 0x94, 0x08, 0x64, 0x0f, 0x05]),
+		( 5, [0xb3, 0x9e, 0x44, 0x48, 0x1b, 0xdb, 0x1e, 0x6e]),
+		( 6, [0x94, 0x57, 0xaa, 0x83, 0xb1, 0x92, 0x8c, 0x0d]),
+		( 7, [0x8b, 0xb7, 0x70, 0x32, 0xf9, 0x60, 0x62, 0x9d]),
+		( 8, [0xe8, 0x7a, 0x24, 0x4e, 0x2c, 0xc8, 0x5e, 0x82]),
+		( 9, [0x15, 0x75, 0x0e, 0x7a, 0x4f, 0x4e, 0xc5, 0x77]),
+		(10, [0x12, 0x2b, 0xa7, 0x0b, 0x3a, 0xb6, 0x4a, 0xe0]),
+		(11, [0x3a, 0x83, 0x3c, 0x9a, 0xff, 0xc5, 0x37, 0xf6]),
+		(12, [0x94, 0x09, 0xda, 0x87, 0xa9, 0x0f, 0x6b, 0xf2]),
+		(13, [0x88, 0x4f, 0x80, 0x62, 0x50, 0x60, 0xb8, 0xb4]),
+		(14, [0x1f, 0x85, 0x03, 0x1c, 0x19, 0xe1, 0x19, 0x68]),
+		(15, [0x79, 0xd9, 0x37, 0x3a, 0x71, 0x4c, 0xa3, 0x4f]),
+		(16, [0x93, 0x14, 0x28, 0x87, 0xee, 0x3b, 0xe1, 0x5c]),
+		(17, [0x03, 0x42, 0x9e, 0x83, 0x8c, 0xe2, 0xd1, 0x4b]),
+		(18, [0xa4, 0x29, 0x9e, 0x27, 0x46, 0x9f, 0xf6, 0x7b]),
+		(19, [0xaf, 0xd5, 0xae, 0xd1, 0xc1, 0xbc, 0x96, 0xa8]),
+		(20, [0x10, 0x85, 0x1c, 0x0e, 0x38, 0x58, 0xda, 0x9f]),
+		(21, [0xe6, 0xf5, 0x1e, 0xd7, 0x9b, 0x9d, 0xb2, 0x1f]),
+		(22, [0x64, 0xa6, 0xe1, 0x4a, 0xfd, 0x36, 0xb4, 0x6f]),
+		(23, [0x80, 0xc7, 0xd7, 0xd4, 0x5a, 0x54, 0x79, 0xad]),
+		(24, [0x05, 0x04, 0x4b, 0x62, 0xfa, 0x52, 0xd0, 0x80]),
+		(56, [0x99, 0x6c, 0x39, 0x73, 0x3a, 0x8d, 0xb8, 0xbc]),
+		(57, [0xdf, 0x57, 0xf0, 0x32, 0x7d, 0x41, 0xf6, 0x9a]),
+		(72, [0xa9, 0xcb, 0x81, 0x4c, 0x20, 0x9b, 0x70, 0x63]),
+	];
 	
 	#[test]
 	fn test_block() {
@@ -377,9 +402,9 @@ mod tests {
 		let expected = (0xdf333fd2, 0x30a71bb4);
 	
 	   let key = "TESTKEY".as_bytes();
-	   let bf = match new(&key) {
+	   let bf = match new(key) {
          Ok(v) => v,
-         Err(err) => panic!(err)
+         Err(err) => panic!("{}", err)
       };
 	
 		let _now = Instant::now();
@@ -405,15 +430,15 @@ mod tests {
 		let expected = vec![0x38, 0xed, 0x5c, 0x3f, 0x60, 0xb6, 0xa2, 0x52, 0x55, 0x89, 0xb0, 0x9d, 0xc, 0x36, 0xa0, 0xb7, 0xfd, 0x43, 0xdb, 0x72, 0xde, 0xd8, 0x27, 0x14, 0x8a, 0xb2, 0x8c, 0xdf, 0xd8, 0xe4, 0x83, 0x22, 0x55, 0xd6, 0xf1, 0x49, 0x14, 0x4b, 0x91, 0xbb];
 	
 	   let key = "TESTKEY".as_bytes();
-	   let bf = match new(&key) {
+	   let bf = match new(key) {
          Ok(v) => v,
-         Err(err) => panic!(err)
+         Err(err) => panic!("{}", err)
       };
 		
-		match bf.encrypt_ecb(&plain) {
+		match bf.encrypt_ecb(&plain, Padding::Legacy) {
 			Ok(encrypted) => {
 				assert_eq!(encrypted, expected);
-				match bf.decrypt_ecb(&encrypted) {
+				match bf.decrypt_ecb(&encrypted, Padding::Legacy) {
 					Ok(decrypted) => {
 						assert_eq!(decrypted, plain);
 					},
@@ -428,14 +453,14 @@ mod tests {
 	fn test_cbc() {
       let plain = "Włodzimierz Piotr Pszczółkowski".as_bytes().to_vec();
 		let key = "TESTKEY".as_bytes();
-		let bf = match new(&key) {
+		let bf = match new(key) {
          Ok(v) => v,
-         Err(err) => panic!(err)
+         Err(err) => panic!("{}", err)
       };
 		
-		match bf.encrypt_cbc(&plain) {
+		match bf.encrypt_cbc(&plain, Padding::Legacy) {
          Ok(encrypted) => {
-            match bf.decrypt_cbc(&encrypted) {
+            match bf.decrypt_cbc(&encrypted, Padding::Legacy) {
                Ok(decrypted) => {
                   assert_eq!(decrypted, plain);
                },
@@ -458,15 +483,15 @@ mod tests {
                           0x56, 0xa3, 0xd8, 0xc5, 0x29, 0xfd, 0xe9,
                           0xf7, 0xa7, 0xf7, 0x9a, 0x60, 0x5e];
 		let key = "TESTKEY".as_bytes();
-		let bf = match new(&key) {
+		let bf = match new(key) {
          Ok(v) => v,
-         Err(err) => panic!(err)
+         Err(err) => panic!("{}", err)
       };
 		
-		match bf.encrypt_cbc_iv(&plain, &iv) {
+		match bf.encrypt_cbc_iv(&plain, &iv, Padding::Legacy) {
          Ok(encrypted) => {
             assert_eq!(encrypted, expected);
-            match bf.decrypt_cbc(&encrypted) {
+            match bf.decrypt_cbc(&encrypted, Padding::Legacy) {
                Ok(decrypted) => {
                   assert_eq!(decrypted, plain);
                },
@@ -476,6 +501,127 @@ mod tests {
          Err(err) => panic!("CBC-IV encryption failed: {:?}", err)
       }
 	}
+
+	#[test]
+	fn test_block_cipher() {
+		let plain = [0x01u8, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00];
+		let expected = [0xd2u8, 0x3f, 0x33, 0xdf, 0xb4, 0x1b, 0xa7, 0x30];
+
+		let bf = <Blowfish as BlockCipher>::new(b"TESTKEY").unwrap();
+		let mut block = plain;
+		bf.encrypt_block(&mut block);
+		assert_eq!(block, expected);
+		bf.decrypt_block(&mut block);
+		assert_eq!(block, plain);
+
+		assert_eq!(new(&[0u8; 3]).err(), Some(Error::InvalidKeyLength{len: 3, min: 4, max: 56}));
+		assert!(<Blowfish as BlockCipher>::new(&[0u8; Blowfish::KEY_SIZE_MIN - 1]).is_err());
+		assert!(<Blowfish as BlockCipher>::new(&[0u8; Blowfish::KEY_SIZE_MAX + 1]).is_err());
+	}
+
+	#[test]
+	fn test_ecb_vectors() {
+		for (key, plain, expected) in ECB_VECTORS.iter() {
+			let bf = with_byte_order(key, ByteOrder::Big).unwrap();
+			let mut block = *plain;
+			bf.encrypt_block(&mut block);
+			assert_eq!(&block, expected);
+			bf.decrypt_block(&mut block);
+			assert_eq!(&block, plain);
+		}
+	}
+
+	#[test]
+	fn test_cbc_vector() {
+		// Eric Young's CBC test (openssl enc -bf-cbc -nopad)
+		let key = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xf0, 0xe1, 0xd2, 0xc3, 0xb4, 0xa5, 0x96, 0x87];
+		let iv = [0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10];
+		let plain = b"7654321 Now is the time for \0";
+		let expected = [
+			0x6b, 0x77, 0xb4, 0xd6, 0x30, 0x06, 0xde, 0xe6, 0x05, 0xb1, 0x56, 0xe2, 0x74, 0x03, 0x97, 0x93,
+			0x58, 0xde, 0xb9, 0xe7, 0x15, 0x46, 0x16, 0xd9, 0x59, 0xf1, 0x65, 0x2b, 0xd5, 0xff, 0x92, 0xcc,
+		];
+		let bf = with_byte_order(&key, ByteOrder::Big).unwrap();
+
+		let encrypted = bf.encrypt_cbc_iv(plain, &iv, Padding::Zero).unwrap();
+		assert_eq!(&encrypted[..8], &iv);
+		assert_eq!(&encrypted[8..], &expected[..]);
+		assert_eq!(bf.decrypt_cbc(&encrypted, Padding::Zero).unwrap(), &plain[..28]);
+	}
+
+	#[test]
+	fn test_byte_order() {
+		let key = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+		let le = new(&key).unwrap();
+		let be = with_byte_order(&key, ByteOrder::Big).unwrap();
+		assert_eq!(le.order, ByteOrder::Little);
+
+		// the same cipher, only words of the block are read differently
+		let mut x = [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11];
+		be.encrypt_block(&mut x);
+		assert_eq!(x, [0x61, 0xf9, 0xc3, 0x80, 0x22, 0x81, 0xb0, 0x96]);
+		let mut y = [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11];
+		le.encrypt_block(&mut y);
+		assert_eq!(y, [0x80, 0xc3, 0xf9, 0x61, 0x96, 0xb0, 0x81, 0x22]);
+	}
+
+	fn set_key(len: usize) -> Vec<u8> {
+		(0..len).map(|i| if i < SET_KEY.len() { SET_KEY[i] } else { (i * 5 + 1) as u8 }).collect()
+	}
+
+	#[test]
+	fn test_set_key_vectors() {
+		let plain = [0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10];
+		for (len, expected) in SET_KEY_VECTORS.iter() {
+			let bf = with_rounds(&set_key(*len), ByteOrder::Big, 16).unwrap();
+			let mut block = plain;
+			bf.encrypt_block(&mut block);
+			assert_eq!(&block, expected, "key length {}", len);
+			bf.decrypt_block(&mut block);
+			assert_eq!(block, plain);
+
+			if (KEY_SIZE_MIN..=KEY_SIZE_MAX).contains(len) {
+				let mut block = plain;
+				with_byte_order(&set_key(*len), ByteOrder::Big).unwrap().encrypt_block(&mut block);
+				assert_eq!(&block, expected);
+			}
+		}
+	}
+
+	#[test]
+	fn test_rounds() {
+		let key = set_key(16);
+		let plain = [0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10];
+		let bf = with_rounds(&key, ByteOrder::Big, 16).unwrap();
+		assert_eq!(bf.rounds, 16);
+
+		// the generic rounds give the same result as the unrolled ones
+		let x = bf.encrypt(0x01234567, 0x89abcdef);
+		assert_eq!(bf.encrypt_rounds(0x01234567, 0x89abcdef), x);
+		assert_eq!(bf.decrypt_rounds(x.0, x.1), (0x01234567, 0x89abcdef));
+
+		let mut blocks = Vec::new();
+		for &rounds in [ROUNDS_MIN, 2, 8, 15, 16, 20, 32, ROUNDS_MAX].iter() {
+			let bf = with_rounds(&key, ByteOrder::Big, rounds).unwrap();
+			assert_eq!(bf.rounds, rounds as usize);
+			let mut block = plain;
+			bf.encrypt_block(&mut block);
+			assert!(!blocks.contains(&block));
+			blocks.push(block);
+			bf.decrypt_block(&mut block);
+			assert_eq!(block, plain);
+		}
+	}
+
+	#[test]
+	fn test_with_rounds_errors() {
+		assert_eq!(with_rounds(&[], ByteOrder::Big, 16).err(), Some(Error::InvalidKeyLength{len: 0, min: 1, max: 72}));
+		assert_eq!(with_rounds(&[0u8; 73], ByteOrder::Big, 16).err(), Some(Error::InvalidKeyLength{len: 73, min: 1, max: 72}));
+		assert_eq!(with_rounds(&[0u8; 8], ByteOrder::Big, 0).err(), Some(Error::InvalidRounds{rounds: 0, min: 1, max: 64}));
+		assert_eq!(with_rounds(&[0u8; 8], ByteOrder::Big, 65).err(), Some(Error::InvalidRounds{rounds: 65, min: 1, max: 64}));
+		// the standard constructors keep the 32..448 bit limit
+		assert!(with_byte_order(&[0u8; 57], ByteOrder::Big).is_err());
+	}
 }
 
 const ORIG_P: [u32; 18] = [
@@ -0,0 +1,184 @@
+/*
+* Module: error
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use std::error;
+use std::fmt;
+
+/// Errors returned by the ciphers and modes of this crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+	/// The key is shorter than 'min' or longer than 'max' bytes.
+	InvalidKeyLength { len: usize, min: usize, max: usize },
+	/// The IV is not 'expected' bytes long.
+	InvalidIvLength { len: usize, expected: usize },
+	/// The plain text is not a whole number of blocks and padding is not used.
+	InvalidPlaintextLength { len: usize, block_size: usize },
+	/// The cipher text is too short or is not a whole number of blocks.
+	InvalidCiphertextLength { len: usize, block_size: usize },
+	/// The CTR counter is not 1..='block_size' bytes long.
+	InvalidCounterSize { size: usize, block_size: usize },
+	/// The CFB segment is not 1..='block_size' bytes long.
+	InvalidSegmentSize { size: usize, block_size: usize },
+	/// The mode doesn't support ciphers with blocks of 'size' bytes.
+	UnsupportedBlockSize { size: usize },
+	/// The authentication tag is shorter than 'min' or longer than 'max' bytes.
+	InvalidTagLength { len: usize, min: usize, max: usize },
+	/// The message is longer than 'max' bytes allowed by the mode.
+	MessageTooLong { len: u64, max: u64 },
+	/// The authentication tag (MAC) doesn't match the data.
+	AuthenticationFailed,
+	/// The S-box table contains a value bigger than 15.
+	InvalidSBox,
+	/// The wrapped key is not 'expected' bytes long.
+	InvalidWrappedKeyLength { len: usize, expected: usize },
+	/// The salt is not 'expected' bytes long.
+	InvalidSaltLength { len: usize, expected: usize },
+	/// The password is longer than 'max' bytes.
+	InvalidPasswordLength { len: usize, max: usize },
+	/// The cost (work factor) is smaller than 'min' or bigger than 'max'.
+	InvalidCost { cost: u32, min: u32, max: u32 },
+	/// The number of rounds is smaller than 'min' or bigger than 'max'
+	/// (u32::MAX when unbounded).
+	InvalidRounds { rounds: u32, min: u32, max: u32 },
+	/// The password hash string is malformed.
+	InvalidHashString,
+	/// The padding of the decrypted data is malformed.
+	InvalidPadding,
+	/// There is nothing to encrypt or decrypt.
+	EmptyInput,
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::InvalidKeyLength { len, min, max } => {
+				if min == max {
+					write!(f, "invalid key length {} (expected {} bytes)", len, min)
+				} else {
+					write!(f, "invalid key length {} (expected {}..{} bytes)", len, min, max)
+				}
+			},
+			Error::InvalidIvLength { len, expected } =>
+				write!(f, "invalid IV length {} (expected {} bytes)", len, expected),
+			Error::InvalidPlaintextLength { len, block_size } =>
+				write!(f, "invalid plain text length {} (block size is {} bytes)", len, block_size),
+			Error::InvalidCiphertextLength { len, block_size } =>
+				write!(f, "invalid cipher text length {} (block size is {} bytes)", len, block_size),
+			Error::InvalidCounterSize { size, block_size } =>
+				write!(f, "invalid counter size {} (expected 1..{} bytes)", size, block_size),
+			Error::InvalidSegmentSize { size, block_size } =>
+				write!(f, "invalid segment size {} (expected 1..{} bytes)", size, block_size),
+			Error::UnsupportedBlockSize { size } =>
+				write!(f, "unsupported block size {} bytes", size),
+			Error::InvalidTagLength { len, min, max } => {
+				if min == max {
+					write!(f, "invalid tag length {} (expected {} bytes)", len, min)
+				} else {
+					write!(f, "invalid tag length {} (expected {}..{} bytes)", len, min, max)
+				}
+			},
+			Error::MessageTooLong { len, max } =>
+				write!(f, "invalid message length {} (expected at most {} bytes)", len, max),
+			Error::AuthenticationFailed =>
+				write!(f, "authentication failed"),
+			Error::InvalidSBox =>
+				write!(f, "invalid S-box"),
+			Error::InvalidWrappedKeyLength { len, expected } =>
+				write!(f, "invalid wrapped key length {} (expected {} bytes)", len, expected),
+			Error::InvalidSaltLength { len, expected } =>
+				write!(f, "invalid salt length {} (expected {} bytes)", len, expected),
+			Error::InvalidPasswordLength { len, max } =>
+				write!(f, "invalid password length {} (expected at most {} bytes)", len, max),
+			Error::InvalidCost { cost, min, max } =>
+				write!(f, "invalid cost {} (expected {}..{})", cost, min, max),
+			Error::InvalidRounds { rounds, min, max } if max == u32::MAX =>
+				write!(f, "invalid number of rounds {} (expected at least {})", rounds, min),
+			Error::InvalidRounds { rounds, min, max } =>
+				write!(f, "invalid number of rounds {} (expected {}..{})", rounds, min, max),
+			Error::InvalidHashString =>
+				write!(f, "invalid hash string"),
+			Error::InvalidPadding =>
+				write!(f, "invalid padding"),
+			Error::EmptyInput =>
+				write!(f, "empty input"),
+		}
+	}
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_display() {
+		let err = Error::InvalidKeyLength { len: 3, min: 4, max: 56 };
+		assert_eq!(err.to_string(), "invalid key length 3 (expected 4..56 bytes)");
+		let err = Error::InvalidKeyLength { len: 3, min: 32, max: 32 };
+		assert_eq!(err.to_string(), "invalid key length 3 (expected 32 bytes)");
+		let err = Error::InvalidIvLength { len: 7, expected: 8 };
+		assert_eq!(err.to_string(), "invalid IV length 7 (expected 8 bytes)");
+		let err = Error::InvalidPlaintextLength { len: 9, block_size: 8 };
+		assert_eq!(err.to_string(), "invalid plain text length 9 (block size is 8 bytes)");
+		let err = Error::InvalidCiphertextLength { len: 9, block_size: 8 };
+		assert_eq!(err.to_string(), "invalid cipher text length 9 (block size is 8 bytes)");
+		let err = Error::InvalidCounterSize { size: 0, block_size: 16 };
+		assert_eq!(err.to_string(), "invalid counter size 0 (expected 1..16 bytes)");
+		let err = Error::InvalidSegmentSize { size: 9, block_size: 8 };
+		assert_eq!(err.to_string(), "invalid segment size 9 (expected 1..8 bytes)");
+		let err = Error::UnsupportedBlockSize { size: 12 };
+		assert_eq!(err.to_string(), "unsupported block size 12 bytes");
+		let err = Error::InvalidTagLength { len: 3, min: 4, max: 8 };
+		assert_eq!(err.to_string(), "invalid tag length 3 (expected 4..8 bytes)");
+		let err = Error::MessageTooLong { len: 68719476705, max: 68719476704 };
+		assert_eq!(err.to_string(), "invalid message length 68719476705 (expected at most 68719476704 bytes)");
+		assert_eq!(Error::AuthenticationFailed.to_string(), "authentication failed");
+		assert_eq!(Error::InvalidSBox.to_string(), "invalid S-box");
+		let err = Error::InvalidWrappedKeyLength { len: 43, expected: 44 };
+		assert_eq!(err.to_string(), "invalid wrapped key length 43 (expected 44 bytes)");
+		let err = Error::InvalidSaltLength { len: 15, expected: 16 };
+		assert_eq!(err.to_string(), "invalid salt length 15 (expected 16 bytes)");
+		let err = Error::InvalidPasswordLength { len: 73, max: 72 };
+		assert_eq!(err.to_string(), "invalid password length 73 (expected at most 72 bytes)");
+		let err = Error::InvalidCost { cost: 3, min: 4, max: 31 };
+		assert_eq!(err.to_string(), "invalid cost 3 (expected 4..31)");
+		let err = Error::InvalidRounds { rounds: 0, min: 1, max: u32::MAX };
+		assert_eq!(err.to_string(), "invalid number of rounds 0 (expected at least 1)");
+		let err = Error::InvalidRounds { rounds: 65, min: 1, max: 64 };
+		assert_eq!(err.to_string(), "invalid number of rounds 65 (expected 1..64)");
+		assert_eq!(Error::InvalidHashString.to_string(), "invalid hash string");
+		assert_eq!(Error::InvalidPadding.to_string(), "invalid padding");
+		assert_eq!(Error::EmptyInput.to_string(), "empty input");
+
+		let err: Box<dyn error::Error> = Box::new(Error::EmptyInput);
+		assert_eq!(err.to_string(), "empty input");
+	}
+}
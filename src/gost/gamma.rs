@@ -0,0 +1,361 @@
+/*
+* Module: gost::gamma
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use bytes2block;
+use words2bytes;
+use BlockCipher;
+use Error;
+use super::{Gost, BLOCK_SIZE, KEY_SIZE, key_words};
+
+/// Constant added to N3 (modulo 2^32).
+const C1: u32 = 0x01010101;
+/// Constant added to N4 (modulo 2^32 - 1).
+const C2: u32 = 0x01010104;
+
+/// Number of bytes encrypted with one key when key meshing is on.
+const MESHING_PERIOD: usize = 1024;
+
+/// Constant key C of the CryptoPro key meshing (RFC 4357, 2.3.2).
+const MESHING_KEY: [u8; KEY_SIZE] = [
+	0x69, 0x00, 0x72, 0x22, 0x64, 0xc9, 0x04, 0x23, 0x8d, 0x3a, 0xdb, 0x96, 0x46, 0xe9, 0x2a, 0xc4,
+	0x18, 0xfe, 0xac, 0x94, 0x00, 0xed, 0x07, 0x12, 0xc0, 0x86, 0xdc, 0xc2, 0xef, 0x4c, 0xa9, 0x2b,
+];
+
+/// CryptoPro key meshing (RFC 4357, 2.3.2): the new key is the decryption
+/// of MESHING_KEY with the current key, 'iv' is encrypted with the new key.
+fn key_meshing(gost: &mut Gost, iv: &mut [u8]) {
+	let mut key = MESHING_KEY;
+	for block in key.chunks_mut(BLOCK_SIZE) {
+		gost.decrypt_block(block);
+	}
+	gost.k = key_words(&key);
+	gost.encrypt_block(iv);
+}
+
+/// GOST 28147-89 gamma (counter mode) keystream.
+/// The synchro-message (IV) is encrypted first, the result is the initial
+/// state of the counter (N3, N4). Every block of the gamma is the encryption
+/// of the counter after adding C1 to N3 and C2 to N4.
+/// With key meshing the key and the counter are changed every 1024 bytes.
+/// Compatible with OpenSSL's gost engine (gost89-cnt) and GnuPG.
+pub struct Gamma {
+	gost: Gost,
+	n3: u32,
+	n4: u32,
+	block: [u8; BLOCK_SIZE],
+	pos: usize,
+	meshing: bool,
+	count: usize,
+}
+
+impl Gamma {
+	/// Creates gamma generator for 'gost' with the synchro-message 'iv'.
+	pub fn new(gost: &Gost, iv: &[u8]) -> Result<Gamma, Error> {
+		Gamma::init(gost, iv, false)
+	}
+
+	/// Creates gamma generator for 'gost' with the synchro-message 'iv'
+	/// and CryptoPro key meshing.
+	pub fn with_key_meshing(gost: &Gost, iv: &[u8]) -> Result<Gamma, Error> {
+		Gamma::init(gost, iv, true)
+	}
+
+	fn init(gost: &Gost, iv: &[u8], meshing: bool) -> Result<Gamma, Error> {
+		if iv.len() != BLOCK_SIZE {
+			return Err(Error::InvalidIvLength{len: iv.len(), expected: BLOCK_SIZE})
+		}
+
+		let mut block = [0u8; BLOCK_SIZE];
+		block.copy_from_slice(iv);
+		gost.encrypt_block(&mut block);
+		let (n3, n4) = bytes2block(&block);
+		Ok(Gamma{gost: gost.clone(), n3, n4, block, pos: BLOCK_SIZE, meshing, count: 0})
+	}
+
+	/// xors 'data' in place with next bytes of the gamma (encrypts or decrypts).
+	pub fn apply_keystream(&mut self, data: &mut [u8]) {
+		for b in data.iter_mut() {
+			if self.pos == BLOCK_SIZE {
+				self.next_block();
+			}
+			*b ^= self.block[self.pos];
+			self.pos += 1;
+		}
+	}
+
+	fn next_block(&mut self) {
+		if self.meshing && self.count == MESHING_PERIOD {
+			words2bytes(self.n3, self.n4, &mut self.block);
+			key_meshing(&mut self.gost, &mut self.block);
+			let (n3, n4) = bytes2block(&self.block);
+			self.n3 = n3;
+			self.n4 = n4;
+			self.count = 0;
+		}
+
+		self.n3 = self.n3.wrapping_add(C1);
+		let n4 = self.n4.wrapping_add(C2);
+		// addition modulo 2^32 - 1
+		self.n4 = if n4 < self.n4 { n4.wrapping_add(1) } else { n4 };
+
+		words2bytes(self.n3, self.n4, &mut self.block);
+		self.gost.encrypt_block(&mut self.block);
+		self.pos = 0;
+		self.count += BLOCK_SIZE;
+	}
+}
+
+/// GOST 28147-89 gamma with feedback (CFB-64).
+/// Every block of the gamma is the encryption of the previous block
+/// of the cipher text (the synchro-message for the first block).
+/// With key meshing the key and the feedback are changed every 1024 bytes.
+/// Compatible with OpenSSL's gost engine (gost89) and libgcrypt.
+pub struct GammaFeedback {
+	gost: Gost,
+	register: [u8; BLOCK_SIZE],
+	block: [u8; BLOCK_SIZE],
+	pos: usize,
+	meshing: bool,
+	count: usize,
+}
+
+impl GammaFeedback {
+	/// Creates gamma with feedback for 'gost' with the synchro-message 'iv'.
+	pub fn new(gost: &Gost, iv: &[u8]) -> Result<GammaFeedback, Error> {
+		GammaFeedback::init(gost, iv, false)
+	}
+
+	/// Creates gamma with feedback for 'gost' with the synchro-message 'iv'
+	/// and CryptoPro key meshing.
+	pub fn with_key_meshing(gost: &Gost, iv: &[u8]) -> Result<GammaFeedback, Error> {
+		GammaFeedback::init(gost, iv, true)
+	}
+
+	fn init(gost: &Gost, iv: &[u8], meshing: bool) -> Result<GammaFeedback, Error> {
+		if iv.len() != BLOCK_SIZE {
+			return Err(Error::InvalidIvLength{len: iv.len(), expected: BLOCK_SIZE})
+		}
+
+		let mut register = [0u8; BLOCK_SIZE];
+		register.copy_from_slice(iv);
+		Ok(GammaFeedback{gost: gost.clone(), register, block: [0u8; BLOCK_SIZE], pos: 0, meshing, count: 0})
+	}
+
+	/// Encrypts 'data' in place.
+	pub fn encrypt(&mut self, data: &mut [u8]) {
+		for b in data.iter_mut() {
+			let c = *b ^ self.next_byte();
+			self.feed(c);
+			*b = c;
+		}
+	}
+
+	/// Decrypts 'data' in place.
+	pub fn decrypt(&mut self, data: &mut [u8]) {
+		for b in data.iter_mut() {
+			let c = *b;
+			*b ^= self.next_byte();
+			self.feed(c);
+		}
+	}
+
+	fn next_byte(&mut self) -> u8 {
+		if self.pos == 0 {
+			if self.meshing && self.count == MESHING_PERIOD {
+				key_meshing(&mut self.gost, &mut self.register);
+				self.count = 0;
+			}
+			self.block = self.register;
+			self.gost.encrypt_block(&mut self.block);
+			self.count += BLOCK_SIZE;
+		}
+		self.block[self.pos]
+	}
+
+	fn feed(&mut self, c: u8) {
+		self.register[self.pos] = c;
+		self.pos = (self.pos + 1) % BLOCK_SIZE;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use gost::{new, SBox};
+
+	const IV: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+	fn key() -> Vec<u8> {
+		(0..32).collect()
+	}
+
+	#[test]
+	fn test_gamma() {
+		let plain = "Yamato & Musashi, Artur, Błazej, Jolanta".as_bytes();
+		let expected = [
+			0xfb, 0x15, 0x90, 0x88, 0x11, 0x1a, 0x4d, 0xee, 0x72, 0x5e, 0x08, 0xe4, 0x5b, 0x31, 0x3a, 0x22,
+			0x28, 0xb4, 0x15, 0xe3, 0xb3, 0xbd, 0x29, 0x19, 0x90, 0x57, 0xd0, 0xe3, 0xb9, 0x3e, 0x73, 0xe6,
+			0xf7, 0x94, 0xd7, 0x7e, 0x91, 0x97, 0x6e, 0x89, 0xc6,
+		];
+		let gt = new(&key(), SBox::Default).unwrap();
+
+		let encrypted = gt.encrypt_gamma_iv(plain, &IV).unwrap();
+		assert_eq!(&encrypted[..8], &IV);
+		assert_eq!(&encrypted[8..], &expected[..]);
+		assert_eq!(gt.decrypt_gamma(&encrypted).unwrap(), plain);
+
+		// the same gamma for data passed in pieces
+		for piece in 1..10 {
+			let mut data = plain.to_vec();
+			let mut gamma = Gamma::new(&gt, &IV).unwrap();
+			for chunk in data.chunks_mut(piece) {
+				gamma.apply_keystream(chunk);
+			}
+			assert_eq!(&data[..], &expected[..]);
+		}
+
+		let encrypted = gt.encrypt_gamma(plain).unwrap();
+		assert_eq!(gt.decrypt_gamma(&encrypted).unwrap(), plain);
+	}
+
+	#[test]
+	fn test_gamma_feedback() {
+		let plain = "Yamato & Musashi, Artur, Błazej, Jolanta".as_bytes();
+		let expected = [
+			0x7d, 0xa1, 0x47, 0x36, 0xa5, 0xcd, 0x39, 0xe4, 0x0f, 0xce, 0x9d, 0x10, 0x91, 0x73, 0x65, 0x1e,
+			0xfe, 0xeb, 0x5e, 0x96, 0x12, 0x38, 0x9a, 0xa7, 0x86, 0x98, 0x3f, 0x9e, 0x99, 0x79, 0x79, 0x65,
+			0xe8, 0xa3, 0x27, 0xdb, 0xa7, 0xe6, 0xc7, 0xab, 0x1e,
+		];
+		let gt = new(&key(), SBox::Default).unwrap();
+
+		let encrypted = gt.encrypt_cfb_iv(plain, &IV).unwrap();
+		assert_eq!(&encrypted[8..], &expected[..]);
+		assert_eq!(gt.decrypt_cfb(&encrypted).unwrap(), plain);
+
+		let encrypted = gt.encrypt_cfb(plain).unwrap();
+		assert_eq!(gt.decrypt_cfb(&encrypted).unwrap(), plain);
+	}
+
+	#[test]
+	fn test_test_parameter_set() {
+		// gamma of the OpenSSL gost engine (gost_cnt_next), gamma with feedback
+		// verified with libgcrypt (GCRY_CIPHER_MODE_CFB)
+		let gamma = [
+			0xf7, 0x75, 0x35, 0xf1, 0x56, 0x9d, 0xa0, 0xb3, 0xb8, 0x6c, 0xa6, 0x76, 0x0e, 0x14, 0xf3, 0x40,
+			0xdb, 0xad, 0xf1, 0x9a, 0x6f, 0xb3, 0xa2, 0x7c, 0x94, 0xc4, 0x21, 0x23, 0x21, 0x14, 0x59, 0x59,
+			0x22, 0x8f, 0x59, 0x11, 0x39, 0xe8, 0xd3, 0xf1,
+		];
+		let gamma_feedback = [
+			0x1b, 0xe9, 0x64, 0x89, 0xdc, 0x52, 0x8c, 0x43, 0x02, 0xcd, 0x27, 0x89, 0x68, 0x71, 0xed, 0xd2,
+			0x54, 0xd7, 0xc8, 0x80, 0x57, 0xab, 0xa8, 0x44, 0x09, 0xf6, 0x98, 0x5c, 0x1c, 0xcd, 0x13, 0x78,
+			0xb7, 0x6d, 0x86, 0x1c, 0x5a, 0xd4, 0x34, 0xaf,
+		];
+		let gt = new(&key(), SBox::Test).unwrap();
+		let zeros = [0u8; 40];
+
+		assert_eq!(&gt.encrypt_gamma_iv(&zeros, &IV).unwrap()[8..], &gamma[..]);
+		assert_eq!(&gt.encrypt_cfb_iv(&zeros, &IV).unwrap()[8..], &gamma_feedback[..]);
+	}
+
+	#[test]
+	fn test_key_meshing() {
+		// gamma of the OpenSSL gost engine (gost_cnt_next) with key meshing,
+		// gamma with feedback verified with libgcrypt (GCRY_CIPHER_GOST28147_MESH)
+		let gamma = [
+			0x87, 0xe4, 0xd9, 0xc2, 0xce, 0x61, 0x4f, 0xbe, 0x59, 0x1a, 0x36, 0xef, 0xcf, 0x65, 0x0c, 0xa8,
+			0x27, 0xd3, 0x2e, 0x1f, 0xef, 0x83, 0x64, 0x08,
+		];
+		let gamma_end = [
+			0x30, 0x1f, 0x62, 0x84, 0xa4, 0x1b, 0x7d, 0x2b, 0xe0, 0x3e, 0x99, 0x38, 0x27, 0x18, 0xa5, 0x34,
+		];
+		let gamma_feedback = [
+			0xc5, 0x06, 0x7d, 0x6d, 0x1f, 0x0b, 0xe0, 0x38, 0xc9, 0x65, 0x46, 0x8d, 0xf3, 0x1e, 0xf0, 0xbf,
+			0x28, 0x33, 0xec, 0x1b, 0x7d, 0xd6, 0x2c, 0xb5,
+		];
+		let gamma_feedback_end = [
+			0xd7, 0x2b, 0x13, 0x73, 0xe6, 0xa5, 0x34, 0x43, 0x9a, 0x62, 0x21, 0x71, 0xf4, 0x8f, 0x5f, 0xb6,
+		];
+		let gt = new(&key(), SBox::CryptoProA).unwrap();
+		let n = 2600;
+
+		let mut data = vec![0u8; n];
+		Gamma::with_key_meshing(&gt, &IV).unwrap().apply_keystream(&mut data);
+		assert_eq!(&data[1016..1040], &gamma[..]);
+		assert_eq!(&data[(n - 16)..], &gamma_end[..]);
+
+		// key meshing doesn't change the first 1024 bytes
+		let mut plain = vec![0u8; n];
+		Gamma::new(&gt, &IV).unwrap().apply_keystream(&mut plain);
+		assert_eq!(&plain[..1024], &data[..1024]);
+		assert!(plain[1024..] != data[1024..]);
+
+		let mut data = vec![0u8; n];
+		let mut gamma = GammaFeedback::with_key_meshing(&gt, &IV).unwrap();
+		for chunk in data.chunks_mut(100) {
+			gamma.encrypt(chunk);
+		}
+		assert_eq!(&data[1016..1040], &gamma_feedback[..]);
+		assert_eq!(&data[(n - 16)..], &gamma_feedback_end[..]);
+
+		let mut gamma = GammaFeedback::with_key_meshing(&gt, &IV).unwrap();
+		for chunk in data.chunks_mut(7) {
+			gamma.decrypt(chunk);
+		}
+		assert_eq!(data, vec![0u8; n]);
+	}
+
+	#[test]
+	fn test_counter() {
+		let gt = new(&key(), SBox::Default).unwrap();
+		let mut gamma = Gamma::new(&gt, &IV).unwrap();
+
+		gamma.n3 = 0xfefefeff;
+		gamma.n4 = 0xfefefefb;
+		gamma.next_block();
+		assert_eq!((gamma.n3, gamma.n4), (0, 0xffffffff));
+
+		// N4 is added modulo 2^32 - 1
+		gamma.n4 = 0xfefefefc;
+		gamma.next_block();
+		assert_eq!((gamma.n3, gamma.n4), (C1, 1));
+		gamma.next_block();
+		assert_eq!((gamma.n3, gamma.n4), (2 * C1, 1 + C2));
+	}
+
+	#[test]
+	fn test_errors() {
+		let gt = new(&key(), SBox::Default).unwrap();
+
+		assert!(Gamma::new(&gt, &IV[..7]).is_err());
+		assert_eq!(gt.encrypt_gamma(&[]), Err(Error::EmptyInput));
+		assert_eq!(gt.decrypt_gamma(&IV),
+		           Err(Error::InvalidCiphertextLength{len: 8, block_size: 8}));
+	}
+}
@@ -0,0 +1,222 @@
+/*
+* Module: gost::keywrap
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use BlockCipher;
+use Error;
+use super::{new, GammaFeedback, Mac, SBox, BLOCK_SIZE, KEY_SIZE};
+
+/// Size of the user keying material (UKM).
+const UKM_SIZE: usize = 8;
+/// Size of the MAC of the wrapped key.
+const MAC_SIZE: usize = 4;
+/// Size of the wrapped key: UKM, encrypted key and MAC.
+pub const WRAPPED_KEY_SIZE: usize = UKM_SIZE + KEY_SIZE + MAC_SIZE;
+
+/// GOST 28147-89 key wrap (RFC 4357, 6.1).
+/// Encrypts content encryption key 'cek' with key encryption key 'kek' in ECB mode.
+/// The result is 'ukm', the encrypted key and 4 bytes MAC of 'cek' computed
+/// with 'kek' and 'ukm' as the synchro-message.
+pub fn wrap_key(kek: &[u8], ukm: &[u8], cek: &[u8], sbox: SBox) -> Result<Vec<u8>, Error> {
+	// handle caller mistakes
+	if ukm.len() != UKM_SIZE { return Err(Error::InvalidIvLength{len: ukm.len(), expected: UKM_SIZE}) }
+	if cek.len() != KEY_SIZE {
+		return Err(Error::InvalidKeyLength{len: cek.len(), min: KEY_SIZE, max: KEY_SIZE})
+	}
+	let gost = new(kek, sbox)?;
+
+	let mut buffer = ukm.to_vec();
+	buffer.extend_from_slice(cek);
+	for block in buffer[UKM_SIZE..].chunks_mut(BLOCK_SIZE) {
+		gost.encrypt_block(block);
+	}
+	let mut mac = Mac::with_iv(&gost, ukm, MAC_SIZE)?;
+	mac.update(cek);
+	buffer.extend(mac.finalize());
+	Ok(buffer)
+}
+
+/// GOST 28147-89 key unwrap (RFC 4357, 6.2).
+/// Decrypts the key wrapped by wrap_key and checks its MAC.
+pub fn unwrap_key(kek: &[u8], wrapped: &[u8], sbox: SBox) -> Result<Vec<u8>, Error> {
+	if wrapped.len() != WRAPPED_KEY_SIZE {
+		return Err(Error::InvalidWrappedKeyLength{len: wrapped.len(), expected: WRAPPED_KEY_SIZE})
+	}
+	let gost = new(kek, sbox)?;
+	let ukm = &wrapped[..UKM_SIZE];
+
+	let mut cek = wrapped[UKM_SIZE..(UKM_SIZE + KEY_SIZE)].to_vec();
+	for block in cek.chunks_mut(BLOCK_SIZE) {
+		gost.decrypt_block(block);
+	}
+	let mut mac = Mac::with_iv(&gost, ukm, MAC_SIZE)?;
+	mac.update(&cek);
+	mac.verify(&wrapped[(UKM_SIZE + KEY_SIZE)..])?;
+	Ok(cek)
+}
+
+/// CryptoPro key wrap (RFC 4357, 6.3).
+/// The same as wrap_key, but 'kek' is diversified with 'ukm' first.
+pub fn cryptopro_wrap_key(kek: &[u8], ukm: &[u8], cek: &[u8], sbox: SBox) -> Result<Vec<u8>, Error> {
+	let kek = cryptopro_kek_diversify(kek, ukm, sbox)?;
+	wrap_key(&kek, ukm, cek, sbox)
+}
+
+/// CryptoPro key unwrap (RFC 4357, 6.4).
+/// Decrypts the key wrapped by cryptopro_wrap_key and checks its MAC.
+pub fn cryptopro_unwrap_key(kek: &[u8], wrapped: &[u8], sbox: SBox) -> Result<Vec<u8>, Error> {
+	if wrapped.len() != WRAPPED_KEY_SIZE {
+		return Err(Error::InvalidWrappedKeyLength{len: wrapped.len(), expected: WRAPPED_KEY_SIZE})
+	}
+	let kek = cryptopro_kek_diversify(kek, &wrapped[..UKM_SIZE], sbox)?;
+	unwrap_key(&kek, wrapped, sbox)
+}
+
+/// CryptoPro KEK diversification (RFC 4357, 6.5).
+/// In each of 8 steps the key is encrypted with itself in gamma with feedback
+/// mode, the synchro-message is the sum of key words selected by bits
+/// of the next byte of 'ukm' followed by the sum of the other words.
+pub fn cryptopro_kek_diversify(kek: &[u8], ukm: &[u8], sbox: SBox) -> Result<Vec<u8>, Error> {
+	if ukm.len() != UKM_SIZE { return Err(Error::InvalidIvLength{len: ukm.len(), expected: UKM_SIZE}) }
+	// checks the key and the S-box
+	new(kek, sbox)?;
+	let mut key = kek.to_vec();
+
+	for a in ukm {
+		let mut s1 = 0u32;
+		let mut s2 = 0u32;
+		for (j, w) in key.chunks(4).enumerate() {
+			let k = u32::from(w[0]) | u32::from(w[1]) << 8 | u32::from(w[2]) << 16 | u32::from(w[3]) << 24;
+			if (a >> j) & 1 == 1 {
+				s1 = s1.wrapping_add(k);
+			} else {
+				s2 = s2.wrapping_add(k);
+			}
+		}
+		let mut iv = [0u8; BLOCK_SIZE];
+		iv[..4].copy_from_slice(&s1.to_le_bytes());
+		iv[4..].copy_from_slice(&s2.to_le_bytes());
+
+		let gost = new(&key, sbox)?;
+		GammaFeedback::new(&gost, &iv)?.encrypt(&mut key);
+	}
+	Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const UKM: [u8; 8] = [0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0];
+
+	fn keys() -> (Vec<u8>, Vec<u8>) {
+		let kek = (0..32).collect();
+		let cek = (0..32).map(|i| 0xff - i).collect();
+		(kek, cek)
+	}
+
+	// Expected values were computed from RFC 4357 with libgcrypt
+	// ECB, CFB and MAC (GCRY_MAC_GOST28147_IMIT) primitives.
+
+	#[test]
+	fn test_wrap_key() {
+		let expected = [
+			0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0xbd, 0x09, 0x28, 0x77, 0x25, 0x5a, 0x1c, 0x3b,
+			0xe8, 0x20, 0x7f, 0xcf, 0xa5, 0x43, 0x45, 0xc7, 0x65, 0x59, 0xb4, 0x87, 0x9b, 0x3d, 0xfa, 0x88,
+			0x0a, 0x70, 0x23, 0x55, 0x9e, 0xaa, 0x76, 0x3d, 0xb1, 0x36, 0x42, 0x77,
+		];
+		let (kek, cek) = keys();
+
+		let wrapped = wrap_key(&kek, &UKM, &cek, SBox::CryptoProA).unwrap();
+		assert_eq!(&wrapped[..], &expected[..]);
+		assert_eq!(unwrap_key(&kek, &wrapped, SBox::CryptoProA).unwrap(), cek);
+	}
+
+	#[test]
+	fn test_cryptopro_wrap_key() {
+		let diversified = [
+			0x28, 0xf3, 0xbf, 0x25, 0x7d, 0x6b, 0x6f, 0x60, 0x29, 0xa5, 0xfa, 0xae, 0xad, 0xed, 0xf4, 0xf5,
+			0x00, 0xaa, 0x3e, 0xeb, 0xed, 0x2e, 0x77, 0xa4, 0x6b, 0x20, 0x4c, 0xbb, 0x37, 0xce, 0x63, 0x0a,
+		];
+		let expected = [
+			0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x8e, 0x01, 0x8a, 0x03, 0x52, 0x33, 0x10, 0x15,
+			0x7f, 0xc8, 0xaf, 0xa6, 0xdd, 0xdc, 0x43, 0x2b, 0x63, 0x17, 0xb9, 0x1c, 0x80, 0x50, 0xc3, 0x7a,
+			0x20, 0x37, 0x63, 0xd8, 0xbf, 0xe7, 0x78, 0xe2, 0xa3, 0x24, 0xb3, 0x13,
+		];
+		let (kek, cek) = keys();
+
+		assert_eq!(cryptopro_kek_diversify(&kek, &UKM, SBox::CryptoProA).unwrap(), &diversified[..]);
+		let wrapped = cryptopro_wrap_key(&kek, &UKM, &cek, SBox::CryptoProA).unwrap();
+		assert_eq!(&wrapped[..], &expected[..]);
+		assert_eq!(cryptopro_unwrap_key(&kek, &wrapped, SBox::CryptoProA).unwrap(), cek);
+	}
+
+	#[test]
+	fn test_tampered() {
+		let (kek, cek) = keys();
+
+		for sbox in &[SBox::Default, SBox::CryptoProA, SBox::Tc26Z] {
+			let wrapped = wrap_key(&kek, &UKM, &cek, *sbox).unwrap();
+			let cp_wrapped = cryptopro_wrap_key(&kek, &UKM, &cek, *sbox).unwrap();
+			for i in 0..WRAPPED_KEY_SIZE {
+				let mut tampered = wrapped.clone();
+				tampered[i] ^= 0x80;
+				assert_eq!(unwrap_key(&kek, &tampered, *sbox), Err(Error::AuthenticationFailed));
+
+				let mut tampered = cp_wrapped.clone();
+				tampered[i] ^= 0x01;
+				assert_eq!(cryptopro_unwrap_key(&kek, &tampered, *sbox), Err(Error::AuthenticationFailed));
+			}
+
+			// wrong KEK
+			let mut other = kek.clone();
+			other[0] ^= 1;
+			assert_eq!(unwrap_key(&other, &wrapped, *sbox), Err(Error::AuthenticationFailed));
+			assert_eq!(cryptopro_unwrap_key(&other, &cp_wrapped, *sbox), Err(Error::AuthenticationFailed));
+		}
+	}
+
+	#[test]
+	fn test_errors() {
+		let (kek, cek) = keys();
+		let sbox = SBox::CryptoProA;
+
+		assert_eq!(wrap_key(&kek, &UKM[1..], &cek, sbox), Err(Error::InvalidIvLength{len: 7, expected: 8}));
+		assert_eq!(wrap_key(&kek, &UKM, &cek[1..], sbox),
+		           Err(Error::InvalidKeyLength{len: 31, min: 32, max: 32}));
+		assert_eq!(wrap_key(&kek[1..], &UKM, &cek, sbox),
+		           Err(Error::InvalidKeyLength{len: 31, min: 32, max: 32}));
+		assert_eq!(cryptopro_wrap_key(&kek[1..], &UKM, &cek, sbox),
+		           Err(Error::InvalidKeyLength{len: 31, min: 32, max: 32}));
+		assert_eq!(unwrap_key(&kek, &[0u8; 43], sbox),
+		           Err(Error::InvalidWrappedKeyLength{len: 43, expected: 44}));
+		assert_eq!(cryptopro_unwrap_key(&kek, &[0u8; 45], sbox),
+		           Err(Error::InvalidWrappedKeyLength{len: 45, expected: 44}));
+	}
+}
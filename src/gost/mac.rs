@@ -0,0 +1,226 @@
+/*
+* Module: gost::mac
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use bytes2block;
+use words2bytes;
+use constant_time_eq;
+use Error;
+use super::{Gost, BLOCK_SIZE};
+
+/// The shortest MAC (32 bits).
+pub const MAC_SIZE_MIN: usize = 4;
+/// The longest MAC (whole block).
+pub const MAC_SIZE_MAX: usize = BLOCK_SIZE;
+
+/// GOST 28147-89 MAC (imitovstavka).
+/// Every block of the data is xored to the state and processed by 16 rounds
+/// of the cipher (the first two cycles of the key schedule). The last block
+/// is filled with zeros, data of one block is followed by a block of zeros.
+/// The MAC is the first 'size' bytes of the final state.
+/// Compatible with OpenSSL's gost engine (gost-mac) and libgcrypt.
+pub struct Mac<'a> {
+	gost: &'a Gost,
+	state: [u8; BLOCK_SIZE],
+	block: [u8; BLOCK_SIZE],
+	nbytes: usize,
+	nblocks: usize,
+	size: usize,
+}
+
+impl<'a> Mac<'a> {
+	/// Creates MAC of 'size' bytes (4..8) with zero initial state.
+	pub fn new(gost: &'a Gost, size: usize) -> Result<Mac<'a>, Error> {
+		Mac::with_iv(gost, &[0u8; BLOCK_SIZE], size)
+	}
+
+	/// Creates MAC of 'size' bytes (4..8) with initial state 'iv' (synchro-message).
+	pub fn with_iv(gost: &'a Gost, iv: &[u8], size: usize) -> Result<Mac<'a>, Error> {
+		// handle caller mistakes
+		if iv.len() != BLOCK_SIZE {
+			return Err(Error::InvalidIvLength{len: iv.len(), expected: BLOCK_SIZE})
+		}
+		if !(MAC_SIZE_MIN..=MAC_SIZE_MAX).contains(&size) {
+			return Err(Error::InvalidTagLength{len: size, min: MAC_SIZE_MIN, max: MAC_SIZE_MAX})
+		}
+
+		let mut state = [0u8; BLOCK_SIZE];
+		state.copy_from_slice(iv);
+		Ok(Mac{gost, state, block: [0u8; BLOCK_SIZE], nbytes: 0, nblocks: 0, size})
+	}
+
+	/// Adds 'data' to the MAC.
+	pub fn update(&mut self, data: &[u8]) {
+		for b in data {
+			// the last block waits, it may need the extra block of zeros
+			if self.nbytes == BLOCK_SIZE {
+				self.process_block();
+			}
+			self.block[self.nbytes] = *b;
+			self.nbytes += 1;
+		}
+	}
+
+	/// Returns the MAC of all added data.
+	pub fn finalize(mut self) -> Vec<u8> {
+		if self.nbytes > 0 {
+			for b in self.block[self.nbytes..].iter_mut() {
+				*b = 0;
+			}
+			self.nbytes = BLOCK_SIZE;
+			if self.nblocks == 0 {
+				self.process_block();
+				self.nbytes = BLOCK_SIZE;
+			}
+			self.process_block();
+		}
+		self.state[..self.size].to_vec()
+	}
+
+	/// Checks in constant time if 'mac' is the MAC of all added data.
+	pub fn verify(self, mac: &[u8]) -> Result<(), Error> {
+		if mac.len() != self.size {
+			return Err(Error::InvalidTagLength{len: mac.len(), min: self.size, max: self.size})
+		}
+		if constant_time_eq(&self.finalize(), mac) {
+			Ok(())
+		} else {
+			Err(Error::AuthenticationFailed)
+		}
+	}
+
+	/// xors the waiting block to the state and runs 16 rounds over it.
+	fn process_block(&mut self) {
+		for (s, b) in self.state.iter_mut().zip(self.block.iter()) {
+			*s ^= *b;
+		}
+		for b in self.block.iter_mut() {
+			*b = 0;
+		}
+		self.nbytes = 0;
+		self.nblocks += 1;
+
+		let (mut n1, mut n2) = bytes2block(&self.state);
+		let gost = self.gost;
+		for _ in 0..2 {
+			n2 ^= gost.f(n1.wrapping_add(gost.k[0]));
+			n1 ^= gost.f(n2.wrapping_add(gost.k[1]));
+			n2 ^= gost.f(n1.wrapping_add(gost.k[2]));
+			n1 ^= gost.f(n2.wrapping_add(gost.k[3]));
+			n2 ^= gost.f(n1.wrapping_add(gost.k[4]));
+			n1 ^= gost.f(n2.wrapping_add(gost.k[5]));
+			n2 ^= gost.f(n1.wrapping_add(gost.k[6]));
+			n1 ^= gost.f(n2.wrapping_add(gost.k[7]));
+		}
+		words2bytes(n1, n2, &mut self.state);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use gost::{new, SBox};
+
+	const DATA: &str = "Yamato & Musashi, Artur, Błazej, Jolanta";
+
+	#[test]
+	fn test_mac() {
+		let key: Vec<u8> = (0..32).collect();
+		let gt = new(&key, SBox::Default).unwrap();
+		let data = DATA.as_bytes();
+
+		let vectors: [(usize, [u8; 8]); 4] = [
+			(1, [0x8f, 0xf0, 0x43, 0x26, 0xa2, 0x1d, 0x4d, 0xb1]),
+			(8, [0xde, 0x83, 0x61, 0x97, 0x8a, 0xa6, 0xb9, 0x23]),
+			(16, [0x0c, 0x26, 0xab, 0x8d, 0xeb, 0x32, 0xa5, 0x28]),
+			(41, [0x38, 0xfd, 0x28, 0xf5, 0xd3, 0x38, 0x21, 0x7c]),
+		];
+		for &(n, ref expected) in vectors.iter() {
+			assert_eq!(gt.mac(&data[..n], 8).unwrap(), &expected[..]);
+			assert_eq!(gt.mac(&data[..n], 4).unwrap(), &expected[..4]);
+			assert_eq!(gt.verify_mac(&data[..n], &expected[..5]), Ok(()));
+
+			// the same MAC for data passed in pieces
+			for piece in 1..10 {
+				let mut mac = Mac::new(&gt, 8).unwrap();
+				for chunk in data[..n].chunks(piece) {
+					mac.update(chunk);
+				}
+				assert_eq!(mac.finalize(), &expected[..]);
+			}
+		}
+
+		let mut mac = Mac::with_iv(&gt, &[1, 2, 3, 4, 5, 6, 7, 8], 4).unwrap();
+		mac.update(data);
+		assert_eq!(mac.finalize(), [0x99, 0x13, 0xd9, 0x61]);
+	}
+
+	#[test]
+	fn test_cryptopro_a() {
+		// verified with libgcrypt (GCRY_MAC_GOST28147_IMIT)
+		let key: Vec<u8> = (0..32).collect();
+		let data: Vec<u8> = (0..20u8).map(|i| i * 7 + 1).collect();
+		let gt = new(&key, SBox::CryptoProA).unwrap();
+
+		assert_eq!(gt.mac(&data, 8).unwrap(), [0xe1, 0xc9, 0x93, 0x12, 0x57, 0x46, 0xd4, 0x70]);
+		assert_eq!(gt.mac(&data[..8], 8).unwrap(), [0xe9, 0x34, 0x41, 0xd7, 0x27, 0xcc, 0x40, 0xba]);
+		assert_eq!(gt.mac(&data[..1], 4).unwrap(), [0xf7, 0xbc, 0x9f, 0x51]);
+
+		let mut mac = Mac::with_iv(&gt, &[1, 2, 3, 4, 5, 6, 7, 8], 8).unwrap();
+		mac.update(&data);
+		assert_eq!(mac.finalize(), [0xbe, 0x53, 0xde, 0x1c, 0x5b, 0xe0, 0x32, 0x1f]);
+	}
+
+	#[test]
+	fn test_verify() {
+		let gt = new(b"12345678901234567890123456789012", SBox::Default).unwrap();
+		let data = DATA.as_bytes();
+		let mac = gt.mac(data, 4).unwrap();
+
+		assert_eq!(gt.verify_mac(data, &mac), Ok(()));
+		assert_eq!(gt.verify_mac(&data[1..], &mac), Err(Error::AuthenticationFailed));
+		let mut tampered = mac.clone();
+		tampered[3] ^= 1;
+		assert_eq!(gt.verify_mac(data, &tampered), Err(Error::AuthenticationFailed));
+
+		let mut m = Mac::new(&gt, 4).unwrap();
+		m.update(data);
+		assert_eq!(m.verify(&mac[..3]), Err(Error::InvalidTagLength{len: 3, min: 4, max: 4}));
+	}
+
+	#[test]
+	fn test_errors() {
+		let gt = new(b"12345678901234567890123456789012", SBox::Default).unwrap();
+
+		assert_eq!(gt.mac(b"x", 3), Err(Error::InvalidTagLength{len: 3, min: 4, max: 8}));
+		assert_eq!(gt.mac(b"x", 9), Err(Error::InvalidTagLength{len: 9, min: 4, max: 8}));
+		assert_eq!(gt.verify_mac(b"x", &[0u8; 2]), Err(Error::InvalidTagLength{len: 2, min: 4, max: 8}));
+		assert!(Mac::with_iv(&gt, &[0u8; 7], 4).is_err());
+	}
+}
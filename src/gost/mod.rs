@@ -28,25 +28,29 @@
 * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
-use padding;
-use padding_index;
 use bytes2block;
 use block2bytes;
-use words2bytes;
+use BlockCipher;
+use Error;
+use Padding;
+use modes;
 use rand::Rng;
 
+mod gamma;
+mod keywrap;
+mod mac;
+mod sbox;
+
+pub use self::gamma::{Gamma, GammaFeedback};
+pub use self::keywrap::{wrap_key, unwrap_key, cryptopro_wrap_key, cryptopro_unwrap_key, cryptopro_kek_diversify,
+                        WRAPPED_KEY_SIZE};
+pub use self::mac::{Mac, MAC_SIZE_MIN, MAC_SIZE_MAX};
+pub use self::sbox::{SBox, Table};
+
 const BLOCK_SIZE: usize = 8;  // 8 bytes, 2 u32, 54 bit
 const KEY_SIZE: usize = 32;	// 32 bytes, 8 u32, 256 bit
-const K8: [u8; 16] = [14,  4, 13,  1,  2, 15, 11,  8,  3, 10,  6, 12,  5,  9,  0,  7];
-const K7: [u8; 16] = [15,  1,  8, 14,  6, 11,  3,  4,  9,  7,  2, 13, 12,  0,  5, 10];
-const K6: [u8; 16] = [10,  0,  9, 14,  6,  3, 15,  5,  1, 13, 12,  7, 11,  4,  2,  8];
-const K5: [u8; 16] = [ 7, 13, 14,  3,  0,  6,  9, 10,  1,  2,  8,  5, 11, 12,  4, 15];
-const K4: [u8; 16] = [ 2, 12,  4,  1,  7, 10, 11,  6,  8,  5,  3, 15, 13,  0, 14,  9];
-const K3: [u8; 16] = [12,  1, 10, 15,  9,  2,  6,  8,  0, 13,  3,  4, 14,  7,  5, 11];
-const K2: [u8; 16] = [ 4, 11,  2, 14, 15,  0,  8, 13,  3, 12,  9,  7,  5, 10,  6,  1];
-const K1: [u8; 16] = [13,  2,  8,  4,  6, 15, 11,  1, 10,  9,  3, 14,  5,  0, 12,  7];
-
 
+#[derive(Clone)]
 pub struct Gost {
     k: [u32; 8],
     k87: [u8; 256],
@@ -55,12 +59,13 @@ pub struct Gost {
     k21: [u8; 256],
 }
 
-pub fn new(key: &[u8]) -> Result<Gost, String> {
+/// Creates GOST cipher for 'key' with S-boxes of parameter set 'sbox'.
+pub fn new(key: &[u8], sbox: SBox) -> Result<Gost, Error> {
 	if key.len() != KEY_SIZE {
-		return Err("invalid key size".to_string())
+		return Err(Error::InvalidKeyLength{len: key.len(), min: KEY_SIZE, max: KEY_SIZE})
 	}
+	let t = sbox.table()?;
 	
-	let mut k = [0u32; 8];
    let mut k87 = [0u8; 256];
    let mut k65 = [0u8; 256];
    let mut k43 = [0u8; 256];
@@ -70,14 +75,20 @@ pub fn new(key: &[u8]) -> Result<Gost, String> {
    while i < 256 {
    	let idx1 = i >> 4;
       let idx2 = i & 15;
-      k87[i] = (K8[idx1] << 4) | K7[idx2];
-		k65[i] = (K6[idx1] << 4) | K5[idx2];
-		k43[i] = (K4[idx1] << 4) | K3[idx2];
-		k21[i] = (K2[idx1] << 4) | K1[idx2];
+      k87[i] = (t[7][idx1] << 4) | t[6][idx2];
+		k65[i] = (t[5][idx1] << 4) | t[4][idx2];
+		k43[i] = (t[3][idx1] << 4) | t[2][idx2];
+		k21[i] = (t[1][idx1] << 4) | t[0][idx2];
       i += 1;
 	}
 
-	i = 0usize;
+    Ok(Gost{k: key_words(key), k87, k65, k43, k21})
+}
+
+/// Converts 32 bytes of the key to 8 u32 words (little-endian).
+fn key_words(key: &[u8]) -> [u32; 8] {
+	let mut k = [0u32; 8];
+	let mut i = 0usize;
 	while i < 8 {
 		let mut idx = (i * 4) + 3;
 		let mut v = 0u32;
@@ -88,145 +99,128 @@ pub fn new(key: &[u8]) -> Result<Gost, String> {
 		k[i] = v;
 		i += 1;
 	}
-
-    Ok(Gost{k, k87, k65, k43, k21})
+	k
 }
 
 impl Gost {
 	
-	pub fn encrypt_cbc_iv(&self, input: &Vec<u8>, iv: &Vec<u8>) -> Result<Vec<u8>, String> {
-		// handle caller mistakes
-		if iv.len() != BLOCK_SIZE {
-			return Err("invalid iv size".to_string());
-		}
-		if input.len() == 0 {
-			return Err("nothing to encrypt".to_string());
-		}
-		
-		// create plain text buffer from 'input',
-		// with padding if needed
-		let plain = {
-         let mut buffer: Vec<u8> = Vec::new();
-         buffer.extend(input);
-         let n = buffer.len() % BLOCK_SIZE;
-         if n != 0 {
-            buffer.extend(padding(BLOCK_SIZE - n));
-         }
-         buffer
-		};
-      let nbytes = plain.len();
-		
-		// create cipher text buffer
-		// the first block initialised with 'iv' content
-		let mut cipher = {
-			let mut buffer = Vec::new();
-			buffer.resize(nbytes + BLOCK_SIZE, 0);
-         buffer[0..BLOCK_SIZE].copy_from_slice(iv);
-			buffer
-		};
-		
-		let mut i = 0usize;
-		let mut x = bytes2block(iv);
-		
-		while i < nbytes {
-			let t = bytes2block(&plain[i..]);
-			x = self.encrypt_2u32(t.0 ^ x.0, t.1 ^ x.1);
-			block2bytes(x, &mut cipher[(i + BLOCK_SIZE)..]);
-			i += BLOCK_SIZE;
-		}
-		
-		// Ok
-		Ok(cipher)
+	pub fn encrypt_cbc_iv(&self, input: &[u8], iv: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc_iv(self, input, iv, padding)
 	}
 	
-	pub fn encrypt_cbc(&self, input: &Vec<u8>) -> Result<Vec<u8>, String> {
-		self.encrypt_cbc_iv(input, {
-			let mut buffer = [0u8; BLOCK_SIZE];
-			rand::thread_rng().fill(&mut buffer);
-			&buffer.to_vec()
-		})
+	pub fn encrypt_cbc(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc(self, input, padding)
 	}
 	
 	/// CBC : decrypts vector of bytes in CBC mode
-	pub fn decrypt_cbc(&self, cipher: &Vec<u8>) -> Result<Vec<u8>, String> {
- 		let nbytes = cipher.len();
-		if nbytes <= BLOCK_SIZE {
-			return  Err("cipher data size is to short".to_string());
-		}
-		let mut plain: Vec<u8> = Vec::new();
-      plain.resize(nbytes - BLOCK_SIZE, 0);
-
- 		let mut p = bytes2block(&cipher[..]);
-      let mut i = BLOCK_SIZE;
-		while i < nbytes {
-			let x = bytes2block(&cipher[i..]);
- 			let t = x;
-			let c = self.decrypt(x);
- 			words2bytes(c.0 ^ p.0, c.1 ^ p.1, &mut plain[(i - BLOCK_SIZE)..]);
-			p = t;
-			i += BLOCK_SIZE;
-		}
-
-		match padding_index(&plain) {
-			Some(idx) => {
-            let retv = plain[..idx].to_vec();
-				Ok(retv)
-			},
-			_ => {
-				Ok(plain)
-			}
-		}
+	pub fn decrypt_cbc(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_cbc(self, cipher, padding)
 	}
 	
 	/// encrypts vector of bytes in ECB mode
-	pub fn encrypt_ecb(&self, input: &Vec<u8>) -> Result<Vec<u8>, String> {
-		if input.len() == 0 { return Err("plain text size is 0".to_string()) }
-				
-		let plain = {
-			let mut buffer = Vec::new();
-			buffer.extend(input);
-			let n = buffer.len() % BLOCK_SIZE;
-			if n != 0 {
-				buffer.extend(padding(BLOCK_SIZE -n));
-			}
-			buffer
-		};
-		let nbytes = plain.len();
-		
-		let mut cipher = Vec::with_capacity(nbytes);
-		cipher.resize(nbytes, 0);
-		
-		let mut i = 0usize;
-		while i < nbytes {
-			let x = bytes2block(&plain[i..]);
-			let x = self.encrypt(x);
-			block2bytes(x, &mut cipher[i..]);
-			i += BLOCK_SIZE;
-		}
-				
-		Ok(cipher)
+	pub fn encrypt_ecb(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ecb(self, input, padding)
 	}
 	
 	// decrypts vector of bytes in ECB mode
-	pub fn decrypt_ecb(&self, cipher: &Vec<u8>) -> Result<Vec<u8>, String> {
-		let nbytes = cipher.len();
-		if nbytes == 0 { return Err("cipher text size is 0".to_string()) }
-		
-		let mut plain = Vec::with_capacity(nbytes);
-		plain.resize(nbytes, 0);
-		
-		let mut i = 0usize;
-		while i < nbytes {
-			let x = bytes2block(&cipher[i..]);
-			let x = self.decrypt(x);
-			block2bytes(x, &mut plain[i..]);
-			i += BLOCK_SIZE;
+	pub fn decrypt_ecb(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ecb(self, cipher, padding)
+	}
+
+	/// CTR : encrypts vector of bytes in CTR mode
+	/// using passed IV vector (initial counter block).
+	pub fn encrypt_ctr_iv(&self, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr_iv(self, input, iv)
+	}
+
+	pub fn encrypt_ctr(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr(self, input)
+	}
+
+	/// CTR : decrypts vector of bytes in CTR mode
+	pub fn decrypt_ctr(&self, cipher: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ctr(self, cipher)
+	}
+
+	/// Gamma : encrypts vector of bytes in GOST 28147-89 gamma (counter) mode
+	/// using passed synchro-message 'iv'. The IV is stored in the first block of the result.
+	pub fn encrypt_gamma_iv(&self, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+		let mut gamma = Gamma::new(self, iv)?;
+		if input.is_empty() { return Err(Error::EmptyInput) }
+
+		let mut buffer = iv.to_vec();
+		buffer.extend_from_slice(input);
+		gamma.apply_keystream(&mut buffer[BLOCK_SIZE..]);
+		Ok(buffer)
+	}
+
+	pub fn encrypt_gamma(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+		let mut iv = [0u8; BLOCK_SIZE];
+		rand::thread_rng().fill(&mut iv);
+		self.encrypt_gamma_iv(input, &iv)
+	}
+
+	/// Gamma : decrypts vector of bytes in GOST 28147-89 gamma (counter) mode
+	pub fn decrypt_gamma(&self, cipher: &[u8]) -> Result<Vec<u8>, Error> {
+		if cipher.len() <= BLOCK_SIZE {
+			return Err(Error::InvalidCiphertextLength{len: cipher.len(), block_size: BLOCK_SIZE})
 		}
-		
-		match padding_index(&plain) {
-			Some(idx) => Ok(plain[..idx].to_vec()),
-			_ => Ok(plain)
+
+		let mut gamma = Gamma::new(self, &cipher[..BLOCK_SIZE])?;
+		let mut buffer = cipher[BLOCK_SIZE..].to_vec();
+		gamma.apply_keystream(&mut buffer);
+		Ok(buffer)
+	}
+
+	/// Gamma with feedback : encrypts vector of bytes in GOST 28147-89
+	/// gamma with feedback mode (CFB-64) using passed synchro-message 'iv'.
+	/// The IV is stored in the first block of the result.
+	pub fn encrypt_cfb_iv(&self, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+		let mut gamma = GammaFeedback::new(self, iv)?;
+		if input.is_empty() { return Err(Error::EmptyInput) }
+
+		let mut buffer = iv.to_vec();
+		buffer.extend_from_slice(input);
+		gamma.encrypt(&mut buffer[BLOCK_SIZE..]);
+		Ok(buffer)
+	}
+
+	pub fn encrypt_cfb(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+		let mut iv = [0u8; BLOCK_SIZE];
+		rand::thread_rng().fill(&mut iv);
+		self.encrypt_cfb_iv(input, &iv)
+	}
+
+	/// Gamma with feedback : decrypts vector of bytes in GOST 28147-89
+	/// gamma with feedback mode (CFB-64)
+	pub fn decrypt_cfb(&self, cipher: &[u8]) -> Result<Vec<u8>, Error> {
+		if cipher.len() <= BLOCK_SIZE {
+			return Err(Error::InvalidCiphertextLength{len: cipher.len(), block_size: BLOCK_SIZE})
 		}
+
+		let mut gamma = GammaFeedback::new(self, &cipher[..BLOCK_SIZE])?;
+		let mut buffer = cipher[BLOCK_SIZE..].to_vec();
+		gamma.decrypt(&mut buffer);
+		Ok(buffer)
+	}
+
+	/// MAC : computes MAC (imitovstavka) of 'size' bytes (4..8) of 'data'.
+	pub fn mac(&self, data: &[u8], size: usize) -> Result<Vec<u8>, Error> {
+		let mut mac = Mac::new(self, size)?;
+		mac.update(data);
+		Ok(mac.finalize())
+	}
+
+	/// MAC : checks in constant time if 'mac' is the MAC of 'data'.
+	pub fn verify_mac(&self, data: &[u8], mac: &[u8]) -> Result<(), Error> {
+		let mut m = Mac::new(self, mac.len())?;
+		m.update(data);
+		m.verify(mac)
+	}
+
+	/// Replaces the key keeping the S-boxes, 'key' must be 32 bytes long.
+	pub(crate) fn set_key(&mut self, key: &[u8]) {
+		self.k = key_words(key);
 	}
 
 	/// Encrypts one block (two u32 words)
@@ -338,6 +332,26 @@ impl Gost {
 	}
 }
 
+impl BlockCipher for Gost {
+	const BLOCK_SIZE: usize = BLOCK_SIZE;
+	const KEY_SIZE_MIN: usize = KEY_SIZE;
+	const KEY_SIZE_MAX: usize = KEY_SIZE;
+
+	fn new(key: &[u8]) -> Result<Gost, Error> {
+		new(key, SBox::Default)
+	}
+
+	fn encrypt_block(&self, block: &mut [u8]) {
+		let x = bytes2block(block);
+		block2bytes(self.encrypt(x), block);
+	}
+
+	fn decrypt_block(&self, block: &mut [u8]) {
+		let x = bytes2block(block);
+		block2bytes(self.decrypt(x), block);
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -346,7 +360,7 @@ mod tests {
 	fn test_block_00() {
 		let key = vec![0u8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0, 0, 0, 7, 0, 0, 0];
 
-		let gt = new(&key).unwrap();
+		let gt = new(&key, SBox::Default).unwrap();
 		let plain = (0u32, 0u32);
 		let expected = (0x37ef7123u32, 0x361b7184u32);
 
@@ -360,7 +374,7 @@ mod tests {
 	fn test_block_10() {
 		let key = vec![0u8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0, 0, 0, 7, 0, 0, 0];
 
-		let gt = new(&key).unwrap();
+		let gt = new(&key, SBox::Default).unwrap();
 		let plain = (1u32, 0u32);
 		let expected = (0x1159d751u32, 0xff9b91d2u32);
 
@@ -374,7 +388,7 @@ mod tests {
 	fn test_block_01() {
 		let key = vec![0u8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0, 0, 0, 7, 0, 0, 0];
 
-		let gt = new(&key).unwrap();
+		let gt = new(&key, SBox::Default).unwrap();
 		let plain = (0u32, 1u32);
 		let expected = (0xc79c4ef4u32, 0x27ac9149u32);
 
@@ -388,7 +402,7 @@ mod tests {
 	fn test_block_ff() {
 		let key = vec![0u8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0, 0, 0, 7, 0, 0, 0];
 
-		let gt = new(&key).unwrap();
+		let gt = new(&key, SBox::Default).unwrap();
 		let plain = (0xffffffffu32, 0xffffffffu32);
 		let expected = (0xf9709623u32, 0x56ad8d77u32);
 
@@ -402,24 +416,24 @@ mod tests {
 	fn test_gost_ecb() {
 		let key = vec![0u8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0, 0, 0, 7, 0, 0, 0];
 		
-		let gt = match new(&key) {
+		let gt = match new(&key, SBox::Default) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		
 		let plain = "Yamato & Musashi".as_bytes().to_vec();
 		let expected = vec![0x11, 0x97, 0xf2, 0x66, 0x20, 0x6, 0x13, 0x6e, 0xde, 0x63, 0x8a, 0x5e, 0xa8, 0xc4, 0x9d, 0xa7];
 		
 		
-		let encrypted = match gt.encrypt_ecb(&plain) {
+		let encrypted = match gt.encrypt_ecb(&plain, Padding::Legacy) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		assert_eq!(encrypted, expected);
 		
-		let decrypted = match gt.decrypt_ecb(&encrypted) {
+		let decrypted = match gt.decrypt_ecb(&encrypted, Padding::Legacy) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		assert_eq!(decrypted, plain);	
 	}
@@ -429,24 +443,24 @@ mod tests {
 		let key = vec![0u8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0, 0, 0, 7, 0, 0, 0];
 		let iv = vec![0xf8u8, 0xa4, 0x9e, 0x45, 0x40, 0xa5, 0x65, 0xc8];
 		
-		let gt = match new(&key) {
+		let gt = match new(&key, SBox::Default) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		
 		let plain = "Yamato & Musashi".as_bytes().to_vec();
 		let expected = vec![0xf8, 0xa4, 0x9e, 0x45, 0x40, 0xa5, 0x65, 0xc8, 0xe3, 0x78, 0x2e, 0x4, 0x30, 0x40, 0x45, 0x7a, 0x5a, 0xbf, 0xe4, 0xc6, 0x9a, 0x53, 0x4f, 0xce];
 		
 		
-		let encrypted = match gt.encrypt_cbc_iv(&plain, &iv) {
+		let encrypted = match gt.encrypt_cbc_iv(&plain, &iv, Padding::Legacy) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		assert_eq!(encrypted, expected);
 		
-		let decrypted = match gt.decrypt_cbc(&encrypted) {
+		let decrypted = match gt.decrypt_cbc(&encrypted, Padding::Legacy) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		assert_eq!(decrypted, plain);	
 	}
@@ -456,23 +470,39 @@ mod tests {
 		//let key = vec![0u8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0, 0, 0, 7, 0, 0, 0];
 		let key = b"12345678901234567890123456789012";
 		
-		let gt = match new(&key[..]) {
+		let gt = match new(&key[..], SBox::Default) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		
 		let plain = "Yamato & Musashi".as_bytes().to_vec();
 		
-		let encrypted = match gt.encrypt_cbc(&plain) {
+		let encrypted = match gt.encrypt_cbc(&plain, Padding::Legacy) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		
-		let decrypted = match gt.decrypt_cbc(&encrypted) {
+		let decrypted = match gt.decrypt_cbc(&encrypted, Padding::Legacy) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		assert_eq!(decrypted, plain);	
 	}
-	
+
+	#[test]
+	fn test_block_cipher() {
+		let key = vec![0u8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0, 0, 0, 7, 0, 0, 0];
+		let plain = [0u8; 8];
+		let expected = [0x23u8, 0x71, 0xef, 0x37, 0x84, 0x71, 0x1b, 0x36];
+
+		let gt = <Gost as BlockCipher>::new(&key).unwrap();
+		let mut block = plain;
+		gt.encrypt_block(&mut block);
+		assert_eq!(block, expected);
+		gt.decrypt_block(&mut block);
+		assert_eq!(block, plain);
+
+		assert_eq!(Gost::BLOCK_SIZE, 8);
+		assert_eq!(new(&key[1..], SBox::Default).err(), Some(Error::InvalidKeyLength{len: 31, min: 32, max: 32}));
+	}
 }
@@ -0,0 +1,220 @@
+/*
+* Module: gost::sbox
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use Error;
+
+/// Substitution tables k1..k8, k1 substitutes the lowest 4 bits of the word.
+pub type Table = [[u8; 16]; 8];
+
+/// S-box parameter set of GOST 28147-89.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SBox {
+	/// S-boxes of the sample code in B. Schneier's "Applied Cryptography",
+	/// used by this module before parameter sets were added.
+	Default,
+	/// id-GostR3411-94-TestParamSet (RFC 4357).
+	Test,
+	/// id-Gost28147-89-CryptoPro-A-ParamSet (RFC 4357).
+	CryptoProA,
+	/// id-Gost28147-89-CryptoPro-B-ParamSet (RFC 4357).
+	CryptoProB,
+	/// id-Gost28147-89-CryptoPro-C-ParamSet (RFC 4357).
+	CryptoProC,
+	/// id-Gost28147-89-CryptoPro-D-ParamSet (RFC 4357).
+	CryptoProD,
+	/// id-GostR3411-94-CryptoProParamSet (RFC 4357), S-boxes of the hash.
+	CryptoProHash,
+	/// id-tc26-gost-28147-param-Z (GOST R 34.12-2015, RFC 7836).
+	Tc26Z,
+	/// User defined tables k1..k8.
+	Custom(Table),
+}
+
+impl SBox {
+	/// Returns substitution tables k1..k8 of the parameter set.
+	/// Fails if a custom table contains a value bigger than 15.
+	pub fn table(&self) -> Result<Table, Error> {
+		let table = match *self {
+			SBox::Default => DEFAULT,
+			SBox::Test => TEST,
+			SBox::CryptoProA => CRYPTOPRO_A,
+			SBox::CryptoProB => CRYPTOPRO_B,
+			SBox::CryptoProC => CRYPTOPRO_C,
+			SBox::CryptoProD => CRYPTOPRO_D,
+			SBox::CryptoProHash => CRYPTOPRO_HASH,
+			SBox::Tc26Z => TC26_Z,
+			SBox::Custom(table) => table,
+		};
+		if table.iter().any(|k| k.iter().any(|v| *v > 15)) {
+			return Err(Error::InvalidSBox)
+		}
+		Ok(table)
+	}
+}
+
+const DEFAULT: Table = [
+	[13,  2,  8,  4,  6, 15, 11,  1, 10,  9,  3, 14,  5,  0, 12,  7],
+	[ 4, 11,  2, 14, 15,  0,  8, 13,  3, 12,  9,  7,  5, 10,  6,  1],
+	[12,  1, 10, 15,  9,  2,  6,  8,  0, 13,  3,  4, 14,  7,  5, 11],
+	[ 2, 12,  4,  1,  7, 10, 11,  6,  8,  5,  3, 15, 13,  0, 14,  9],
+	[ 7, 13, 14,  3,  0,  6,  9, 10,  1,  2,  8,  5, 11, 12,  4, 15],
+	[10,  0,  9, 14,  6,  3, 15,  5,  1, 13, 12,  7, 11,  4,  2,  8],
+	[15,  1,  8, 14,  6, 11,  3,  4,  9,  7,  2, 13, 12,  0,  5, 10],
+	[14,  4, 13,  1,  2, 15, 11,  8,  3, 10,  6, 12,  5,  9,  0,  7],
+];
+
+const TEST: Table = [
+	[ 4, 10,  9,  2, 13,  8,  0, 14,  6, 11,  1, 12,  7, 15,  5,  3],
+	[14, 11,  4, 12,  6, 13, 15, 10,  2,  3,  8,  1,  0,  7,  5,  9],
+	[ 5,  8,  1, 13, 10,  3,  4,  2, 14, 15, 12,  7,  6,  0,  9, 11],
+	[ 7, 13, 10,  1,  0,  8,  9, 15, 14,  4,  6, 12, 11,  2,  5,  3],
+	[ 6, 12,  7,  1,  5, 15, 13,  8,  4, 10,  9, 14,  0,  3, 11,  2],
+	[ 4, 11, 10,  0,  7,  2,  1, 13,  3,  6,  8,  5,  9, 12, 15, 14],
+	[13, 11,  4,  1,  3, 15,  5,  9,  0, 10, 14,  7,  6,  8,  2, 12],
+	[ 1, 15, 13,  0,  5,  7, 10,  4,  9,  2,  3, 14,  6, 11,  8, 12],
+];
+
+const CRYPTOPRO_A: Table = [
+	[ 9,  6,  3,  2,  8, 11,  1,  7, 10,  4, 14, 15, 12,  0, 13,  5],
+	[ 3,  7, 14,  9,  8, 10, 15,  0,  5,  2,  6, 12, 11,  4, 13,  1],
+	[14,  4,  6,  2, 11,  3, 13,  8, 12, 15,  5, 10,  0,  7,  1,  9],
+	[14,  7, 10, 12, 13,  1,  3,  9,  0,  2, 11,  4, 15,  8,  5,  6],
+	[11,  5,  1,  9,  8, 13, 15,  0, 14,  4,  2,  3, 12,  7, 10,  6],
+	[ 3, 10, 13, 12,  1,  2,  0, 11,  7,  5,  9,  4,  8, 15, 14,  6],
+	[ 1, 13,  2,  9,  7, 10,  6,  0,  8, 12,  4,  5, 15,  3, 11, 14],
+	[11, 10, 15,  5,  0, 12, 14,  8,  6,  2,  3,  9,  1,  7, 13,  4],
+];
+
+const CRYPTOPRO_B: Table = [
+	[ 8,  4, 11,  1,  3,  5,  0,  9,  2, 14, 10, 12, 13,  6,  7, 15],
+	[ 0,  1,  2, 10,  4, 13,  5, 12,  9,  7,  3, 15, 11,  8,  6, 14],
+	[14, 12,  0, 10,  9,  2, 13, 11,  7,  5,  8, 15,  3,  6,  1,  4],
+	[ 7,  5,  0, 13, 11,  6,  1,  2,  3, 10, 12, 15,  4, 14,  9,  8],
+	[ 2,  7, 12, 15,  9,  5, 10, 11,  1,  4,  0, 13,  6,  8, 14,  3],
+	[ 8,  3,  2,  6,  4, 13, 14, 11, 12,  1,  7, 15, 10,  0,  9,  5],
+	[ 5,  2, 10, 11,  9,  1, 12,  3,  7,  4, 13,  0,  6, 15,  8, 14],
+	[ 0,  4, 11, 14,  8,  3,  7,  1, 10,  2,  9,  6, 15, 13,  5, 12],
+];
+
+const CRYPTOPRO_C: Table = [
+	[ 1, 11, 12,  2,  9, 13,  0, 15,  4,  5,  8, 14, 10,  7,  6,  3],
+	[ 0,  1,  7, 13, 11,  4,  5,  2,  8, 14, 15, 12,  9, 10,  6,  3],
+	[ 8,  2,  5,  0,  4,  9, 15, 10,  3,  7, 12, 13,  6, 14,  1, 11],
+	[ 3,  6,  0,  1,  5, 13, 10,  8, 11,  2,  9,  7, 14, 15, 12,  4],
+	[ 8, 13, 11,  0,  4,  5,  1,  2,  9,  3, 12, 14,  6, 15, 10,  7],
+	[12,  9, 11,  1,  8, 14,  2,  4,  7,  3,  6,  5, 10,  0, 15, 13],
+	[10,  9,  6,  8, 13, 14,  2,  0, 15,  3,  5, 11,  4,  1, 12,  7],
+	[ 7,  4,  0,  5, 10,  2, 15, 14, 12,  6,  1, 11, 13,  9,  3,  8],
+];
+
+const CRYPTOPRO_D: Table = [
+	[15, 12,  2, 10,  6,  4,  5,  0,  7,  9, 14, 13,  1, 11,  8,  3],
+	[11,  6,  3,  4, 12, 15, 14,  2,  7, 13,  8,  0,  5, 10,  9,  1],
+	[ 1, 12, 11,  0, 15, 14,  6,  5, 10, 13,  4,  8,  9,  3,  7,  2],
+	[ 1,  5, 14, 12, 10,  7,  0, 13,  6,  2, 11,  4,  9,  3, 15,  8],
+	[ 0, 12,  8,  9, 13,  2, 10, 11,  7,  3,  6,  5,  4, 14, 15,  1],
+	[ 8,  0, 15,  3,  2,  5, 14, 11,  1, 10,  4,  7, 12,  9, 13,  6],
+	[ 3,  0,  6, 15,  1, 14,  9,  2, 13,  8, 12,  4, 11, 10,  5,  7],
+	[ 1, 10,  6,  8, 15, 11,  0,  4, 12,  3,  5,  9,  7, 13,  2, 14],
+];
+
+const CRYPTOPRO_HASH: Table = [
+	[10,  4,  5,  6,  8,  1,  3,  7, 13, 12, 14,  0,  9,  2, 11, 15],
+	[ 5, 15,  4,  0,  2, 13, 11,  9,  1,  7,  6,  3, 12, 14, 10,  8],
+	[ 7, 15, 12, 14,  9,  4,  1,  0,  3, 11,  5,  2,  6, 10,  8, 13],
+	[ 4, 10,  7, 12,  0, 15,  2,  8, 14,  1,  6,  5, 13, 11,  9,  3],
+	[ 7,  6,  4, 11,  9, 12,  2, 10,  1,  8,  0, 14, 15, 13,  3,  5],
+	[ 7,  6,  2,  4, 13,  9, 15,  0, 10,  1,  5, 11,  8, 14, 12,  3],
+	[13, 14,  4,  1,  7,  0,  5, 10,  3, 12,  8, 15,  6,  2,  9, 11],
+	[ 1,  3, 10,  9,  5, 11,  4, 15,  8,  6,  7, 14, 13,  0,  2, 12],
+];
+
+const TC26_Z: Table = [
+	[12,  4,  6,  2, 10,  5, 11,  9, 14,  8, 13,  7,  0,  3, 15,  1],
+	[ 6,  8,  2,  3,  9, 10,  5, 12,  1, 14,  4,  7, 11, 13,  0, 15],
+	[11,  3,  5,  8,  2, 15, 10, 13, 14,  1,  7,  4, 12,  9,  6,  0],
+	[12,  8,  2,  1, 13,  4, 15,  6,  7,  0, 10,  5,  3, 14,  9, 11],
+	[ 7, 15,  5, 10,  8,  1,  6, 13,  0,  9,  3, 14, 11,  4,  2, 12],
+	[ 5, 13, 15,  6,  9,  2, 12, 10, 11,  7,  8,  1,  4,  3, 14,  0],
+	[ 8, 14,  2,  5,  6,  9,  1, 12, 15,  4, 11,  0, 13, 10,  3,  7],
+	[ 1,  7, 14, 13,  0,  5,  8,  3,  4, 15, 10,  6,  9, 12, 11,  2],
+];
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use gost::new;
+	use BlockCipher;
+
+	#[test]
+	fn test_parameter_sets() {
+		// verified with libgcrypt (GCRYCTL_SET_SBOX)
+		let vectors = [
+			(SBox::Test, [0x0a, 0x80, 0x27, 0x39, 0x64, 0x60, 0x10, 0xf3, 0xc4, 0xa9, 0x3e, 0x7f, 0x47, 0xb4, 0xcd, 0x29]),
+			(SBox::CryptoProA, [0x5f, 0xbb, 0x88, 0x31, 0x3f, 0xd9, 0x5a, 0x50, 0x68, 0x0e, 0x3a, 0x61, 0xf3, 0xed, 0xef, 0xa9]),
+			(SBox::CryptoProB, [0x3e, 0xf5, 0xed, 0x4c, 0x0e, 0xd3, 0x20, 0xa9, 0x84, 0x26, 0x2b, 0x19, 0x4a, 0x4a, 0x9c, 0x29]),
+			(SBox::CryptoProC, [0x12, 0x42, 0x53, 0x66, 0xeb, 0xf3, 0xec, 0x1e, 0xb9, 0x81, 0xd3, 0xad, 0x9b, 0x5f, 0x0a, 0x57]),
+			(SBox::CryptoProD, [0x4a, 0xb7, 0xbd, 0xaf, 0x76, 0xfb, 0x3c, 0x8d, 0x70, 0xd5, 0x8e, 0xe9, 0x35, 0xc6, 0x3d, 0xe6]),
+			(SBox::CryptoProHash, [0x17, 0x9b, 0x5f, 0x75, 0xc8, 0x94, 0x9a, 0xbe, 0xcf, 0xf9, 0x08, 0x31, 0xd0, 0x2e, 0x33, 0xb7]),
+			(SBox::Tc26Z, [0x6e, 0x3f, 0x9d, 0x6a, 0x40, 0x0c, 0xae, 0xa7, 0x5d, 0xd4, 0x19, 0x1d, 0x92, 0xd0, 0xd2, 0xff]),
+		];
+		let key: Vec<u8> = (0..32).collect();
+		let plain = b"Yamato & Musashi";
+
+		for &(sbox, ref expected) in vectors.iter() {
+			let gt = new(&key, sbox).unwrap();
+			let mut data = plain.to_vec();
+			for block in data.chunks_mut(8) {
+				gt.encrypt_block(block);
+			}
+			assert_eq!(&data[..], &expected[..]);
+			for block in data.chunks_mut(8) {
+				gt.decrypt_block(block);
+			}
+			assert_eq!(&data[..], &plain[..]);
+
+			// the same tables passed as custom ones
+			let custom = new(&key, SBox::Custom(sbox.table().unwrap())).unwrap();
+			let mut data = plain.to_vec();
+			for block in data.chunks_mut(8) {
+				custom.encrypt_block(block);
+			}
+			assert_eq!(&data[..], &expected[..]);
+		}
+	}
+
+	#[test]
+	fn test_custom() {
+		let mut table = SBox::Test.table().unwrap();
+		table[3][7] = 16;
+		assert_eq!(SBox::Custom(table).table(), Err(Error::InvalidSBox));
+		assert!(new(&[0u8; 32], SBox::Custom(table)).is_err());
+	}
+}
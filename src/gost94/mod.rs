@@ -0,0 +1,290 @@
+/*
+* Module: gost94
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use BlockCipher;
+use Error;
+use gost;
+use gost::{Gost, SBox};
+
+/// Size of the hash in bytes.
+pub const DIGEST_SIZE: usize = 32;
+const BLOCK_SIZE: usize = 32;	// 32 bytes, 256 bit
+
+// C3 constant of the key generation, bytes from the least significant one
+const C3: [u8; BLOCK_SIZE] = [
+	0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00,
+	0x00, 0xff, 0xff, 0x00, 0xff, 0x00, 0x00, 0xff, 0xff, 0x00, 0x00, 0x00, 0xff, 0xff, 0x00, 0xff,
+];
+
+/// GOST R 34.11-94 hash function (RFC 5831).
+/// The step function uses GOST 28147-89 with S-boxes of the parameter set,
+/// SBox::Test for id-GostR3411-94-TestParamSet and SBox::CryptoProHash
+/// for id-GostR3411-94-CryptoProParamSet. The initial hash value is zero.
+/// Blocks and the hash are little-endian (as in OpenSSL and libgcrypt).
+pub struct Gost94 {
+	gost: Gost,
+	h: [u8; BLOCK_SIZE],			// current hash value
+	sigma: [u8; BLOCK_SIZE],		// sum of the message blocks mod 2^256
+	length: u64,						// number of hashed bytes
+	block: [u8; BLOCK_SIZE],
+	nbytes: usize,					// number of bytes in 'block'
+}
+
+/// Creates new hash object with S-boxes of parameter set 'sbox'.
+pub fn new(sbox: SBox) -> Result<Gost94, Error> {
+	Ok(Gost94{
+		gost: gost::new(&[0u8; 32], sbox)?,
+		h: [0u8; BLOCK_SIZE],
+		sigma: [0u8; BLOCK_SIZE],
+		length: 0,
+		block: [0u8; BLOCK_SIZE],
+		nbytes: 0,
+	})
+}
+
+/// Returns hash of 'data' computed with S-boxes of parameter set 'sbox'.
+pub fn digest(data: &[u8], sbox: SBox) -> Result<[u8; DIGEST_SIZE], Error> {
+	let mut hash = new(sbox)?;
+	hash.update(data);
+	Ok(hash.finalize())
+}
+
+impl Gost94 {
+	/// Adds 'data' to the hashed message.
+	pub fn update(&mut self, mut data: &[u8]) {
+		self.length = self.length.wrapping_add(data.len() as u64);
+
+		if self.nbytes > 0 {
+			let n = (BLOCK_SIZE - self.nbytes).min(data.len());
+			self.block[self.nbytes..(self.nbytes + n)].copy_from_slice(&data[..n]);
+			self.nbytes += n;
+			data = &data[n..];
+			if self.nbytes < BLOCK_SIZE {
+				return
+			}
+			let block = self.block;
+			self.process(&block);
+			self.nbytes = 0;
+		}
+
+		let mut chunks = data.chunks_exact(BLOCK_SIZE);
+		for chunk in &mut chunks {
+			let mut block = [0u8; BLOCK_SIZE];
+			block.copy_from_slice(chunk);
+			self.process(&block);
+		}
+		let rest = chunks.remainder();
+		self.block[..rest.len()].copy_from_slice(rest);
+		self.nbytes = rest.len();
+	}
+
+	/// Returns hash of the message.
+	/// The last block is filled with zeros, then the length of the message
+	/// in bits and the sum of the blocks are processed.
+	pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+		if self.nbytes > 0 {
+			let mut block = [0u8; BLOCK_SIZE];
+			block[..self.nbytes].copy_from_slice(&self.block[..self.nbytes]);
+			self.process(&block);
+		}
+
+		let mut length = [0u8; BLOCK_SIZE];
+		length[..16].copy_from_slice(&(u128::from(self.length) * 8).to_le_bytes());
+		let h = self.h;
+		self.h = self.step(&h, &length);
+		let (h, sigma) = (self.h, self.sigma);
+		self.step(&h, &sigma)
+	}
+
+	/// Processes one block of the message.
+	fn process(&mut self, m: &[u8; BLOCK_SIZE]) {
+		// sigma += m (mod 2^256)
+		let mut carry = 0u16;
+		for (s, x) in self.sigma.iter_mut().zip(m.iter()) {
+			carry += u16::from(*s) + u16::from(*x);
+			*s = carry as u8;
+			carry >>= 8;
+		}
+		let h = self.h;
+		self.h = self.step(&h, m);
+	}
+
+	/// Step function: returns new hash value for hash 'h' and block 'm'.
+	fn step(&mut self, h: &[u8; BLOCK_SIZE], m: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+		// key generation and encryption of h1..h4 with keys K1..K4
+		let mut u = *h;
+		let mut v = *m;
+		let mut s = *h;
+		for (j, block) in s.chunks_mut(8).enumerate() {
+			if j > 0 {
+				u = a(&u);
+				if j == 2 {
+					xor(&mut u, &C3);
+				}
+				v = a(&a(&v));
+			}
+			let mut w = u;
+			xor(&mut w, &v);
+			self.gost.set_key(&p(&w));
+			self.gost.encrypt_block(block);
+		}
+
+		// mixing: psi^61(h ^ psi(m ^ psi^12(s)))
+		for _ in 0..12 {
+			s = psi(&s);
+		}
+		xor(&mut s, m);
+		s = psi(&s);
+		xor(&mut s, h);
+		for _ in 0..61 {
+			s = psi(&s);
+		}
+		s
+	}
+}
+
+/// xors 'data' with 'other', byte by byte.
+fn xor(data: &mut [u8], other: &[u8]) {
+	for (d, o) in data.iter_mut().zip(other.iter()) {
+		*d ^= *o;
+	}
+}
+
+/// A transformation: (y4, y3, y2, y1) -> (y1 ^ y2, y4, y3, y2), y1 is the lowest u64.
+fn a(y: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+	let mut retv = [0u8; BLOCK_SIZE];
+	retv[..24].copy_from_slice(&y[8..]);
+	for i in 0..8 {
+		retv[24 + i] = y[i] ^ y[8 + i];
+	}
+	retv
+}
+
+/// P transformation: byte i + 1 + 4(k - 1) of the result is byte 8i + k of 'y'.
+fn p(y: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+	let mut retv = [0u8; BLOCK_SIZE];
+	for i in 0..4 {
+		for k in 0..8 {
+			retv[i + 4 * k] = y[8 * i + k];
+		}
+	}
+	retv
+}
+
+/// Psi transformation: linear feedback shift register of u16 words y16..y1,
+/// the new y16 is y1 ^ y2 ^ y3 ^ y4 ^ y13 ^ y16.
+fn psi(y: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+	let mut retv = [0u8; BLOCK_SIZE];
+	retv[..30].copy_from_slice(&y[2..]);
+	for &i in &[0, 1, 2, 3, 12, 15] {
+		retv[30] ^= y[2 * i];
+		retv[31] ^= y[2 * i + 1];
+	}
+	retv
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn hex(data: &[u8]) -> String {
+		data.iter().map(|b| format!("{:02x}", b)).collect()
+	}
+
+	fn check(sbox: SBox, vectors: &[(&str, &str)]) {
+		for &(message, expected) in vectors {
+			assert_eq!(hex(&digest(message.as_bytes(), sbox).unwrap()), expected, "{:?}", message);
+		}
+	}
+
+	#[test]
+	fn test_param_set() {
+		// RFC 5831, 7.3.1 and libgcrypt (GCRY_MD_GOSTR3411_94)
+		check(SBox::Test, &[
+			("", "ce85b99cc46752fffee35cab9a7b0278abb4c2d2055cff685af4912c49490f8d"),
+			("a", "d42c539e367c66e9c88a801f6649349c21871b4344c6a573f849fdce62f314dd"),
+			("abc", "f3134348c44fb1b2a277729e2285ebb5cb5e0f29c975bc753b70497c06a4d51d"),
+			("message digest", "ad4434ecb18f2c99b60cbe59ec3d2469582b65273f48de72db2fde16a4889a4d"),
+			("This is message, length=32 bytes", "b1c466d37519b82e8319819ff32595e047a28cb6f83eff1c6916a815a637fffa"),
+			("Suppose the original message has length = 50 bytes", "471aba57a60a770d3a76130635c1fbea4ef14de51f78b4ae57dd893b62f55208"),
+			("The quick brown fox jumps over the lazy dog", "77b7fa410c9ac58a25f49bca7d0468c9296529315eaca76bd1a10f376d1f4294"),
+		]);
+	}
+
+	#[test]
+	fn test_cryptopro_param_set() {
+		// RFC 5831, 7.3.2 and libgcrypt (GCRY_MD_GOSTR3411_CP)
+		check(SBox::CryptoProHash, &[
+			("", "981e5f3ca30c841487830f84fb433e13ac1101569b9c13584ac483234cd656c0"),
+			("a", "e74c52dd282183bf37af0079c9f78055715a103f17e3133ceff1aacf2f403011"),
+			("abc", "b285056dbf18d7392d7677369524dd14747459ed8143997e163b2986f92fd42c"),
+			("message digest", "bc6041dd2aa401ebfa6e9886734174febdb4729aa972d60f549ac39b29721ba0"),
+			("This is message, length=32 bytes", "2cefc2f7b7bdc514e18ea57fa74ff357e7fa17d652c75f69cb1be7893ede48eb"),
+			("Suppose the original message has length = 50 bytes", "c3730c5cbccacf915ac292676f21e8bd4ef75331d9405e5f1a61dc3130a65011"),
+			("The quick brown fox jumps over the lazy dog", "9004294a361a508c586fe53d1f1b02746765e71b765472786e4770d565830a76"),
+		]);
+	}
+
+	#[test]
+	fn test_long() {
+		let data = [b'U'; 128];
+		assert_eq!(hex(&digest(&data, SBox::Test).unwrap()),
+		           "53a3a3ed25180cef0c1d85a074273e551c25660a87062a52d926a9e8fe5733a4");
+		assert_eq!(hex(&digest(&data, SBox::CryptoProHash).unwrap()),
+		           "1c4ac7614691bbf427fa2316216be8f10d92edfd37cd1027514c1008f649c4e8");
+
+		let data = vec![b'a'; 1_000_000];
+		assert_eq!(hex(&digest(&data, SBox::Test).unwrap()),
+		           "5c00ccc2734cdd3332d3d4749576e3c1a7dbaf0e7ea74e9fa602413c90a129fa");
+		assert_eq!(hex(&digest(&data, SBox::CryptoProHash).unwrap()),
+		           "8693287aa62f9478f7cb312ec0866b6c4e4a0f11160441e8f4ffcd2715dd554f");
+	}
+
+	#[test]
+	fn test_update() {
+		let data: Vec<u8> = (0..200u32).map(|x| (x * 7) as u8).collect();
+		let expected = digest(&data, SBox::CryptoProHash).unwrap();
+
+		for step in 1..70 {
+			let mut hash = new(SBox::CryptoProHash).unwrap();
+			for chunk in data.chunks(step) {
+				hash.update(chunk);
+			}
+			assert_eq!(hash.finalize(), expected);
+		}
+	}
+
+	#[test]
+	fn test_invalid_sbox() {
+		let mut table = SBox::Test.table().unwrap();
+		table[0][0] = 16;
+		assert_eq!(new(SBox::Custom(table)).err(), Some(Error::InvalidSBox));
+	}
+}
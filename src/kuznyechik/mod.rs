@@ -0,0 +1,541 @@
+/*
+* Module: kuznyechik
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use BlockCipher;
+use Error;
+use Padding;
+use modes;
+
+const BLOCK_SIZE: usize = 16;	// 16 bytes, 2 u64, 128 bit
+const KEY_SIZE: usize = 32;		// 32 bytes, 256 bit
+const ROUNDS: usize = 10;		// number of round keys
+
+const POLYNOMIAL: u16 = 0x1c3;	// x^8 + x^7 + x^6 + x + 1, see [RFC 7801] 2
+
+/// Kuznyechik block cipher (GOST R 34.12-2015, RFC 7801).
+/// The block is a big-endian 128-bit value, the first byte of the block
+/// is a15 of the standard.
+pub struct Kuznyechik {
+	ek: [u128; ROUNDS],		// round keys K1..K10
+	dk: [u128; ROUNDS],		// K1, L⁻¹(K2)..L⁻¹(K9), K10 for the decryption
+}
+
+/// Creates new Kuznyechik object initialised with passed 'key'.
+pub fn new(key: &[u8]) -> Result<Kuznyechik, Error> {
+	if key.len() != KEY_SIZE {
+		return Err(Error::InvalidKeyLength{len: key.len(), min: KEY_SIZE, max: KEY_SIZE})
+	}
+
+	// key schedule, see [RFC 7801] 4.3
+	let mut ek = [0u128; ROUNDS];
+	ek[0] = load128(&key[..16]);
+	ek[1] = load128(&key[16..]);
+	for i in 1..(ROUNDS / 2) {
+		let (mut a1, mut a0) = (ek[2 * i - 2], ek[2 * i - 1]);
+		for c in &ITERATION_CONSTANTS[(8 * (i - 1))..(8 * i)] {
+			let t = ls(a1 ^ c) ^ a0;
+			a0 = a1;
+			a1 = t;
+		}
+		ek[2 * i] = a1;
+		ek[2 * i + 1] = a0;
+	}
+
+	let mut dk = ek;
+	for k in dk.iter_mut().take(ROUNDS - 1).skip(1) {
+		*k = l_inverse(*k);
+	}
+
+	Ok(Kuznyechik{ek, dk})
+}
+
+impl Kuznyechik {
+
+	/// CBC : encrypts vector of bytes in CBC mode
+	/// using passed IV vector.
+	pub fn encrypt_cbc_iv(&self, input: &[u8], iv: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc_iv(self, input, iv, padding)
+	}
+
+	pub fn encrypt_cbc(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc(self, input, padding)
+	}
+
+	/// CBC : decrypts vector of bytes in CBC mode
+	pub fn decrypt_cbc(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_cbc(self, cipher, padding)
+	}
+
+	/// ECB : encrypts vector of bytes in ECB mode.
+	pub fn encrypt_ecb(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ecb(self, input, padding)
+	}
+
+	/// ECB : decrypts vector of bytes in ECB mode
+	pub fn decrypt_ecb(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ecb(self, cipher, padding)
+	}
+
+	/// CTR : encrypts vector of bytes in CTR mode
+	/// using passed IV vector (initial counter block).
+	pub fn encrypt_ctr_iv(&self, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr_iv(self, input, iv)
+	}
+
+	pub fn encrypt_ctr(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr(self, input)
+	}
+
+	/// CTR : decrypts vector of bytes in CTR mode
+	pub fn decrypt_ctr(&self, cipher: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ctr(self, cipher)
+	}
+
+	/// Encrypts one block (two u64 words, the high half first)
+	pub fn encrypt(&self, x: (u64, u64)) -> (u64, u64) {
+		let mut a = (u128::from(x.0) << 64) | u128::from(x.1);
+		for k in &self.ek[..(ROUNDS - 1)] {
+			a = ls(a ^ k);
+		}
+		a ^= self.ek[ROUNDS - 1];
+		((a >> 64) as u64, a as u64)
+	}
+
+	/// Decrypts one block (two u64 words, the high half first)
+	pub fn decrypt(&self, x: (u64, u64)) -> (u64, u64) {
+		let a = (u128::from(x.0) << 64) | u128::from(x.1);
+		// L⁻¹ is linear, so L⁻¹(S⁻¹(a) ^ k) = L⁻¹(S⁻¹(a)) ^ L⁻¹(k)
+		// and the inner rounds are table lookups with the transformed keys
+		let mut a = l_inverse(a ^ self.dk[ROUNDS - 1]);
+		for k in self.dk[1..(ROUNDS - 1)].iter().rev() {
+			a = sl_inverse(a) ^ k;
+		}
+		a = s_inverse(a) ^ self.dk[0];
+		((a >> 64) as u64, a as u64)
+	}
+}
+
+impl BlockCipher for Kuznyechik {
+	const BLOCK_SIZE: usize = BLOCK_SIZE;
+	const KEY_SIZE_MIN: usize = KEY_SIZE;
+	const KEY_SIZE_MAX: usize = KEY_SIZE;
+
+	fn new(key: &[u8]) -> Result<Kuznyechik, Error> {
+		new(key)
+	}
+
+	fn encrypt_block(&self, block: &mut [u8]) {
+		let x = self.encrypt(bytes2block(block));
+		block2bytes(x, block);
+	}
+
+	fn decrypt_block(&self, block: &mut [u8]) {
+		let x = self.decrypt(bytes2block(block));
+		block2bytes(x, block);
+	}
+}
+
+/// Converts block of bytes to two big-endian u64 words
+fn bytes2block(data: &[u8]) -> (u64, u64) {
+	let a = load128(data);
+	((a >> 64) as u64, a as u64)
+}
+
+fn block2bytes(x: (u64, u64), data: &mut [u8]) {
+	data[..8].copy_from_slice(&x.0.to_be_bytes());
+	data[8..16].copy_from_slice(&x.1.to_be_bytes());
+}
+
+/// Reads a big-endian u128 from bytes in 'data'.
+fn load128(data: &[u8]) -> u128 {
+	let mut buffer = [0u8; 16];
+	buffer.copy_from_slice(&data[..16]);
+	u128::from_be_bytes(buffer)
+}
+
+/// Returns byte 'i' of 'a', the byte 0 is the most significant one.
+#[inline]
+fn byte(a: u128, i: usize) -> usize {
+	(a >> (120 - 8 * i)) as u8 as usize
+}
+
+/// LS transformation: the S-box followed by the linear layer.
+#[inline]
+fn ls(a: u128) -> u128 {
+	let mut retv = 0u128;
+	for (i, table) in LS_TABLE.iter().enumerate() {
+		retv ^= table[byte(a, i)];
+	}
+	retv
+}
+
+/// (LS)⁻¹ with the order of the steps swapped: L⁻¹(S⁻¹(a)).
+#[inline]
+fn sl_inverse(a: u128) -> u128 {
+	let mut retv = 0u128;
+	for (i, table) in SL_INV_TABLE.iter().enumerate() {
+		retv ^= table[byte(a, i)];
+	}
+	retv
+}
+
+/// S⁻¹ transformation (byte by byte).
+fn s_inverse(a: u128) -> u128 {
+	let mut retv = 0u128;
+	for i in 0..BLOCK_SIZE {
+		retv = (retv << 8) | u128::from(PI_INV[byte(a, i)]);
+	}
+	retv
+}
+
+/// L⁻¹ transformation.
+fn l_inverse(a: u128) -> u128 {
+	u128::from_be_bytes(l_inverse_bytes(a.to_be_bytes()))
+}
+
+/// gf_mult returns a·b in GF(2^8)/POLYNOMIAL
+const fn gf_mult(a: u8, b: u8) -> u8 {
+	let mut a = a as u16;
+	let mut b = b;
+	let mut retv = 0u16;
+	while b != 0 {
+		if b & 1 != 0 {
+			retv ^= a;
+		}
+		a <<= 1;
+		if a & 0x100 != 0 {
+			a ^= POLYNOMIAL;
+		}
+		b >>= 1;
+	}
+	retv as u8
+}
+
+/// l function: linear combination of the bytes with L_COEFFICIENTS.
+const fn l_func(a: &[u8; 16]) -> u8 {
+	let mut retv = 0u8;
+	let mut i = 0;
+	while i < 16 {
+		retv ^= gf_mult(L_COEFFICIENTS[i], a[i]);
+		i += 1;
+	}
+	retv
+}
+
+/// L transformation: 16 rounds of R, see [RFC 7801] 2.
+const fn l_bytes(mut a: [u8; 16]) -> [u8; 16] {
+	let mut n = 0;
+	while n < 16 {
+		let x = l_func(&a);
+		let mut i = 15;
+		while i > 0 {
+			a[i] = a[i - 1];
+			i -= 1;
+		}
+		a[0] = x;
+		n += 1;
+	}
+	a
+}
+
+/// L⁻¹ transformation: 16 rounds of R⁻¹.
+const fn l_inverse_bytes(mut a: [u8; 16]) -> [u8; 16] {
+	let mut n = 0;
+	while n < 16 {
+		let first = a[0];
+		let mut i = 0;
+		while i < 15 {
+			a[i] = a[i + 1];
+			i += 1;
+		}
+		a[15] = first;
+		a[15] = l_func(&a);
+		n += 1;
+	}
+	a
+}
+
+/// Inverse of the PI permutation.
+const fn pi_inverse() -> [u8; 256] {
+	let mut retv = [0u8; 256];
+	let mut i = 0;
+	while i < 256 {
+		retv[PI[i] as usize] = i as u8;
+		i += 1;
+	}
+	retv
+}
+
+/// Iteration constants C1..C32 of the key schedule: Ci = L(i).
+const fn iteration_constants() -> [u128; 32] {
+	let mut retv = [0u128; 32];
+	let mut i = 0;
+	while i < 32 {
+		let mut a = [0u8; 16];
+		a[15] = (i + 1) as u8;
+		retv[i] = u128::from_be_bytes(l_bytes(a));
+		i += 1;
+	}
+	retv
+}
+
+/// Lookup tables of the linear layer combined with the S-box.
+/// L is linear, so it is enough to know the images of all 16 unit
+/// vectors: table[i][b] is the image of the block with 'sbox[b]'
+/// at byte 'i' and zeros elsewhere.
+const fn lookup_table(sbox: &[u8; 256], inverse: bool) -> [[u128; 256]; 16] {
+	// columns of the (inverse) linear layer matrix
+	let mut m = [[0u8; 16]; 16];
+	let mut i = 0;
+	while i < 16 {
+		let mut a = [0u8; 16];
+		a[i] = 1;
+		m[i] = if inverse { l_inverse_bytes(a) } else { l_bytes(a) };
+		i += 1;
+	}
+
+	let mut retv = [[0u128; 256]; 16];
+	let mut i = 0;
+	while i < 16 {
+		let mut b = 0;
+		while b < 256 {
+			let mut a = [0u8; 16];
+			let mut j = 0;
+			while j < 16 {
+				a[j] = gf_mult(m[i][j], sbox[b]);
+				j += 1;
+			}
+			retv[i][b] = u128::from_be_bytes(a);
+			b += 1;
+		}
+		i += 1;
+	}
+	retv
+}
+
+static LS_TABLE: [[u128; 256]; 16] = lookup_table(&PI, false);
+static SL_INV_TABLE: [[u128; 256]; 16] = lookup_table(&PI_INV, true);
+static ITERATION_CONSTANTS: [u128; 32] = iteration_constants();
+const PI_INV: [u8; 256] = pi_inverse();
+
+// coefficients of the l function (a15 first), see [RFC 7801] 2
+const L_COEFFICIENTS: [u8; 16] = [148, 32, 133, 16, 194, 192, 1, 251, 1, 192, 194, 16, 133, 32, 148, 1];
+
+// nonlinear bijection, see [RFC 7801] 2
+const PI: [u8; 256] = [
+	252, 238, 221, 17, 207, 110, 49, 22, 251, 196, 250, 218, 35, 197, 4, 77,
+	233, 119, 240, 219, 147, 46, 153, 186, 23, 54, 241, 187, 20, 205, 95, 193,
+	249, 24, 101, 90, 226, 92, 239, 33, 129, 28, 60, 66, 139, 1, 142, 79,
+	5, 132, 2, 174, 227, 106, 143, 160, 6, 11, 237, 152, 127, 212, 211, 31,
+	235, 52, 44, 81, 234, 200, 72, 171, 242, 42, 104, 162, 253, 58, 206, 204,
+	181, 112, 14, 86, 8, 12, 118, 18, 191, 114, 19, 71, 156, 183, 93, 135,
+	21, 161, 150, 41, 16, 123, 154, 199, 243, 145, 120, 111, 157, 158, 178, 177,
+	50, 117, 25, 61, 255, 53, 138, 126, 109, 84, 198, 128, 195, 189, 13, 87,
+	223, 245, 36, 169, 62, 168, 67, 201, 215, 121, 214, 246, 124, 34, 185, 3,
+	224, 15, 236, 222, 122, 148, 176, 188, 220, 232, 40, 80, 78, 51, 10, 74,
+	167, 151, 96, 115, 30, 0, 98, 68, 26, 184, 56, 130, 100, 159, 38, 65,
+	173, 69, 70, 146, 39, 94, 85, 47, 140, 163, 165, 125, 105, 213, 149, 59,
+	7, 88, 179, 64, 134, 172, 29, 247, 48, 55, 107, 228, 136, 217, 231, 137,
+	225, 27, 131, 73, 76, 63, 248, 254, 141, 83, 170, 144, 202, 216, 133, 97,
+	32, 113, 103, 164, 45, 43, 9, 91, 203, 155, 37, 208, 190, 229, 108, 82,
+	89, 166, 116, 210, 230, 244, 180, 192, 209, 102, 175, 194, 57, 75, 99, 182,
+];
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use modes::gost3413;
+
+	const KEY: [u8; 32] = [
+		0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+		0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+	];
+
+	// plain text of the examples of GOST R 34.13-2015 (A.1)
+	const PLAIN: [u8; 64] = [
+		0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x00, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88,
+		0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a,
+		0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a, 0x00,
+		0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xee, 0xff, 0x0a, 0x00, 0x11,
+	];
+
+	const IV: [u8; 32] = [
+		0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xce, 0xf0, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf0, 0x01, 0x12,
+		0x23, 0x34, 0x45, 0x56, 0x67, 0x78, 0x89, 0x90, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19,
+	];
+
+	#[test]
+	fn test_block() {
+		// RFC 7801, 5.5
+		let kz = new(&KEY).unwrap();
+		let plain = (0x1122334455667700u64, 0xffeeddccbbaa9988u64);
+		let expected = (0x7f679d90bebc2430u64, 0x5a468d42b9d4edcdu64);
+
+		let encrypted = kz.encrypt(plain);
+		assert_eq!(encrypted, expected);
+		let decrypted = kz.decrypt(encrypted);
+		assert_eq!(decrypted, plain);
+	}
+
+	#[test]
+	fn test_key_schedule() {
+		// RFC 7801, 5.4
+		let kz = new(&KEY).unwrap();
+		assert_eq!(ITERATION_CONSTANTS[0], 0x6ea276726c487ab85d27bd10dd849401);
+		assert_eq!(kz.ek[2], 0xdb31485315694343228d6aef8cc78c44);
+		assert_eq!(kz.ek[9], 0x72e9dd7416bcf45b755dbaa88e4a4043);
+	}
+
+	#[test]
+	fn test_transformations() {
+		// RFC 7801, 5.1 and 5.2
+		let s = 0xb66cd8887d38e8d77765aeea0c9a7efcu128;
+		let l = 0xd456584dd0e3e84cc3166e4b7fa2890du128;
+		assert_eq!(s_inverse(s), 0xffeeddccbbaa99881122334455667700);
+		assert_eq!(l_inverse(l), 0x64a59400000000000000000000000000);
+		assert_eq!(ls(s_inverse(0x64a59400000000000000000000000000)), l);
+		assert_eq!(sl_inverse(s), l_inverse(s_inverse(s)));
+	}
+
+	#[test]
+	fn test_block_cipher() {
+		let kz = <Kuznyechik as BlockCipher>::new(&KEY).unwrap();
+		let mut block = [0u8; 16];
+		block.copy_from_slice(&PLAIN[..16]);
+		kz.encrypt_block(&mut block);
+		assert_eq!(block, [
+			0x7f, 0x67, 0x9d, 0x90, 0xbe, 0xbc, 0x24, 0x30, 0x5a, 0x46, 0x8d, 0x42, 0xb9, 0xd4, 0xed, 0xcd,
+		]);
+		kz.decrypt_block(&mut block);
+		assert_eq!(block, &PLAIN[..16]);
+
+		assert_eq!(Kuznyechik::BLOCK_SIZE, 16);
+		assert_eq!(new(&KEY[..16]).err(), Some(Error::InvalidKeyLength{len: 16, min: 32, max: 32}));
+	}
+
+	#[test]
+	fn test_ecb() {
+		let expected = [
+			0x7f, 0x67, 0x9d, 0x90, 0xbe, 0xbc, 0x24, 0x30, 0x5a, 0x46, 0x8d, 0x42, 0xb9, 0xd4, 0xed, 0xcd,
+			0xb4, 0x29, 0x91, 0x2c, 0x6e, 0x00, 0x32, 0xf9, 0x28, 0x54, 0x52, 0xd7, 0x67, 0x18, 0xd0, 0x8b,
+			0xf0, 0xca, 0x33, 0x54, 0x9d, 0x24, 0x7c, 0xee, 0xf3, 0xf5, 0xa5, 0x31, 0x3b, 0xd4, 0xb1, 0x57,
+			0xd0, 0xb0, 0x9c, 0xcd, 0xe8, 0x30, 0xb9, 0xeb, 0x3a, 0x02, 0xc4, 0xc5, 0xaa, 0x8a, 0xda, 0x98,
+		];
+		let kz = new(&KEY).unwrap();
+
+		let encrypted = kz.encrypt_ecb(&PLAIN, Padding::NoPadding).unwrap();
+		assert_eq!(&encrypted[..], &expected[..]);
+		assert_eq!(kz.decrypt_ecb(&encrypted, Padding::NoPadding).unwrap(), &PLAIN[..]);
+	}
+
+	#[test]
+	fn test_cbc() {
+		let kz = new(&KEY).unwrap();
+		let plain = "Artur, Błazej, Jolanta, Piotr Pszczółkowski".as_bytes();
+
+		let encrypted = kz.encrypt_cbc(plain, Padding::Pkcs7).unwrap();
+		assert_eq!(encrypted.len(), 64);
+		assert_eq!(kz.decrypt_cbc(&encrypted, Padding::Pkcs7).unwrap(), plain);
+
+		let encrypted = kz.encrypt_ctr(plain).unwrap();
+		assert_eq!(encrypted.len(), 16 + plain.len());
+		assert_eq!(kz.decrypt_ctr(&encrypted).unwrap(), plain);
+	}
+
+	#[test]
+	fn test_gost3413_ctr() {
+		let expected = [
+			0xf1, 0x95, 0xd8, 0xbe, 0xc1, 0x0e, 0xd1, 0xdb, 0xd5, 0x7b, 0x5f, 0xa2, 0x40, 0xbd, 0xa1, 0xb8,
+			0x85, 0xee, 0xe7, 0x33, 0xf6, 0xa1, 0x3e, 0x5d, 0xf3, 0x3c, 0xe4, 0xb3, 0x3c, 0x45, 0xde, 0xe4,
+			0xa5, 0xea, 0xe8, 0x8b, 0xe6, 0x35, 0x6e, 0xd3, 0xd5, 0xe8, 0x77, 0xf1, 0x35, 0x64, 0xa3, 0xa5,
+			0xcb, 0x91, 0xfa, 0xb1, 0xf2, 0x0c, 0xba, 0xb6, 0xd1, 0xc6, 0xd1, 0x58, 0x20, 0xbd, 0xba, 0x73,
+		];
+		let kz = new(&KEY).unwrap();
+
+		let encrypted = gost3413::encrypt_ctr(&kz, &PLAIN, &IV[..8]).unwrap();
+		assert_eq!(&encrypted[..], &expected[..]);
+		assert_eq!(gost3413::decrypt_ctr(&kz, &encrypted, &IV[..8]).unwrap(), &PLAIN[..]);
+	}
+
+	#[test]
+	fn test_gost3413_ofb() {
+		let expected = [
+			0x81, 0x80, 0x0a, 0x59, 0xb1, 0x84, 0x2b, 0x24, 0xff, 0x1f, 0x79, 0x5e, 0x89, 0x7a, 0xbd, 0x95,
+			0xed, 0x5b, 0x47, 0xa7, 0x04, 0x8c, 0xfa, 0xb4, 0x8f, 0xb5, 0x21, 0x36, 0x9d, 0x93, 0x26, 0xbf,
+			0x66, 0xa2, 0x57, 0xac, 0x3c, 0xa0, 0xb8, 0xb1, 0xc8, 0x0f, 0xe7, 0xfc, 0x10, 0x28, 0x8a, 0x13,
+			0x20, 0x3e, 0xbb, 0xc0, 0x66, 0x13, 0x86, 0x60, 0xa0, 0x29, 0x22, 0x43, 0xf6, 0x90, 0x31, 0x50,
+		];
+		let kz = new(&KEY).unwrap();
+
+		let encrypted = gost3413::encrypt_ofb(&kz, &PLAIN, &IV).unwrap();
+		assert_eq!(&encrypted[..], &expected[..]);
+		assert_eq!(gost3413::decrypt_ofb(&kz, &encrypted, &IV).unwrap(), &PLAIN[..]);
+	}
+
+	#[test]
+	fn test_gost3413_cbc() {
+		let expected = [
+			0x68, 0x99, 0x72, 0xd4, 0xa0, 0x85, 0xfa, 0x4d, 0x90, 0xe5, 0x2e, 0x3d, 0x6d, 0x7d, 0xcc, 0x27,
+			0x28, 0x26, 0xe6, 0x61, 0xb4, 0x78, 0xec, 0xa6, 0xaf, 0x1e, 0x8e, 0x44, 0x8d, 0x5e, 0xa5, 0xac,
+			0xfe, 0x7b, 0xab, 0xf1, 0xe9, 0x19, 0x99, 0xe8, 0x56, 0x40, 0xe8, 0xb0, 0xf4, 0x9d, 0x90, 0xd0,
+			0x16, 0x76, 0x88, 0x06, 0x5a, 0x89, 0x5c, 0x63, 0x1a, 0x2d, 0x9a, 0x15, 0x60, 0xb6, 0x39, 0x70,
+		];
+		let kz = new(&KEY).unwrap();
+
+		let encrypted = gost3413::encrypt_cbc(&kz, &PLAIN, &IV, Padding::NoPadding).unwrap();
+		assert_eq!(&encrypted[..], &expected[..]);
+		assert_eq!(gost3413::decrypt_cbc(&kz, &encrypted, &IV, Padding::NoPadding).unwrap(), &PLAIN[..]);
+	}
+
+	#[test]
+	fn test_gost3413_cfb() {
+		let expected = [
+			0x81, 0x80, 0x0a, 0x59, 0xb1, 0x84, 0x2b, 0x24, 0xff, 0x1f, 0x79, 0x5e, 0x89, 0x7a, 0xbd, 0x95,
+			0xed, 0x5b, 0x47, 0xa7, 0x04, 0x8c, 0xfa, 0xb4, 0x8f, 0xb5, 0x21, 0x36, 0x9d, 0x93, 0x26, 0xbf,
+			0x79, 0xf2, 0xa8, 0xeb, 0x5c, 0xc6, 0x8d, 0x38, 0x84, 0x2d, 0x26, 0x4e, 0x97, 0xa2, 0x38, 0xb5,
+			0x4f, 0xfe, 0xbe, 0xcd, 0x4e, 0x92, 0x2d, 0xe6, 0xc7, 0x5b, 0xd9, 0xdd, 0x44, 0xfb, 0xf4, 0xd1,
+		];
+		let kz = new(&KEY).unwrap();
+
+		let encrypted = gost3413::encrypt_cfb(&kz, &PLAIN, &IV).unwrap();
+		assert_eq!(&encrypted[..], &expected[..]);
+		assert_eq!(gost3413::decrypt_cfb(&kz, &encrypted, &IV).unwrap(), &PLAIN[..]);
+	}
+
+	#[test]
+	fn test_gost3413_mac() {
+		let expected = [0x33, 0x6f, 0x4d, 0x29, 0x60, 0x59, 0xfb, 0xe3];
+		let kz = new(&KEY).unwrap();
+
+		assert_eq!(gost3413::mac(&kz, &PLAIN, 8).unwrap(), expected);
+		assert_eq!(gost3413::verify_mac(&kz, &PLAIN, &expected), Ok(()));
+		assert_eq!(gost3413::verify_mac(&kz, &PLAIN[..63], &expected), Err(Error::AuthenticationFailed));
+	}
+}
@@ -31,7 +31,38 @@ pub mod blowfish;
 pub mod twofish;
 pub mod gost;
 pub mod way3;
+pub mod magma;
+pub mod kuznyechik;
+pub mod gost94;
+pub mod bcrypt;
+pub mod sha512;
+pub mod modes;
+mod error;
+mod padding;
 
+pub use error::Error;
+pub use padding::Padding;
+
+/// Common interface of the block ciphers implemented in this crate.
+/// Blocks are passed as byte slices, the byte order of the words
+/// is the one used by the cipher's own ECB/CBC functions.
+pub trait BlockCipher {
+	/// Block size in bytes.
+	const BLOCK_SIZE: usize;
+	/// The shortest accepted key in bytes.
+	const KEY_SIZE_MIN: usize;
+	/// The longest accepted key in bytes.
+	const KEY_SIZE_MAX: usize;
+
+	/// Creates new cipher object initialised with passed 'key'.
+	fn new(key: &[u8]) -> Result<Self, Error> where Self: Sized;
+
+	/// Encrypts in place one block, 'block' must be BLOCK_SIZE bytes long.
+	fn encrypt_block(&self, block: &mut [u8]);
+
+	/// Decrypts in place one block, 'block' must be BLOCK_SIZE bytes long.
+	fn decrypt_block(&self, block: &mut [u8]);
+}
 
 /// Converts block of bytes to two u32 words
 #[inline]
@@ -64,29 +95,31 @@ fn words2bytes(xl: u32, xr: u32, data: &mut [u8]) {
    data[4] = xr as u8;
 }
 
+/// Compares 'a' and 'b' in time depending only on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	if a.len() != b.len() {
+		return false;
+	}
+	let mut diff = 0u8;
+	for (x, y) in a.iter().zip(b.iter()) {
+		diff |= x ^ y;
+	}
+	diff == 0
+}
+
+/// Returns legacy padding of 'nbytes' bytes: 0x80 followed by zeros.
+/// New code should use Padding.
 pub fn padding(nbytes: usize) -> Vec<u8> {
-   let mut s = Vec::with_capacity(nbytes);
-	s.resize(nbytes, 0);
+   let mut s = vec![0u8; nbytes];
    s[0] = 128;
    s
 }
 
+/// Returns index where legacy padding starts in 'data'.
+/// New code should use Padding.
 pub fn padding_index(data: &[u8]) -> Option<usize> {
-	let mut i = data.len();
-	
-	if i > 0 {
-		loop {
-			i -= 1;
-			let c = data[i];
-			if c != 0 {
-				if data[i] == 128 {
-					return Some(i);
-				}
-            else {
-                break;
-            }
-			}
-	  	}
+	match data.iter().rposition(|c| *c != 0) {
+		Some(i) if data[i] == 128 => Some(i),
+		_ => None,
 	}
-	None
 }
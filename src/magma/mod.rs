@@ -0,0 +1,299 @@
+/*
+* Module: magma
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use BlockCipher;
+use Error;
+use Padding;
+use modes;
+use gost;
+use gost::{Gost, SBox};
+
+const BLOCK_SIZE: usize = 8;  // 8 bytes, 2 u32, 64 bit
+const KEY_SIZE: usize = 32;   // 32 bytes, 8 u32, 256 bit
+
+/// Magma block cipher (GOST R 34.12-2015, RFC 8891).
+/// GOST 28147-89 with the S-box of the TC26-Z parameter set
+/// and big-endian order of bytes in the key and block words.
+pub struct Magma {
+	gost: Gost,
+}
+
+/// Creates new Magma object initialised with passed 'key'.
+pub fn new(key: &[u8]) -> Result<Magma, Error> {
+	if key.len() != KEY_SIZE {
+		return Err(Error::InvalidKeyLength{len: key.len(), min: KEY_SIZE, max: KEY_SIZE})
+	}
+
+	// the Feistel core reads key words in little-endian order
+	let mut k = key.to_vec();
+	for word in k.chunks_mut(4) {
+		word.reverse();
+	}
+	Ok(Magma{gost: gost::new(&k, SBox::Tc26Z)?})
+}
+
+impl Magma {
+
+	/// CBC : encrypts vector of bytes in CBC mode
+	/// using passed IV vector.
+	pub fn encrypt_cbc_iv(&self, input: &[u8], iv: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc_iv(self, input, iv, padding)
+	}
+
+	pub fn encrypt_cbc(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc(self, input, padding)
+	}
+
+	/// CBC : decrypts vector of bytes in CBC mode
+	pub fn decrypt_cbc(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_cbc(self, cipher, padding)
+	}
+
+	/// ECB : encrypts vector of bytes in ECB mode.
+	pub fn encrypt_ecb(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ecb(self, input, padding)
+	}
+
+	/// ECB : decrypts vector of bytes in ECB mode
+	pub fn decrypt_ecb(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ecb(self, cipher, padding)
+	}
+
+	/// CTR : encrypts vector of bytes in CTR mode
+	/// using passed IV vector (initial counter block).
+	pub fn encrypt_ctr_iv(&self, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr_iv(self, input, iv)
+	}
+
+	pub fn encrypt_ctr(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr(self, input)
+	}
+
+	/// CTR : decrypts vector of bytes in CTR mode
+	pub fn decrypt_ctr(&self, cipher: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ctr(self, cipher)
+	}
+
+	/// Encrypts one block (two u32 words, the high half first)
+	pub fn encrypt(&self, x: (u32, u32)) -> (u32, u32) {
+		// the core works on (low, high) halves
+		let (lo, hi) = self.gost.encrypt((x.1, x.0));
+		(hi, lo)
+	}
+
+	/// Decrypts one block (two u32 words, the high half first)
+	pub fn decrypt(&self, x: (u32, u32)) -> (u32, u32) {
+		let (lo, hi) = self.gost.decrypt((x.1, x.0));
+		(hi, lo)
+	}
+}
+
+impl BlockCipher for Magma {
+	const BLOCK_SIZE: usize = BLOCK_SIZE;
+	const KEY_SIZE_MIN: usize = KEY_SIZE;
+	const KEY_SIZE_MAX: usize = KEY_SIZE;
+
+	fn new(key: &[u8]) -> Result<Magma, Error> {
+		new(key)
+	}
+
+	fn encrypt_block(&self, block: &mut [u8]) {
+		let x = self.encrypt(bytes2block(block));
+		block2bytes(x, block);
+	}
+
+	fn decrypt_block(&self, block: &mut [u8]) {
+		let x = self.decrypt(bytes2block(block));
+		block2bytes(x, block);
+	}
+}
+
+/// Converts block of bytes to two big-endian u32 words
+fn bytes2block(data: &[u8]) -> (u32, u32) {
+	let hi = u32::from(data[0]) << 24 | u32::from(data[1]) << 16 | u32::from(data[2]) << 8 | u32::from(data[3]);
+	let lo = u32::from(data[4]) << 24 | u32::from(data[5]) << 16 | u32::from(data[6]) << 8 | u32::from(data[7]);
+	(hi, lo)
+}
+
+fn block2bytes(x: (u32, u32), data: &mut [u8]) {
+	data[..4].copy_from_slice(&x.0.to_be_bytes());
+	data[4..8].copy_from_slice(&x.1.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use modes::gost3413;
+
+	const KEY: [u8; 32] = [
+		0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00,
+		0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff,
+	];
+
+	// plain text of the examples of GOST R 34.13-2015 (A.2)
+	const PLAIN: [u8; 32] = [
+		0x92, 0xde, 0xf0, 0x6b, 0x3c, 0x13, 0x0a, 0x59, 0xdb, 0x54, 0xc7, 0x04, 0xf8, 0x18, 0x9d, 0x20,
+		0x4a, 0x98, 0xfb, 0x2e, 0x67, 0xa8, 0x02, 0x4c, 0x89, 0x12, 0x40, 0x9b, 0x17, 0xb5, 0x7e, 0x41,
+	];
+
+	#[test]
+	fn test_block() {
+		// RFC 8891, A.3
+		let mg = new(&KEY).unwrap();
+		let plain = (0xfedcba98u32, 0x76543210u32);
+		let expected = (0x4ee901e5u32, 0xc2d8ca3du32);
+
+		let encrypted = mg.encrypt(plain);
+		assert_eq!(encrypted, expected);
+		let decrypted = mg.decrypt(encrypted);
+		assert_eq!(decrypted, plain);
+	}
+
+	#[test]
+	fn test_block_cipher() {
+		let mg = <Magma as BlockCipher>::new(&KEY).unwrap();
+		let mut block = [0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10];
+		mg.encrypt_block(&mut block);
+		assert_eq!(block, [0x4e, 0xe9, 0x01, 0xe5, 0xc2, 0xd8, 0xca, 0x3d]);
+		mg.decrypt_block(&mut block);
+		assert_eq!(block, [0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10]);
+
+		assert_eq!(Magma::BLOCK_SIZE, 8);
+		assert_eq!(new(&KEY[1..]).err(), Some(Error::InvalidKeyLength{len: 31, min: 32, max: 32}));
+	}
+
+	#[test]
+	fn test_ecb() {
+		let expected = [
+			0x2b, 0x07, 0x3f, 0x04, 0x94, 0xf3, 0x72, 0xa0, 0xde, 0x70, 0xe7, 0x15, 0xd3, 0x55, 0x6e, 0x48,
+			0x11, 0xd8, 0xd9, 0xe9, 0xea, 0xcf, 0xbc, 0x1e, 0x7c, 0x68, 0x26, 0x09, 0x96, 0xc6, 0x7e, 0xfb,
+		];
+		let mg = new(&KEY).unwrap();
+
+		let encrypted = mg.encrypt_ecb(&PLAIN, Padding::NoPadding).unwrap();
+		assert_eq!(&encrypted[..], &expected[..]);
+		assert_eq!(mg.decrypt_ecb(&encrypted, Padding::NoPadding).unwrap(), &PLAIN[..]);
+	}
+
+	#[test]
+	fn test_ctr() {
+		let iv = [0x12, 0x34, 0x56, 0x78];
+		let expected = [
+			0x4e, 0x98, 0x11, 0x0c, 0x97, 0xb7, 0xb9, 0x3c, 0x3e, 0x25, 0x0d, 0x93, 0xd6, 0xe8, 0x5d, 0x69,
+			0x13, 0x6d, 0x86, 0x88, 0x07, 0xb2, 0xdb, 0xef, 0x56, 0x8e, 0xb6, 0x80, 0xab, 0x52, 0xa1, 0x2d,
+		];
+		let mg = new(&KEY).unwrap();
+
+		let encrypted = gost3413::encrypt_ctr(&mg, &PLAIN, &iv).unwrap();
+		assert_eq!(&encrypted[..], &expected[..]);
+		assert_eq!(gost3413::decrypt_ctr(&mg, &encrypted, &iv).unwrap(), &PLAIN[..]);
+		assert_eq!(gost3413::encrypt_ctr(&mg, &PLAIN[..13], &iv).unwrap(), &expected[..13]);
+	}
+
+	#[test]
+	fn test_ofb() {
+		let iv = [
+			0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef, 0x23, 0x45, 0x67, 0x89, 0x0a, 0xbc, 0xde, 0xf1,
+		];
+		let expected = [
+			0xdb, 0x37, 0xe0, 0xe2, 0x66, 0x90, 0x3c, 0x83, 0x0d, 0x46, 0x64, 0x4c, 0x1f, 0x9a, 0x08, 0x9c,
+			0xa0, 0xf8, 0x30, 0x62, 0x43, 0x0e, 0x32, 0x7e, 0xc8, 0x24, 0xef, 0xb8, 0xbd, 0x4f, 0xdb, 0x05,
+		];
+		let mg = new(&KEY).unwrap();
+
+		let encrypted = gost3413::encrypt_ofb(&mg, &PLAIN, &iv).unwrap();
+		assert_eq!(&encrypted[..], &expected[..]);
+		assert_eq!(gost3413::decrypt_ofb(&mg, &encrypted, &iv).unwrap(), &PLAIN[..]);
+		assert_eq!(gost3413::encrypt_ofb(&mg, &PLAIN[..13], &iv).unwrap(), &expected[..13]);
+	}
+
+	#[test]
+	fn test_cbc() {
+		let iv = [
+			0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef, 0x23, 0x45, 0x67, 0x89, 0x0a, 0xbc, 0xde, 0xf1,
+			0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef, 0x12,
+		];
+		let expected = [
+			0x96, 0xd1, 0xb0, 0x5e, 0xea, 0x68, 0x39, 0x19, 0xaf, 0xf7, 0x61, 0x29, 0xab, 0xb9, 0x37, 0xb9,
+			0x50, 0x58, 0xb4, 0xa1, 0xc4, 0xbc, 0x00, 0x19, 0x20, 0xb7, 0x8b, 0x1a, 0x7c, 0xd7, 0xe6, 0x67,
+		];
+		let mg = new(&KEY).unwrap();
+
+		let encrypted = gost3413::encrypt_cbc(&mg, &PLAIN, &iv, Padding::NoPadding).unwrap();
+		assert_eq!(&encrypted[..], &expected[..]);
+		assert_eq!(gost3413::decrypt_cbc(&mg, &encrypted, &iv, Padding::NoPadding).unwrap(), &PLAIN[..]);
+
+		let encrypted = gost3413::encrypt_cbc(&mg, &PLAIN[..13], &iv, Padding::Iso7816).unwrap();
+		assert_eq!(gost3413::decrypt_cbc(&mg, &encrypted, &iv, Padding::Iso7816).unwrap(), &PLAIN[..13]);
+	}
+
+	#[test]
+	fn test_cfb() {
+		let iv = [
+			0x12, 0x34, 0x56, 0x78, 0x90, 0xab, 0xcd, 0xef, 0x23, 0x45, 0x67, 0x89, 0x0a, 0xbc, 0xde, 0xf1,
+		];
+		let expected = [
+			0xdb, 0x37, 0xe0, 0xe2, 0x66, 0x90, 0x3c, 0x83, 0x0d, 0x46, 0x64, 0x4c, 0x1f, 0x9a, 0x08, 0x9c,
+			0x24, 0xbd, 0xd2, 0x03, 0x53, 0x15, 0xd3, 0x8b, 0xbc, 0xc0, 0x32, 0x14, 0x21, 0x07, 0x55, 0x05,
+		];
+		let mg = new(&KEY).unwrap();
+
+		let encrypted = gost3413::encrypt_cfb(&mg, &PLAIN, &iv).unwrap();
+		assert_eq!(&encrypted[..], &expected[..]);
+		assert_eq!(gost3413::decrypt_cfb(&mg, &encrypted, &iv).unwrap(), &PLAIN[..]);
+		assert_eq!(gost3413::encrypt_cfb(&mg, &PLAIN[..13], &iv).unwrap(), &expected[..13]);
+		assert_eq!(gost3413::decrypt_cfb(&mg, &expected[..13], &iv).unwrap(), &PLAIN[..13]);
+	}
+
+	#[test]
+	fn test_mac() {
+		let expected = [0x15, 0x4e, 0x72, 0x10, 0x20, 0x30, 0xc5, 0xbb];
+		let mg = new(&KEY).unwrap();
+
+		assert_eq!(gost3413::mac(&mg, &PLAIN, 8).unwrap(), expected);
+		assert_eq!(gost3413::mac(&mg, &PLAIN, 4).unwrap(), &expected[..4]);
+		assert_eq!(gost3413::verify_mac(&mg, &PLAIN, &expected[..4]), Ok(()));
+		assert_eq!(gost3413::verify_mac(&mg, &PLAIN[1..], &expected[..4]), Err(Error::AuthenticationFailed));
+		assert_ne!(gost3413::mac(&mg, &PLAIN[..13], 8).unwrap(), gost3413::mac(&mg, &PLAIN[..12], 8).unwrap());
+		assert_eq!(gost3413::mac(&mg, &PLAIN, 9), Err(Error::InvalidTagLength{len: 9, min: 1, max: 8}));
+	}
+
+	#[test]
+	fn test_errors() {
+		let mg = new(&KEY).unwrap();
+
+		assert_eq!(gost3413::encrypt_ctr(&mg, &PLAIN, &KEY[..8]), Err(Error::InvalidIvLength{len: 8, expected: 4}));
+		assert_eq!(gost3413::encrypt_ofb(&mg, &PLAIN, &KEY[..12]), Err(Error::InvalidIvLength{len: 12, expected: 8}));
+		assert_eq!(gost3413::encrypt_cbc(&mg, &PLAIN, &[], Padding::Pkcs7), Err(Error::InvalidIvLength{len: 0, expected: 8}));
+		assert_eq!(gost3413::encrypt_cfb(&mg, &[], &KEY[..8]), Err(Error::EmptyInput));
+		assert_eq!(gost3413::decrypt_cbc(&mg, &PLAIN[..9], &KEY[..8], Padding::Pkcs7),
+		           Err(Error::InvalidCiphertextLength{len: 9, block_size: 8}));
+	}
+}
@@ -0,0 +1,244 @@
+/*
+* Module: modes::cfb
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use super::{block_size, MAX_BLOCK_SIZE};
+use BlockCipher;
+use Error;
+use rand::Rng;
+
+/// CFB mode of a block cipher with 'segment_size' bytes segments,
+/// e.g. 1 for CFB-8 or the block size for full block CFB (CFB-64 for
+/// 8 bytes blocks). Data may be passed in pieces of any length,
+/// partial segments are continued by the next call. No padding is used.
+pub struct Cfb<'a, C: 'a + BlockCipher> {
+	cipher: &'a C,
+	register: [u8; MAX_BLOCK_SIZE],
+	keystream: [u8; MAX_BLOCK_SIZE],
+	segment: [u8; MAX_BLOCK_SIZE],
+	segment_size: usize,
+	pos: usize,
+}
+
+impl<'a, C: BlockCipher> Cfb<'a, C> {
+	/// Creates CFB state for 'cipher' with shift register initialised with 'iv'.
+	pub fn new(cipher: &'a C, iv: &[u8], segment_size: usize) -> Result<Cfb<'a, C>, Error> {
+		let bs = block_size::<C>()?;
+		// handle caller mistakes
+		if iv.len() != bs { return Err(Error::InvalidIvLength{len: iv.len(), expected: bs}) }
+		if segment_size == 0 || segment_size > bs {
+			return Err(Error::InvalidSegmentSize{size: segment_size, block_size: bs})
+		}
+
+		let mut cfb = Cfb {
+			cipher,
+			register: [0u8; MAX_BLOCK_SIZE],
+			keystream: [0u8; MAX_BLOCK_SIZE],
+			segment: [0u8; MAX_BLOCK_SIZE],
+			segment_size,
+			pos: 0,
+		};
+		cfb.register[..bs].copy_from_slice(iv);
+		Ok(cfb)
+	}
+
+	/// Encrypts 'data' in place.
+	pub fn encrypt(&mut self, data: &mut [u8]) {
+		for b in data.iter_mut() {
+			let c = *b ^ self.next_byte();
+			self.feed(c);
+			*b = c;
+		}
+	}
+
+	/// Decrypts 'data' in place.
+	pub fn decrypt(&mut self, data: &mut [u8]) {
+		for b in data.iter_mut() {
+			let c = *b;
+			*b ^= self.next_byte();
+			self.feed(c);
+		}
+	}
+
+	/// Returns keystream byte for the current position of the segment.
+	fn next_byte(&mut self) -> u8 {
+		if self.pos == 0 {
+			let bs = C::BLOCK_SIZE;
+			self.keystream = self.register;
+			self.cipher.encrypt_block(&mut self.keystream[..bs]);
+		}
+		self.keystream[self.pos]
+	}
+
+	/// Stores cipher text byte 'c' of the segment, the complete segment
+	/// is shifted into the register.
+	fn feed(&mut self, c: u8) {
+		self.segment[self.pos] = c;
+		self.pos += 1;
+		if self.pos == self.segment_size {
+			let bs = C::BLOCK_SIZE;
+			let n = self.segment_size;
+			self.register.copy_within(n..bs, 0);
+			self.register[(bs - n)..bs].copy_from_slice(&self.segment[..n]);
+			self.pos = 0;
+		}
+	}
+}
+
+/// CFB : encrypts 'input' with 'cipher' in CFB mode using random generated IV.
+/// The IV is stored in the first block of the result.
+pub fn encrypt_cfb<C: BlockCipher>(cipher: &C, input: &[u8], segment_size: usize) -> Result<Vec<u8>, Error> {
+	let mut iv = [0u8; MAX_BLOCK_SIZE];
+	rand::thread_rng().fill(&mut iv);
+	encrypt_cfb_iv(cipher, input, &iv[..block_size::<C>()?], segment_size)
+}
+
+/// CFB : encrypts 'input' with 'cipher' in CFB mode using passed 'iv'.
+/// The IV is stored in the first block of the result, no padding is used.
+pub fn encrypt_cfb_iv<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8], segment_size: usize) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	let mut cfb = Cfb::new(cipher, iv, segment_size)?;
+	if input.is_empty() { return Err(Error::EmptyInput) }
+
+	let mut buffer = iv.to_vec();
+	buffer.extend_from_slice(input);
+	cfb.encrypt(&mut buffer[bs..]);
+	Ok(buffer)
+}
+
+/// CFB : decrypts 'input' with 'cipher' in CFB mode.
+/// The first block of 'input' is the IV.
+pub fn decrypt_cfb<C: BlockCipher>(cipher: &C, input: &[u8], segment_size: usize) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	// the IV plus at least one byte
+	if input.len() <= bs {
+		return Err(Error::InvalidCiphertextLength{len: input.len(), block_size: bs})
+	}
+
+	let mut cfb = Cfb::new(cipher, &input[..bs], segment_size)?;
+	let mut buffer = input[bs..].to_vec();
+	cfb.decrypt(&mut buffer);
+	Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use blowfish;
+	use blowfish::{Blowfish, ByteOrder};
+	use gost::Gost;
+	use way3::Way3;
+	use twofish::Twofish;
+
+	const KEY: [u8; 16] = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xf0, 0xe1, 0xd2, 0xc3, 0xb4, 0xa5, 0x96, 0x87];
+	const IV: [u8; 8] = [0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10];
+	const PLAIN: &[u8] = b"7654321 Now is the time for \0";
+
+	#[test]
+	fn test_bf_cfb64() {
+		// openssl enc -bf-cfb -K 0123456789abcdeff0e1d2c3b4a59687 -iv fedcba9876543210
+		let expected = [
+			0xe7, 0x32, 0x14, 0xa2, 0x82, 0x21, 0x39, 0xca, 0xf2, 0x6e, 0xcf, 0x6d, 0x2e, 0xb9, 0xe7, 0x6e,
+			0x3d, 0xa3, 0xde, 0x04, 0xd1, 0x51, 0x72, 0x00, 0x51, 0x9d, 0x57, 0xa6, 0xc3,
+		];
+		let cipher = blowfish::with_byte_order(&KEY, ByteOrder::Big).unwrap();
+
+		let encrypted = encrypt_cfb_iv(&cipher, PLAIN, &IV, 8).unwrap();
+		assert_eq!(&encrypted[..8], &IV);
+		assert_eq!(&encrypted[8..], &expected[..]);
+		assert_eq!(decrypt_cfb(&cipher, &encrypted, 8).unwrap(), PLAIN);
+	}
+
+	#[test]
+	fn test_bf_cfb8() {
+		// verified with libgcrypt (GCRY_CIPHER_MODE_CFB8)
+		let expected = [
+			0xe7, 0xbb, 0x1f, 0xc3, 0x07, 0x3e, 0xb8, 0x31, 0x4b, 0x37, 0x86, 0x89, 0xfa, 0xb0, 0xb1, 0x16,
+			0x02, 0x44, 0x41, 0x5d, 0x4d, 0x01, 0x7f, 0xce, 0x0d, 0x9b, 0x4c, 0xb2, 0xfa,
+		];
+		let cipher = blowfish::with_byte_order(&KEY, ByteOrder::Big).unwrap();
+
+		let encrypted = encrypt_cfb_iv(&cipher, PLAIN, &IV, 1).unwrap();
+		assert_eq!(&encrypted[8..], &expected[..]);
+		assert_eq!(decrypt_cfb(&cipher, &encrypted, 1).unwrap(), PLAIN);
+	}
+
+	fn check_streaming<C: BlockCipher>(key: &[u8]) {
+		let cipher = C::new(key).unwrap();
+		let bs = C::BLOCK_SIZE;
+		let iv = vec![0x5au8; bs];
+		let plain = "Artur, Błazej, Jolanta, Piotr Pszczółkowski".as_bytes();
+
+		for segment_size in 1..=bs {
+			let encrypted = encrypt_cfb_iv(&cipher, plain, &iv, segment_size).unwrap();
+			assert_eq!(decrypt_cfb(&cipher, &encrypted, segment_size).unwrap(), plain);
+
+			// the same result for data passed in pieces
+			for piece in 1..=(bs + 1) {
+				let mut data = plain.to_vec();
+				let mut cfb = Cfb::new(&cipher, &iv, segment_size).unwrap();
+				for chunk in data.chunks_mut(piece) {
+					cfb.encrypt(chunk);
+				}
+				assert_eq!(&data[..], &encrypted[bs..]);
+
+				let mut cfb = Cfb::new(&cipher, &iv, segment_size).unwrap();
+				for chunk in data.chunks_mut(piece) {
+					cfb.decrypt(chunk);
+				}
+				assert_eq!(&data[..], plain);
+			}
+		}
+
+		let encrypted = encrypt_cfb(&cipher, plain, bs).unwrap();
+		assert_eq!(decrypt_cfb(&cipher, &encrypted, bs).unwrap(), plain);
+	}
+
+	#[test]
+	fn test_streaming() {
+		check_streaming::<Blowfish>(b"TESTKEY");
+		check_streaming::<Gost>(b"12345678901234567890123456789012");
+		check_streaming::<Way3>(b"123456789012");
+		check_streaming::<Twofish>(b"0123456789abcdef");
+	}
+
+	#[test]
+	fn test_errors() {
+		let cipher = Blowfish::new(b"TESTKEY").unwrap();
+
+		assert!(Cfb::new(&cipher, &IV[..7], 8).is_err());
+		assert_eq!(Cfb::new(&cipher, &IV, 0).err(),
+		           Some(Error::InvalidSegmentSize{size: 0, block_size: 8}));
+		assert_eq!(Cfb::new(&cipher, &IV, 9).err(),
+		           Some(Error::InvalidSegmentSize{size: 9, block_size: 8}));
+		assert_eq!(encrypt_cfb(&cipher, &[], 8), Err(Error::EmptyInput));
+		assert_eq!(decrypt_cfb(&cipher, &IV, 8),
+		           Err(Error::InvalidCiphertextLength{len: 8, block_size: 8}));
+	}
+}
@@ -0,0 +1,271 @@
+/*
+* Module: modes::cmac
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use super::{block_size, xor, MAX_BLOCK_SIZE};
+use constant_time_eq;
+use BlockCipher;
+use Error;
+
+/// CMAC (OMAC1, NIST SP 800-38B) of a block cipher with 64 or 128 bit
+/// blocks. The message can be passed in parts of any size.
+pub struct Cmac<'a, C: 'a + BlockCipher> {
+	cipher: &'a C,
+	k1: [u8; MAX_BLOCK_SIZE],
+	k2: [u8; MAX_BLOCK_SIZE],
+	state: [u8; MAX_BLOCK_SIZE],
+	block: [u8; MAX_BLOCK_SIZE],	// the last, not processed block
+	nbytes: usize,					// number of bytes in 'block'
+}
+
+impl<'a, C: BlockCipher> Cmac<'a, C> {
+	/// Creates CMAC object for 'cipher'.
+	/// Subkeys K1 and K2 are derived from the encryption of the zero block.
+	pub fn new(cipher: &'a C) -> Result<Cmac<'a, C>, Error> {
+		let bs = block_size::<C>()?;
+		let b = subkey_constant::<C>()?;
+
+		let mut k1 = [0u8; MAX_BLOCK_SIZE];
+		cipher.encrypt_block(&mut k1[..bs]);
+		double(&mut k1[..bs], b);
+		let mut k2 = k1;
+		double(&mut k2[..bs], b);
+
+		Ok(Cmac {
+			cipher,
+			k1,
+			k2,
+			state: [0u8; MAX_BLOCK_SIZE],
+			block: [0u8; MAX_BLOCK_SIZE],
+			nbytes: 0,
+		})
+	}
+
+	/// Adds 'data' to the authenticated message. The last block
+	/// is kept in the buffer until finalize, even if complete.
+	pub fn update(&mut self, mut data: &[u8]) {
+		let bs = C::BLOCK_SIZE;
+		while !data.is_empty() {
+			if self.nbytes == bs {
+				xor(&mut self.state[..bs], &self.block[..bs]);
+				self.cipher.encrypt_block(&mut self.state[..bs]);
+				self.nbytes = 0;
+			}
+			let n = (bs - self.nbytes).min(data.len());
+			self.block[self.nbytes..(self.nbytes + n)].copy_from_slice(&data[..n]);
+			self.nbytes += n;
+			data = &data[n..];
+		}
+	}
+
+	/// Returns MAC of the message (one block).
+	/// The last block is xored with K1 if complete or padded
+	/// with 0x80 00 .. 00 and xored with K2 otherwise.
+	pub fn finalize(mut self) -> Vec<u8> {
+		let bs = C::BLOCK_SIZE;
+		xor(&mut self.state[..bs], &self.block[..self.nbytes]);
+		if self.nbytes == bs {
+			xor(&mut self.state[..bs], &self.k1[..bs]);
+		} else {
+			self.state[self.nbytes] ^= 0x80;
+			xor(&mut self.state[..bs], &self.k2[..bs]);
+		}
+		self.cipher.encrypt_block(&mut self.state[..bs]);
+		self.state[..bs].to_vec()
+	}
+
+	/// Checks in constant time if 'tag' (MAC truncated to 1..n bytes)
+	/// is the MAC of the message.
+	pub fn verify(self, tag: &[u8]) -> Result<(), Error> {
+		let bs = C::BLOCK_SIZE;
+		if tag.is_empty() || tag.len() > bs {
+			return Err(Error::InvalidTagLength{len: tag.len(), min: 1, max: bs})
+		}
+		if constant_time_eq(&self.finalize()[..tag.len()], tag) {
+			Ok(())
+		} else {
+			Err(Error::AuthenticationFailed)
+		}
+	}
+}
+
+/// CMAC : computes MAC of 'size' bytes (1..n) of 'input' with 'cipher'.
+pub fn cmac<C: BlockCipher>(cipher: &C, input: &[u8], size: usize) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	if size == 0 || size > bs { return Err(Error::InvalidTagLength{len: size, min: 1, max: bs}) }
+
+	let mut mac = Cmac::new(cipher)?;
+	mac.update(input);
+	let mut tag = mac.finalize();
+	tag.truncate(size);
+	Ok(tag)
+}
+
+/// CMAC : checks in constant time if 'tag' is the MAC of 'input'.
+pub fn verify_cmac<C: BlockCipher>(cipher: &C, input: &[u8], tag: &[u8]) -> Result<(), Error> {
+	let mut mac = Cmac::new(cipher)?;
+	mac.update(input);
+	mac.verify(tag)
+}
+
+/// Returns the constant of the subkey derivation for the block size,
+/// x^64 + x^4 + x^3 + x + 1 and x^128 + x^7 + x^2 + x + 1.
+fn subkey_constant<C: BlockCipher>() -> Result<u8, Error> {
+	match C::BLOCK_SIZE {
+		8 => Ok(0x1b),
+		16 => Ok(0x87),
+		size => Err(Error::UnsupportedBlockSize{size}),
+	}
+}
+
+/// Multiplies 'block' by x in GF(2^n): shifts it left by one bit
+/// and xors the last byte with 'b' if the first bit was set.
+fn double(block: &mut [u8], b: u8) {
+	let msb = block[0] >> 7;
+	for i in 0..(block.len() - 1) {
+		block[i] = (block[i] << 1) | (block[i + 1] >> 7);
+	}
+	let last = block.len() - 1;
+	block[last] = (block[last] << 1) ^ (b * msb);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use modes::unhex;
+	use blowfish::{self, Blowfish, ByteOrder};
+	use gost::{self, Gost, SBox};
+	use twofish::Twofish;
+	use way3::Way3;
+
+	// messages of the NIST SP 800-38B examples (D.1 - D.4)
+	const MESSAGE: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+	                       30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+
+	#[test]
+	fn test_subkeys() {
+		// subkeys of the AES-128 and TDEA examples of SP 800-38B from L = E(0)
+		let vectors = [
+			("7df76b0c1ab899b33e42f047b91b546f", 0x87, "fbeed618357133667c85e08f7236a8de", "f7ddac306ae266ccf90bc11ee46d513b"),
+			("c8cc74e98a7329a2", 0x1b, "9198e9d314e6535f", "2331d3a629cca6a5"),
+		];
+		for &(l, b, k1, k2) in vectors.iter() {
+			let mut k = unhex(l);
+			double(&mut k, b);
+			assert_eq!(k, unhex(k1));
+			double(&mut k, b);
+			assert_eq!(k, unhex(k2));
+		}
+	}
+
+	#[test]
+	fn test_vectors() {
+		let message = unhex(MESSAGE);
+
+		// key of the AES-128 examples, verified with libgcrypt (GCRY_MAC_CMAC_TWOFISH)
+		let tf = <Twofish as BlockCipher>::new(&unhex("2b7e151628aed2a6abf7158809cf4f3c")).unwrap();
+		let vectors = [
+			(0, "aaac00a56376d50a326c8d2999a6b27e"), (16, "dd382029f96579b4b8e7cf0d1fdfc139"),
+			(40, "143f1a18ae30617bc6cb2357e865cf30"), (64, "8f6e4fc9d240b221475d2be827f956ed"),
+		];
+		for &(n, expected) in vectors.iter() {
+			assert_eq!(cmac(&tf, &message[..n], 16).unwrap(), unhex(expected));
+		}
+
+		// 3-key TDEA examples, verified with libgcrypt (GCRY_MAC_CMAC_BLOWFISH)
+		let bf = blowfish::with_byte_order(&unhex("8aa83bf8cbda10620bc1bf19fbb6cd58bc313d4a371ca8b5"), ByteOrder::Big).unwrap();
+		let vectors = [(0, "980e211d6c070892"), (8, "acd14c414bbd3fd7"), (20, "32814228b751be19"), (32, "794179da95c85ec7")];
+		for &(n, expected) in vectors.iter() {
+			assert_eq!(cmac(&bf, &message[..n], 8).unwrap(), unhex(expected));
+		}
+
+		// key of the AES-256 examples, verified with libgcrypt (GCRY_MAC_CMAC_GOST28147)
+		let gt = gost::new(&unhex("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"), SBox::Test).unwrap();
+		let vectors = [(0, "97ac44e1513519c4"), (8, "a603f35f66ce6ac3"), (20, "529d68d03a889bc0"), (32, "9f817fe4db3f7fb1")];
+		for &(n, expected) in vectors.iter() {
+			assert_eq!(cmac(&gt, &message[..n], 8).unwrap(), unhex(expected));
+		}
+	}
+
+	fn check_update<C: BlockCipher>(key: &[u8]) {
+		let cipher = C::new(key).unwrap();
+		let message = unhex(MESSAGE);
+		for n in 0..message.len() {
+			let expected = cmac(&cipher, &message[..n], C::BLOCK_SIZE).unwrap();
+			for step in 1..20 {
+				let mut mac = Cmac::new(&cipher).unwrap();
+				for chunk in message[..n].chunks(step) {
+					mac.update(chunk);
+				}
+				assert_eq!(mac.finalize(), expected);
+			}
+		}
+	}
+
+	#[test]
+	fn test_update() {
+		check_update::<Blowfish>(b"TESTKEY");
+		check_update::<Gost>(b"12345678901234567890123456789012");
+		check_update::<Twofish>(b"0123456789abcdef");
+	}
+
+	#[test]
+	fn test_verify() {
+		let tf = <Twofish as BlockCipher>::new(b"0123456789abcdef").unwrap();
+		let tag = cmac(&tf, b"Yamato & Musashi", 16).unwrap();
+		assert_eq!(cmac(&tf, b"Yamato & Musashi", 8).unwrap(), &tag[..8]);
+
+		assert_eq!(verify_cmac(&tf, b"Yamato & Musashi", &tag), Ok(()));
+		assert_eq!(verify_cmac(&tf, b"Yamato & Musashi", &tag[..8]), Ok(()));
+		assert_eq!(verify_cmac(&tf, b"Yamato & Musashi!", &tag), Err(Error::AuthenticationFailed));
+		let mut tampered = tag.clone();
+		tampered[15] ^= 0x01;
+		assert_eq!(verify_cmac(&tf, b"Yamato & Musashi", &tampered), Err(Error::AuthenticationFailed));
+
+		// the padded message differs from the complete one
+		assert_ne!(cmac(&tf, b"Yamato & Musash", 16).unwrap(), cmac(&tf, b"Yamato & Musash\x80", 16).unwrap());
+	}
+
+	#[test]
+	fn test_errors() {
+		let bf = <Blowfish as BlockCipher>::new(b"TESTKEY").unwrap();
+		assert_eq!(cmac(&bf, b"x", 0), Err(Error::InvalidTagLength{len: 0, min: 1, max: 8}));
+		assert_eq!(cmac(&bf, b"x", 9), Err(Error::InvalidTagLength{len: 9, min: 1, max: 8}));
+		assert_eq!(verify_cmac(&bf, b"x", &[]), Err(Error::InvalidTagLength{len: 0, min: 1, max: 8}));
+		assert_eq!(verify_cmac(&bf, b"x", &[0u8; 9]), Err(Error::InvalidTagLength{len: 9, min: 1, max: 8}));
+	}
+
+	#[test]
+	fn test_block_size() {
+		let w3 = <Way3 as BlockCipher>::new(b"123456789012").unwrap();
+		assert!(Cmac::new(&w3).is_err());
+		assert_eq!(cmac(&w3, b"x", 12), Err(Error::UnsupportedBlockSize{size: 12}));
+		assert_eq!(verify_cmac(&w3, b"x", &[0u8; 12]), Err(Error::UnsupportedBlockSize{size: 12}));
+	}
+}
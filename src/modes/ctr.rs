@@ -0,0 +1,313 @@
+/*
+* Module: modes::ctr
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use super::{block_size, xor, MAX_BLOCK_SIZE};
+use BlockCipher;
+use Error;
+use rand::Rng;
+
+/// Byte order of the counter in the counter block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+	/// The last byte of the counter is the least significant (NIST SP 800-38A).
+	Big,
+	/// The first byte of the counter is the least significant.
+	Little,
+}
+
+/// CTR mode keystream of a block cipher.
+/// The counter block is a nonce followed by 'counter_size' bytes of the counter.
+/// The counter starts at the value passed in the initial block and wraps
+/// around inside its own bytes, it never changes the nonce.
+/// Encryption and decryption are the same operation (apply_keystream).
+pub struct Ctr<'a, C: 'a + BlockCipher> {
+	cipher: &'a C,
+	iv: [u8; MAX_BLOCK_SIZE],
+	counter: [u8; MAX_BLOCK_SIZE],
+	keystream: [u8; MAX_BLOCK_SIZE],
+	counter_size: usize,
+	endian: Endian,
+	offset: u64,
+}
+
+impl<'a, C: BlockCipher> Ctr<'a, C> {
+	/// Creates CTR keystream for 'cipher' starting with counter block 'iv'.
+	/// The last 'counter_size' bytes of 'iv' are the counter, the rest is the nonce.
+	pub fn new(cipher: &'a C, iv: &[u8], counter_size: usize, endian: Endian) -> Result<Ctr<'a, C>, Error> {
+		let bs = block_size::<C>()?;
+		// handle caller mistakes
+		if iv.len() != bs { return Err(Error::InvalidIvLength{len: iv.len(), expected: bs}) }
+		if counter_size == 0 || counter_size > bs {
+			return Err(Error::InvalidCounterSize{size: counter_size, block_size: bs})
+		}
+
+		let mut ctr = Ctr {
+			cipher,
+			iv: [0u8; MAX_BLOCK_SIZE],
+			counter: [0u8; MAX_BLOCK_SIZE],
+			keystream: [0u8; MAX_BLOCK_SIZE],
+			counter_size,
+			endian,
+			offset: 0,
+		};
+		ctr.iv[..bs].copy_from_slice(iv);
+		ctr.counter = ctr.iv;
+		Ok(ctr)
+	}
+
+	/// Current position in the stream (in bytes).
+	pub fn position(&self) -> u64 {
+		self.offset
+	}
+
+	/// Moves to byte 'offset' of the stream,
+	/// next apply_keystream starts with keystream byte at this position.
+	pub fn seek(&mut self, offset: u64) {
+		let bs = C::BLOCK_SIZE as u64;
+		self.counter = self.iv;
+		self.add(offset / bs);
+		self.offset = offset;
+		if !offset.is_multiple_of(bs) {
+			self.next_block();
+		}
+	}
+
+	/// xors 'data' in place with the keystream (encrypts or decrypts).
+	pub fn apply_keystream(&mut self, data: &mut [u8]) {
+		let bs = C::BLOCK_SIZE;
+		let mut i = 0;
+		while i < data.len() {
+			let pos = (self.offset % bs as u64) as usize;
+			if pos == 0 {
+				self.next_block();
+			}
+			let n = (bs - pos).min(data.len() - i);
+			xor(&mut data[i..(i + n)], &self.keystream[pos..(pos + n)]);
+			self.offset += n as u64;
+			i += n;
+		}
+	}
+
+	/// Encrypts the counter block to the keystream buffer
+	/// and increments the counter.
+	fn next_block(&mut self) {
+		let bs = C::BLOCK_SIZE;
+		self.keystream = self.counter;
+		self.cipher.encrypt_block(&mut self.keystream[..bs]);
+		self.add(1);
+	}
+
+	/// Adds 'n' to the counter (modulo 2^(8 * counter_size)).
+	fn add(&mut self, n: u64) {
+		let bs = C::BLOCK_SIZE;
+		let start = bs - self.counter_size;
+		let mut carry = n;
+		for i in 0..self.counter_size {
+			if carry == 0 { break }
+			let idx = match self.endian {
+				Endian::Big => bs - 1 - i,
+				Endian::Little => start + i,
+			};
+			let sum = self.counter[idx] as u64 + (carry & 0xff);
+			self.counter[idx] = sum as u8;
+			carry = (carry >> 8) + (sum >> 8);
+		}
+	}
+}
+
+/// CTR : encrypts 'input' with 'cipher' in CTR mode using random generated IV.
+/// The IV is stored in the first block of the result.
+pub fn encrypt_ctr<C: BlockCipher>(cipher: &C, input: &[u8]) -> Result<Vec<u8>, Error> {
+	let mut iv = [0u8; MAX_BLOCK_SIZE];
+	rand::thread_rng().fill(&mut iv);
+	encrypt_ctr_iv(cipher, input, &iv[..block_size::<C>()?])
+}
+
+/// CTR : encrypts 'input' with 'cipher' in CTR mode using passed 'iv'
+/// as the initial counter block (whole block is a big-endian counter).
+/// The IV is stored in the first block of the result, no padding is used.
+pub fn encrypt_ctr_iv<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	if input.is_empty() { return Err(Error::EmptyInput) }
+
+	let mut ctr = Ctr::new(cipher, iv, bs, Endian::Big)?;
+	let mut buffer = iv.to_vec();
+	buffer.extend_from_slice(input);
+	ctr.apply_keystream(&mut buffer[bs..]);
+	Ok(buffer)
+}
+
+/// CTR : decrypts 'input' with 'cipher' in CTR mode.
+/// The first block of 'input' is the IV.
+pub fn decrypt_ctr<C: BlockCipher>(cipher: &C, input: &[u8]) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	// the IV plus at least one byte
+	if input.len() <= bs {
+		return Err(Error::InvalidCiphertextLength{len: input.len(), block_size: bs})
+	}
+
+	let mut ctr = Ctr::new(cipher, &input[..bs], bs, Endian::Big)?;
+	let mut buffer = input[bs..].to_vec();
+	ctr.apply_keystream(&mut buffer);
+	Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use blowfish::Blowfish;
+	use gost::Gost;
+	use way3::Way3;
+	use twofish::Twofish;
+
+	const PLAIN: &str = "Artur, Błazej, Jolanta, Piotr Pszczółkowski";
+
+	#[test]
+	fn test_twofish_vector() {
+		// verified with libgcrypt (GCRY_CIPHER_MODE_CTR)
+		let expected = [
+			0xd0, 0x8b, 0x4d, 0x06, 0xfc, 0x92, 0xc3, 0x2b, 0x71, 0x92, 0x4d, 0x50, 0x31, 0xed, 0x44, 0x4b,
+			0x5e, 0x74, 0x80, 0x50, 0x38, 0x8d, 0xaa, 0x26, 0x97, 0x7c, 0x5d, 0x89, 0xec, 0xd5, 0xca, 0xbd,
+			0x61, 0x25, 0xc4, 0x6f, 0x10, 0xcb, 0x6a, 0x93, 0x97, 0xde, 0x51, 0x73, 0xf4, 0xbc,
+		];
+		let cipher = Twofish::new(b"0123456789abcdef").unwrap();
+		let iv: Vec<u8> = (0xf0..=0xff).collect();
+
+		let encrypted = encrypt_ctr_iv(&cipher, PLAIN.as_bytes(), &iv).unwrap();
+		assert_eq!(&encrypted[..16], &iv[..]);
+		assert_eq!(&encrypted[16..], &expected[..]);
+		assert_eq!(decrypt_ctr(&cipher, &encrypted).unwrap(), PLAIN.as_bytes());
+	}
+
+	fn check_seek<C: BlockCipher>(key: &[u8], endian: Endian) {
+		let cipher = C::new(key).unwrap();
+		let bs = C::BLOCK_SIZE;
+		let iv = vec![0xfdu8; bs];
+		let mut stream = vec![0u8; 20 * bs + 5];
+		Ctr::new(&cipher, &iv, 4, endian).unwrap().apply_keystream(&mut stream);
+
+		// random access
+		for offset in 0..stream.len() {
+			let mut ctr = Ctr::new(&cipher, &iv, 4, endian).unwrap();
+			ctr.seek(offset as u64);
+			let mut data = vec![0u8; stream.len() - offset];
+			ctr.apply_keystream(&mut data);
+			assert_eq!(&data[..], &stream[offset..]);
+			assert_eq!(ctr.position(), stream.len() as u64);
+		}
+
+		// many small pieces
+		let mut ctr = Ctr::new(&cipher, &iv, 4, endian).unwrap();
+		let mut data = vec![0u8; stream.len()];
+		for chunk in data.chunks_mut(3) {
+			ctr.apply_keystream(chunk);
+		}
+		assert_eq!(data, stream);
+	}
+
+	#[test]
+	fn test_seek() {
+		for endian in &[Endian::Big, Endian::Little] {
+			check_seek::<Blowfish>(b"TESTKEY", *endian);
+			check_seek::<Gost>(b"12345678901234567890123456789012", *endian);
+			check_seek::<Way3>(b"123456789012", *endian);
+			check_seek::<Twofish>(b"0123456789abcdef", *endian);
+		}
+	}
+
+	#[test]
+	fn test_counter() {
+		let cipher = Blowfish::new(b"TESTKEY").unwrap();
+		let keystream = |block: &[u8]| {
+			let mut b = block.to_vec();
+			cipher.encrypt_block(&mut b);
+			b
+		};
+		let iv = [1u8, 2, 3, 4, 5, 6, 0xff, 0xfe];
+
+		// big-endian: ... 0xff 0xfe -> 0xff 0xff -> 0x00 0x00 (nonce unchanged)
+		let mut ctr = Ctr::new(&cipher, &iv, 2, Endian::Big).unwrap();
+		let mut data = [0u8; 24];
+		ctr.apply_keystream(&mut data);
+		assert_eq!(&data[..8], &keystream(&iv)[..]);
+		assert_eq!(&data[8..16], &keystream(&[1, 2, 3, 4, 5, 6, 0xff, 0xff])[..]);
+		assert_eq!(&data[16..], &keystream(&[1, 2, 3, 4, 5, 6, 0, 0])[..]);
+
+		// little-endian: 0xff 0xfe -> 0x00 0xff
+		let mut ctr = Ctr::new(&cipher, &iv, 2, Endian::Little).unwrap();
+		ctr.seek(8);
+		let mut data = [0u8; 8];
+		ctr.apply_keystream(&mut data);
+		assert_eq!(&data[..], &keystream(&[1, 2, 3, 4, 5, 6, 0, 0xff])[..]);
+
+		// one byte counter wraps after 256 blocks
+		let mut ctr = Ctr::new(&cipher, &iv, 1, Endian::Big).unwrap();
+		ctr.seek(256 * 8 + 3);
+		let mut data = [0u8; 5];
+		ctr.apply_keystream(&mut data);
+		assert_eq!(&data[..], &keystream(&iv)[3..]);
+	}
+
+	fn check_roundtrip<C: BlockCipher>(key: &[u8]) {
+		let cipher = C::new(key).unwrap();
+		let plain = PLAIN.as_bytes();
+
+		for n in 1..plain.len() {
+			let encrypted = encrypt_ctr(&cipher, &plain[..n]).unwrap();
+			assert_eq!(encrypted.len(), C::BLOCK_SIZE + n);
+			assert_eq!(decrypt_ctr(&cipher, &encrypted).unwrap(), &plain[..n]);
+		}
+	}
+
+	#[test]
+	fn test_roundtrip() {
+		check_roundtrip::<Blowfish>(b"TESTKEY");
+		check_roundtrip::<Gost>(b"12345678901234567890123456789012");
+		check_roundtrip::<Way3>(b"123456789012");
+		check_roundtrip::<Twofish>(b"0123456789abcdef");
+	}
+
+	#[test]
+	fn test_errors() {
+		let cipher = Twofish::new(b"0123456789abcdef").unwrap();
+		let iv = [0u8; 16];
+
+		assert!(Ctr::new(&cipher, &iv[..15], 4, Endian::Big).is_err());
+		assert_eq!(Ctr::new(&cipher, &iv, 0, Endian::Big).err(),
+		           Some(Error::InvalidCounterSize{size: 0, block_size: 16}));
+		assert_eq!(Ctr::new(&cipher, &iv, 17, Endian::Little).err(),
+		           Some(Error::InvalidCounterSize{size: 17, block_size: 16}));
+		assert_eq!(encrypt_ctr(&cipher, &[]), Err(Error::EmptyInput));
+		assert_eq!(encrypt_ctr_iv(&cipher, b"x", &iv[..8]),
+		           Err(Error::InvalidIvLength{len: 8, expected: 16}));
+		assert_eq!(decrypt_ctr(&cipher, &iv),
+		           Err(Error::InvalidCiphertextLength{len: 16, block_size: 16}));
+	}
+}
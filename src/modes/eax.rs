@@ -0,0 +1,205 @@
+/*
+* Module: modes::eax
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use super::{block_size, xor, MAX_BLOCK_SIZE};
+use super::ctr::{Ctr, Endian};
+use super::cmac::Cmac;
+use constant_time_eq;
+use BlockCipher;
+use Error;
+
+/// EAX : encrypts 'input' with 'cipher' using 'nonce' (of any length)
+/// and authenticates it together with associated data 'ad'.
+/// The result is the cipher text followed by the tag of 'tag_size' bytes
+/// (1..block size). EAX uses only the encryption of the cipher,
+/// only ciphers with 64 and 128 bit blocks are supported.
+pub fn encrypt_eax<C: BlockCipher>(cipher: &C, input: &[u8], nonce: &[u8], ad: &[u8], tag_size: usize) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	if bs != 8 && bs != 16 { return Err(Error::UnsupportedBlockSize{size: bs}) }
+	if tag_size == 0 || tag_size > bs { return Err(Error::InvalidTagLength{len: tag_size, min: 1, max: bs}) }
+
+	let n = omac(cipher, 0, nonce)?;
+	let mut buffer = input.to_vec();
+	Ctr::new(cipher, &n, bs, Endian::Big)?.apply_keystream(&mut buffer);
+
+	let tag = tag(cipher, &n, ad, &buffer)?;
+	buffer.extend_from_slice(&tag[..tag_size]);
+	Ok(buffer)
+}
+
+/// EAX : checks the tag of 'input' (cipher text followed by the tag
+/// of 'tag_size' bytes) and decrypts it with 'cipher' using 'nonce'.
+/// Nothing is decrypted when the tag doesn't match.
+pub fn decrypt_eax<C: BlockCipher>(cipher: &C, input: &[u8], nonce: &[u8], ad: &[u8], tag_size: usize) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	if bs != 8 && bs != 16 { return Err(Error::UnsupportedBlockSize{size: bs}) }
+	if tag_size == 0 || tag_size > bs { return Err(Error::InvalidTagLength{len: tag_size, min: 1, max: bs}) }
+	if input.len() < tag_size {
+		return Err(Error::InvalidCiphertextLength{len: input.len(), block_size: bs})
+	}
+
+	let (data, expected) = input.split_at(input.len() - tag_size);
+	let n = omac(cipher, 0, nonce)?;
+	let tag = tag(cipher, &n, ad, data)?;
+	if !constant_time_eq(&tag[..tag_size], expected) {
+		return Err(Error::AuthenticationFailed)
+	}
+
+	let mut buffer = data.to_vec();
+	Ctr::new(cipher, &n, bs, Endian::Big)?.apply_keystream(&mut buffer);
+	Ok(buffer)
+}
+
+/// Returns the full tag: N xor OMAC1(ad) xor OMAC2(cipher text).
+fn tag<C: BlockCipher>(cipher: &C, n: &[u8], ad: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+	let mut tag = n.to_vec();
+	xor(&mut tag, &omac(cipher, 1, ad)?);
+	xor(&mut tag, &omac(cipher, 2, data)?);
+	Ok(tag)
+}
+
+/// OMAC^t : CMAC (OMAC1) of the block with 't' in its last byte followed by 'data'.
+fn omac<C: BlockCipher>(cipher: &C, t: u8, data: &[u8]) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	let mut block = [0u8; MAX_BLOCK_SIZE];
+	block[bs - 1] = t;
+
+	let mut mac = Cmac::new(cipher)?;
+	mac.update(&block[..bs]);
+	mac.update(data);
+	Ok(mac.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use modes::unhex;
+	use blowfish::Blowfish;
+	use gost::Gost;
+	use magma::Magma;
+	use twofish::Twofish;
+	use kuznyechik::Kuznyechik;
+	use way3::Way3;
+
+	// inputs of the EAX paper vectors (message, key, nonce, header) with Twofish,
+	// verified with libgcrypt (GCRY_CIPHER_MODE_EAX)
+	const VECTORS: [(&str, &str, &str, &str, &str); 10] = [
+		("", "233952DEE4D5ED5F9B9C6D6FF80FF478", "62EC67F9C3A4A407FCB2A8C49031A8B3", "6BFB914FD07EAE6B",
+		 "F55BD54783175E1E41F8324B2610B5D5"),
+		("F7FB", "91945D3F4DCBEE0BF45EF52255F095A4", "BECAF043B0A23D843194BA972C66DEBD", "FA3BFD4806EB53FA",
+		 "6DBF9D29030DE75745041D1AA612F41BC058"),
+		("1A47CB4933", "01F74AD64077F2E704C0F60ADA3DD523", "70C3DB4F0D26368400A10ED05D2BFF5E", "234A3463C1264AC6",
+		 "0261A8A96C221E7515A2D7FD2A951E23444C54EABC"),
+		("481C9E39B1", "D07CF6CBB7F313BDDE66B727AFD3C5E8", "8408DFFF3C1A2B1292DC199E46B7D617", "33CCE2EABFF5A79D",
+		 "34DAA382D8AB34CFAC85B06F4CDA52B54037298054"),
+		("40D0C07DA5E4", "35B6D0580005BBC12B0587124557D2C2", "FDB6B06676EEDC5C61D74276E1F8E816", "AEB96EAEBE2970E9",
+		 "DE32755C5CCF1935DDBFF6C8168D1BD23BA70F1AC3C8"),
+		("4DE3B35C3FC039245BD1FB7D", "BD8E6E11475E60B268784C38C62FEB22", "6EAC5C93072D8E8513F750935E46DA1B", "D4482D1CA78DCE0F",
+		 "F45EA1FC47BADCF612911F38A96EF746BA845A9AED25B6EADF7B11B0"),
+		("8B0A79306C9CE7ED99DAE4F87F8DD61636", "7C77D6E813BED5AC98BAA417477A2E7D", "1A8C98DCD73D38393B2BF1569DEEFC19", "65D2017990D62528",
+		 "5EECFAC17046FCACB14C419C9B5E9AB3FC26B0626AB0BC8834F5461B3C2EA875E1"),
+		("1BDA122BCE8A8DBAF1877D962B8592DD2D56", "5FFF20CAFAB119CA2FC73549E20F5B0D", "DDE59B97D722156D4D9AFF2BC7559826", "54B9F04E6A09189A",
+		 "4BE7C9BC9F3D3DAB66536BD793BD2EA0B227DD600F83E5FCFF83346A99E88D9945F5"),
+		("6CF36720872B8513F6EAB1A8A44438D5EF11", "A4A4782BCFFD3EC5E7EF6D8C34A56123", "B781FCF2F75FA5A8DE97A9CA48E522EC", "899A175897561D7E",
+		 "EB7695A5B664136C3F50DFA89B8E70BEFE57D7D26F6FB649B632CF982292B14CA6A7"),
+		("CA40D7446E545FFAED3BD12A740A659FFBBB3CEAB7", "8395FCF1E95BEBD697BD010BC766AAC3", "22E7ADD93CFC6393C57EC0B3C17D6B44", "126735FCC320D25A",
+		 "6088578665CD9E4AA55A7D8316CEED3B1F0A46ED844C63AA5CBFEC3E3E29307B14B986FB29"),
+	];
+
+	#[test]
+	fn test_vectors() {
+		for &(plain, key, nonce, header, expected) in VECTORS.iter() {
+			let (plain, nonce, header, expected) = (unhex(plain), unhex(nonce), unhex(header), unhex(expected));
+			let tf = <Twofish as BlockCipher>::new(&unhex(key)).unwrap();
+
+			let encrypted = encrypt_eax(&tf, &plain, &nonce, &header, 16).unwrap();
+			assert_eq!(encrypted, expected);
+			assert_eq!(decrypt_eax(&tf, &encrypted, &nonce, &header, 16).unwrap(), plain);
+
+			// shorter tag is the prefix of the full one
+			let encrypted = encrypt_eax(&tf, &plain, &nonce, &header, 4).unwrap();
+			assert_eq!(encrypted, &expected[..(plain.len() + 4)]);
+			assert_eq!(decrypt_eax(&tf, &encrypted, &nonce, &header, 4).unwrap(), plain);
+		}
+	}
+
+	fn check_roundtrip<C: BlockCipher>(key: &[u8]) {
+		let cipher = C::new(key).unwrap();
+		let bs = C::BLOCK_SIZE;
+		let plain = "Artur, Błazej, Jolanta, Piotr Pszczółkowski".as_bytes();
+		let nonce = b"nonce of any length";
+
+		for n in 0..plain.len() {
+			for tag_size in 1..=bs {
+				let encrypted = encrypt_eax(&cipher, &plain[..n], &nonce[..(n % nonce.len())], b"header", tag_size).unwrap();
+				assert_eq!(encrypted.len(), n + tag_size);
+				assert_eq!(decrypt_eax(&cipher, &encrypted, &nonce[..(n % nonce.len())], b"header", tag_size).unwrap(), &plain[..n]);
+			}
+		}
+
+		// every bit of the cipher text and the tag, the nonce and the header are authenticated
+		let encrypted = encrypt_eax(&cipher, b"Yamato & Musashi", nonce, b"header", bs).unwrap();
+		for i in 0..encrypted.len() {
+			let mut tampered = encrypted.clone();
+			tampered[i] ^= 0x01;
+			assert_eq!(decrypt_eax(&cipher, &tampered, nonce, b"header", bs), Err(Error::AuthenticationFailed));
+		}
+		assert_eq!(decrypt_eax(&cipher, &encrypted, &nonce[1..], b"header", bs), Err(Error::AuthenticationFailed));
+		assert_eq!(decrypt_eax(&cipher, &encrypted, nonce, b"Header", bs), Err(Error::AuthenticationFailed));
+		assert_eq!(decrypt_eax(&cipher, &encrypted, nonce, b"header", bs - 1), Err(Error::AuthenticationFailed));
+
+		// the header and the cipher text are not interchangeable
+		let a = encrypt_eax(&cipher, b"", nonce, b"data", bs).unwrap();
+		let b = encrypt_eax(&cipher, b"data", nonce, b"", bs).unwrap();
+		assert_ne!(&a[..], &b[4..]);
+	}
+
+	#[test]
+	fn test_roundtrip() {
+		check_roundtrip::<Blowfish>(b"TESTKEY");
+		check_roundtrip::<Gost>(b"12345678901234567890123456789012");
+		check_roundtrip::<Magma>(b"12345678901234567890123456789012");
+		check_roundtrip::<Twofish>(b"0123456789abcdef");
+		check_roundtrip::<Kuznyechik>(b"12345678901234567890123456789012");
+	}
+
+	#[test]
+	fn test_errors() {
+		let bf = <Blowfish as BlockCipher>::new(b"TESTKEY").unwrap();
+		assert_eq!(encrypt_eax(&bf, b"x", b"", b"", 0), Err(Error::InvalidTagLength{len: 0, min: 1, max: 8}));
+		assert_eq!(encrypt_eax(&bf, b"x", b"", b"", 9), Err(Error::InvalidTagLength{len: 9, min: 1, max: 8}));
+		assert_eq!(decrypt_eax(&bf, &[0u8; 8], b"", b"", 9), Err(Error::InvalidTagLength{len: 9, min: 1, max: 8}));
+		assert_eq!(decrypt_eax(&bf, &[0u8; 7], b"", b"", 8), Err(Error::InvalidCiphertextLength{len: 7, block_size: 8}));
+
+		let w3 = <Way3 as BlockCipher>::new(&[0u8; 12]).unwrap();
+		assert_eq!(encrypt_eax(&w3, b"x", b"", b"", 12), Err(Error::UnsupportedBlockSize{size: 12}));
+		assert_eq!(decrypt_eax(&w3, &[0u8; 24], b"", b"", 12), Err(Error::UnsupportedBlockSize{size: 12}));
+	}
+}
@@ -0,0 +1,268 @@
+/*
+* Module: modes::etm
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use super::{block_size, encrypt_cbc_iv, decrypt_cbc, encrypt_ctr_iv, decrypt_ctr, MAX_BLOCK_SIZE};
+use sha512::{Hmac, DIGEST_SIZE};
+use constant_time_eq;
+use BlockCipher;
+use Error;
+use Padding;
+use rand::Rng;
+
+/// Size of the authentication tag in bytes
+/// (HMAC-SHA-512 truncated to 256 bits).
+pub const TAG_SIZE: usize = 32;
+/// The shortest accepted master key.
+pub const MASTER_KEY_SIZE_MIN: usize = 16;
+/// The longest accepted master key.
+pub const MASTER_KEY_SIZE_MAX: usize = DIGEST_SIZE;
+
+/// Encryption mode used by Etm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+	/// CBC with the last block filled with 'Padding'.
+	Cbc(Padding),
+	/// CTR with the whole IV as a big-endian counter.
+	Ctr,
+}
+
+/// Encrypt-then-MAC authenticated encryption with any block cipher 'C'.
+/// The cipher key and the HMAC-SHA-512 key are derived from one master key.
+/// The result is the IV, the cipher text and the tag computed over the
+/// associated data, the IV, the cipher text and the length of the
+/// associated data in bits (as in RFC 7518, 5.2.2.1).
+pub struct Etm<C: BlockCipher> {
+	cipher: C,
+	mac_key: [u8; DIGEST_SIZE],
+	mode: Mode,
+}
+
+impl<C: BlockCipher> Etm<C> {
+	/// Creates new Etm object for 'master_key' (16..64 random bytes, not a password)
+	/// encrypting in 'mode'. Keys for other modes are independent.
+	pub fn new(master_key: &[u8], mode: Mode) -> Result<Etm<C>, Error> {
+		let len = master_key.len();
+		if !(MASTER_KEY_SIZE_MIN..=MASTER_KEY_SIZE_MAX).contains(&len) {
+			return Err(Error::InvalidKeyLength{len, min: MASTER_KEY_SIZE_MIN, max: MASTER_KEY_SIZE_MAX})
+		}
+
+		let (encryption, authentication): (&[u8], &[u8]) = match mode {
+			Mode::Cbc(_) => (b"EtM CBC encryption key", b"EtM CBC authentication key"),
+			Mode::Ctr => (b"EtM CTR encryption key", b"EtM CTR authentication key"),
+		};
+		let key = derive_key(master_key, encryption);
+		let cipher = C::new(&key[..C::KEY_SIZE_MAX.min(DIGEST_SIZE)])?;
+		let mac_key = derive_key(master_key, authentication);
+		Ok(Etm{cipher, mac_key, mode})
+	}
+
+	/// Encrypts 'input' using random generated IV
+	/// and authenticates it together with 'ad'.
+	pub fn encrypt(&self, input: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error> {
+		let mut iv = [0u8; MAX_BLOCK_SIZE];
+		rand::thread_rng().fill(&mut iv);
+		self.encrypt_iv(input, &iv[..block_size::<C>()?], ad)
+	}
+
+	/// Encrypts 'input' using passed 'iv' and authenticates it together with 'ad'.
+	/// In CTR mode 'iv' must never be reused with the same key.
+	pub fn encrypt_iv(&self, input: &[u8], iv: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error> {
+		let mut buffer = match self.mode {
+			Mode::Cbc(padding) => encrypt_cbc_iv(&self.cipher, input, iv, padding)?,
+			Mode::Ctr => encrypt_ctr_iv(&self.cipher, input, iv)?,
+		};
+		let tag = self.tag(&buffer, ad);
+		buffer.extend_from_slice(&tag[..TAG_SIZE]);
+		Ok(buffer)
+	}
+
+	/// Verifies the tag of 'input' and 'ad' and decrypts 'input'.
+	/// Nothing is decrypted when the tag doesn't match.
+	pub fn decrypt(&self, input: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error> {
+		let bs = block_size::<C>()?;
+		// the IV, at least one byte and the tag
+		if input.len() <= bs + TAG_SIZE {
+			return Err(Error::InvalidCiphertextLength{len: input.len(), block_size: bs})
+		}
+
+		let (data, tag) = input.split_at(input.len() - TAG_SIZE);
+		if !constant_time_eq(&self.tag(data, ad)[..TAG_SIZE], tag) {
+			return Err(Error::AuthenticationFailed)
+		}
+		match self.mode {
+			Mode::Cbc(padding) => decrypt_cbc(&self.cipher, data, padding),
+			Mode::Ctr => decrypt_ctr(&self.cipher, data),
+		}
+	}
+
+	/// HMAC of 'ad', 'data' (IV and cipher text) and the bit length of 'ad'.
+	fn tag(&self, data: &[u8], ad: &[u8]) -> [u8; DIGEST_SIZE] {
+		let mut mac = Hmac::new(&self.mac_key);
+		mac.update(ad);
+		mac.update(data);
+		mac.update(&(ad.len() as u64).wrapping_mul(8).to_be_bytes());
+		mac.finalize()
+	}
+}
+
+/// Derives key for 'info' from 'master_key':
+/// the first block of HKDF-Expand (RFC 5869) with HMAC-SHA-512.
+fn derive_key(master_key: &[u8], info: &[u8]) -> [u8; DIGEST_SIZE] {
+	let mut mac = Hmac::new(master_key);
+	mac.update(info);
+	mac.update(&[1]);
+	mac.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use blowfish::Blowfish;
+	use gost::Gost;
+	use way3::Way3;
+	use twofish::Twofish;
+	use magma::Magma;
+	use kuznyechik::Kuznyechik;
+	use sha512::hmac;
+
+	const MASTER_KEY: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+	fn check_roundtrip<C: BlockCipher>() {
+		let plain = "Artur, Błazej, Jolanta, Piotr Pszczółkowski".as_bytes();
+		for mode in [Mode::Cbc(Padding::Pkcs7), Mode::Ctr].iter() {
+			let etm = Etm::<C>::new(MASTER_KEY, *mode).unwrap();
+			for n in 1..plain.len() {
+				let encrypted = etm.encrypt(&plain[..n], b"header").unwrap();
+				assert_eq!(etm.decrypt(&encrypted, b"header").unwrap(), &plain[..n]);
+				assert_eq!(etm.decrypt(&encrypted, b"").err(), Some(Error::AuthenticationFailed));
+			}
+		}
+	}
+
+	fn check_tampering<C: BlockCipher>() {
+		let bs = C::BLOCK_SIZE;
+		for mode in [Mode::Cbc(Padding::Pkcs7), Mode::Ctr].iter() {
+			let etm = Etm::<C>::new(MASTER_KEY, *mode).unwrap();
+			let encrypted = etm.encrypt(b"Yamato & Musashi", b"header").unwrap();
+
+			// every bit of the IV, the cipher text and the tag is authenticated
+			for i in 0..encrypted.len() {
+				for bit in 0..8 {
+					let mut tampered = encrypted.clone();
+					tampered[i] ^= 1 << bit;
+					assert_eq!(etm.decrypt(&tampered, b"header"), Err(Error::AuthenticationFailed));
+				}
+			}
+			assert_eq!(etm.decrypt(&encrypted, b"Header"), Err(Error::AuthenticationFailed));
+			assert_eq!(etm.decrypt(&encrypted[..(encrypted.len() - 1)], b"header"), Err(Error::AuthenticationFailed));
+
+			// the other master key
+			let other = Etm::<C>::new(&MASTER_KEY[1..], *mode).unwrap();
+			assert_eq!(other.decrypt(&encrypted, b"header"), Err(Error::AuthenticationFailed));
+
+			let len = bs + TAG_SIZE;
+			assert_eq!(etm.decrypt(&vec![0u8; len], b""), Err(Error::InvalidCiphertextLength{len, block_size: bs}));
+		}
+	}
+
+	#[test]
+	fn test_roundtrip() {
+		check_roundtrip::<Blowfish>();
+		check_roundtrip::<Gost>();
+		check_roundtrip::<Way3>();
+		check_roundtrip::<Twofish>();
+		check_roundtrip::<Magma>();
+		check_roundtrip::<Kuznyechik>();
+	}
+
+	#[test]
+	fn test_tampering() {
+		check_tampering::<Blowfish>();
+		check_tampering::<Gost>();
+		check_tampering::<Way3>();
+		check_tampering::<Twofish>();
+		check_tampering::<Magma>();
+		check_tampering::<Kuznyechik>();
+	}
+
+	#[test]
+	fn test_vector() {
+		// Kuznyechik in CTR mode, the expected output is built step by step
+		// from HMAC-SHA-512 (RFC 4231 vectors in sha512::hmac) and the CTR mode
+		// of Kuznyechik (GOST R 34.13-2015, A.1.2 in kuznyechik)
+		let master_key: Vec<u8> = (0u8..32).collect();
+		let iv: Vec<u8> = (0xf0u8..=0xff).collect();
+		let (plain, ad) = (b"Yamato & Musashi, 1941", b"header");
+
+		// HKDF-Expand, one block: HMAC(master key, info || 0x01)
+		let key = hmac(&master_key, b"EtM CTR encryption key\x01");
+		let mac_key = hmac(&master_key, b"EtM CTR authentication key\x01");
+
+		// IV || cipher text || HMAC(ad || IV || cipher text || bit length of ad)[..32]
+		let kz = <Kuznyechik as BlockCipher>::new(&key[..32]).unwrap();
+		let mut expected = encrypt_ctr_iv(&kz, plain, &iv).unwrap();
+		let mut message = ad.to_vec();
+		message.extend_from_slice(&expected);
+		message.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 48]);
+		expected.extend_from_slice(&hmac(&mac_key, &message)[..32]);
+
+		let etm = Etm::<Kuznyechik>::new(&master_key, Mode::Ctr).unwrap();
+		let encrypted = etm.encrypt_iv(plain, &iv, ad).unwrap();
+		assert_eq!(encrypted, expected);
+		assert_eq!(encrypted.len(), 16 + plain.len() + TAG_SIZE);
+		assert_eq!(etm.decrypt(&encrypted, ad).unwrap(), plain);
+
+		// the CBC keys are different
+		let etm = Etm::<Kuznyechik>::new(&master_key, Mode::Cbc(Padding::Pkcs7)).unwrap();
+		assert_eq!(etm.decrypt(&encrypted, ad), Err(Error::AuthenticationFailed));
+	}
+
+	#[test]
+	fn test_verify_before_decrypt() {
+		// a block with malformed padding is rejected by the tag, not by unpadding
+		let etm = Etm::<Twofish>::new(MASTER_KEY, Mode::Cbc(Padding::Pkcs7)).unwrap();
+		let mut encrypted = etm.encrypt(b"Yamato & Musashi", b"").unwrap();
+		let n = encrypted.len() - TAG_SIZE - 1;
+		encrypted[n] ^= 0xff;
+		assert_eq!(etm.decrypt(&encrypted, b""), Err(Error::AuthenticationFailed));
+	}
+
+	#[test]
+	fn test_errors() {
+		assert_eq!(Etm::<Twofish>::new(&[0u8; 15], Mode::Ctr).err(),
+		           Some(Error::InvalidKeyLength{len: 15, min: 16, max: 64}));
+		assert_eq!(Etm::<Twofish>::new(&[0u8; 65], Mode::Ctr).err(),
+		           Some(Error::InvalidKeyLength{len: 65, min: 16, max: 64}));
+
+		let etm = Etm::<Twofish>::new(MASTER_KEY, Mode::Ctr).unwrap();
+		assert_eq!(etm.encrypt(b"", b"header"), Err(Error::EmptyInput));
+		assert_eq!(etm.encrypt_iv(b"x", &[0u8; 15], b""), Err(Error::InvalidIvLength{len: 15, expected: 16}));
+	}
+}
@@ -0,0 +1,369 @@
+/*
+* Module: modes::gcm
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use super::ctr::{Ctr, Endian};
+use constant_time_eq;
+use BlockCipher;
+use Error;
+
+const BLOCK_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;	// the recommended nonce size, other are hashed
+const TAG_SIZE_MIN: usize = 4;
+const DATA_SIZE_MAX: u64 = ((1 << 32) - 2) * BLOCK_SIZE as u64;	// 2^39 - 256 bits
+const R: u128 = 0xe1 << 120;	// x^128 + x^7 + x^2 + x + 1, bit reflected
+
+/// GCM (NIST SP 800-38D) streaming encryption or decryption
+/// with a 128-bit block cipher. The message can be passed in parts
+/// of any size, the associated data is passed at creation.
+/// Decrypted parts are not authenticated until verify returns Ok,
+/// use decrypt_gcm to get only the authenticated plain text.
+pub struct Gcm<'a, C: 'a + BlockCipher> {
+	ctr: Ctr<'a, C>,
+	h: u128,						// hash key, E(0)
+	s: u128,						// GHASH state
+	block: [u8; BLOCK_SIZE],	// not hashed cipher text
+	nbytes: usize,				// number of bytes in 'block'
+	ad_len: u64,
+	data_len: u64,
+	tag_mask: u128,				// E(J0)
+	tag_size: usize,
+}
+
+impl<'a, C: BlockCipher> Gcm<'a, C> {
+	/// Creates GCM object for 'cipher', 'nonce' (12 bytes recommended,
+	/// at least 1), associated data 'ad' and tags of 'tag_size' bytes
+	/// (4, 8 or 12..16).
+	pub fn new(cipher: &'a C, nonce: &[u8], ad: &[u8], tag_size: usize) -> Result<Gcm<'a, C>, Error> {
+		// handle caller mistakes
+		if C::BLOCK_SIZE != BLOCK_SIZE { return Err(Error::UnsupportedBlockSize{size: C::BLOCK_SIZE}) }
+		if !valid_tag_size(tag_size) {
+			return Err(Error::InvalidTagLength{len: tag_size, min: TAG_SIZE_MIN, max: BLOCK_SIZE})
+		}
+		if nonce.is_empty() { return Err(Error::InvalidIvLength{len: 0, expected: NONCE_SIZE}) }
+
+		let h = encrypt(cipher, 0);
+		let j0 = if nonce.len() == NONCE_SIZE {
+			let mut j0 = [0u8; BLOCK_SIZE];
+			j0[..NONCE_SIZE].copy_from_slice(nonce);
+			j0[BLOCK_SIZE - 1] = 1;
+			u128::from_be_bytes(j0)
+		} else {
+			let s = ghash(h, 0, nonce);
+			gf_mult(s ^ (nonce.len() as u128 * 8), h)
+		};
+
+		// the counter is the last 32 bits of the block
+		let mut icb = j0.to_be_bytes();
+		icb[12..].copy_from_slice(&(j0 as u32).wrapping_add(1).to_be_bytes());
+		Ok(Gcm {
+			ctr: Ctr::new(cipher, &icb, 4, Endian::Big)?,
+			h,
+			s: ghash(h, 0, ad),
+			block: [0u8; BLOCK_SIZE],
+			nbytes: 0,
+			ad_len: ad.len() as u64,
+			data_len: 0,
+			tag_mask: encrypt(cipher, j0),
+			tag_size,
+		})
+	}
+
+	/// Encrypts next part of the plain text in place.
+	/// The whole message can be at most 2^36 - 32 bytes long.
+	pub fn encrypt(&mut self, data: &mut [u8]) -> Result<(), Error> {
+		self.check_length(data.len())?;
+		self.ctr.apply_keystream(data);
+		self.absorb(data);
+		Ok(())
+	}
+
+	/// Decrypts next part of the cipher text in place.
+	/// The whole message can be at most 2^36 - 32 bytes long.
+	pub fn decrypt(&mut self, data: &mut [u8]) -> Result<(), Error> {
+		self.check_length(data.len())?;
+		self.absorb(data);
+		self.ctr.apply_keystream(data);
+		Ok(())
+	}
+
+	/// Returns tag of the associated data and the cipher text.
+	pub fn finalize(mut self) -> Vec<u8> {
+		self.tag()[..self.tag_size].to_vec()
+	}
+
+	/// Checks in constant time if 'tag' is the tag of the associated data
+	/// and the cipher text.
+	pub fn verify(mut self, tag: &[u8]) -> Result<(), Error> {
+		if tag.len() != self.tag_size {
+			return Err(Error::InvalidTagLength{len: tag.len(), min: self.tag_size, max: self.tag_size})
+		}
+		if constant_time_eq(&self.tag()[..self.tag_size], tag) {
+			Ok(())
+		} else {
+			Err(Error::AuthenticationFailed)
+		}
+	}
+
+	/// Checks that 'len' more bytes don't exceed the message length limit.
+	fn check_length(&self, len: usize) -> Result<(), Error> {
+		let len = self.data_len.saturating_add(len as u64);
+		if len > DATA_SIZE_MAX {
+			return Err(Error::MessageTooLong{len, max: DATA_SIZE_MAX})
+		}
+		Ok(())
+	}
+
+	/// Adds cipher text 'data' to GHASH, an incomplete block is kept
+	/// in the buffer until more data comes.
+	fn absorb(&mut self, mut data: &[u8]) {
+		self.data_len += data.len() as u64;
+
+		if self.nbytes > 0 {
+			let n = (BLOCK_SIZE - self.nbytes).min(data.len());
+			self.block[self.nbytes..(self.nbytes + n)].copy_from_slice(&data[..n]);
+			self.nbytes += n;
+			data = &data[n..];
+			if self.nbytes < BLOCK_SIZE {
+				return
+			}
+			self.s = gf_mult(self.s ^ u128::from_be_bytes(self.block), self.h);
+			self.nbytes = 0;
+		}
+
+		let n = data.len() / BLOCK_SIZE * BLOCK_SIZE;
+		self.s = ghash(self.h, self.s, &data[..n]);
+		self.nbytes = data.len() - n;
+		self.block[..self.nbytes].copy_from_slice(&data[n..]);
+	}
+
+	/// Returns the full tag: GHASH of the padded data and the lengths xored with E(J0).
+	fn tag(&mut self) -> [u8; BLOCK_SIZE] {
+		if self.nbytes > 0 {
+			self.s = ghash(self.h, self.s, &self.block[..self.nbytes]);
+			self.nbytes = 0;
+		}
+		let lengths = ((u128::from(self.ad_len) * 8) << 64) | (u128::from(self.data_len) * 8);
+		(gf_mult(self.s ^ lengths, self.h) ^ self.tag_mask).to_be_bytes()
+	}
+}
+
+/// GCM : encrypts 'input' (may be empty) with 128-bit block 'cipher' using
+/// 'nonce' and authenticates it together with associated data 'ad'.
+/// The result is the cipher text followed by the tag of 'tag_size' bytes
+/// (4, 8 or 12..16).
+pub fn encrypt_gcm<C: BlockCipher>(cipher: &C, input: &[u8], nonce: &[u8], ad: &[u8], tag_size: usize) -> Result<Vec<u8>, Error> {
+	let mut gcm = Gcm::new(cipher, nonce, ad, tag_size)?;
+	gcm.check_length(input.len())?;
+	let mut buffer = input.to_vec();
+	gcm.encrypt(&mut buffer)?;
+	buffer.extend(gcm.finalize());
+	Ok(buffer)
+}
+
+/// GCM : checks the tag of 'input' (cipher text followed by the tag
+/// of 'tag_size' bytes) and decrypts it with 'cipher' using 'nonce'.
+/// Nothing is decrypted when the tag doesn't match.
+pub fn decrypt_gcm<C: BlockCipher>(cipher: &C, input: &[u8], nonce: &[u8], ad: &[u8], tag_size: usize) -> Result<Vec<u8>, Error> {
+	let mut gcm = Gcm::new(cipher, nonce, ad, tag_size)?;
+	if input.len() < tag_size {
+		return Err(Error::InvalidCiphertextLength{len: input.len(), block_size: BLOCK_SIZE})
+	}
+
+	let (data, tag) = input.split_at(input.len() - tag_size);
+	gcm.check_length(data.len())?;
+	gcm.absorb(data);
+	if !constant_time_eq(&gcm.tag()[..tag_size], tag) {
+		return Err(Error::AuthenticationFailed)
+	}
+	let mut buffer = data.to_vec();
+	gcm.ctr.apply_keystream(&mut buffer);
+	Ok(buffer)
+}
+
+/// Returns true for the tag sizes allowed by SP 800-38D, 5.2.1.2.
+fn valid_tag_size(size: usize) -> bool {
+	size == 4 || size == 8 || (12..=BLOCK_SIZE).contains(&size)
+}
+
+/// Returns encrypted block 'x'.
+fn encrypt<C: BlockCipher>(cipher: &C, x: u128) -> u128 {
+	let mut block = x.to_be_bytes();
+	cipher.encrypt_block(&mut block);
+	u128::from_be_bytes(block)
+}
+
+/// Continues GHASH 's' with 'data', the last block is filled with zeros.
+fn ghash(h: u128, mut s: u128, data: &[u8]) -> u128 {
+	for chunk in data.chunks(BLOCK_SIZE) {
+		let mut block = [0u8; BLOCK_SIZE];
+		block[..chunk.len()].copy_from_slice(chunk);
+		s = gf_mult(s ^ u128::from_be_bytes(block), h);
+	}
+	s
+}
+
+/// Multiplies 'x' by 'y' in GF(2^128) of GCM (bit reflected),
+/// in constant time.
+fn gf_mult(x: u128, y: u128) -> u128 {
+	let mut z = 0u128;
+	let mut v = y;
+	for i in (0..128).rev() {
+		z ^= v & 0u128.wrapping_sub((x >> i) & 1);
+		v = (v >> 1) ^ (R & 0u128.wrapping_sub(v & 1));
+	}
+	z
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use modes::unhex;
+	use blowfish::Blowfish;
+	use twofish::Twofish;
+	use kuznyechik::Kuznyechik;
+
+	const KEY: &str = "feffe9928665731c6d6a8f9467308308";
+	const PLAIN: &str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39";
+	const AD: &str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+
+	// inputs of the GCM specification test cases 1, 2, 4, 5, 6 and 16 (key, nonce,
+	// associated data, plain text) with Twofish, verified with libgcrypt (GCRY_CIPHER_MODE_GCM)
+	const VECTORS: [(&str, &str, &str, &str, &str); 6] = [
+		("00000000000000000000000000000000", "000000000000000000000000", "", "",
+		 "ca737ff1fd0fe5b8e41e90358a5f2cb1"),
+		("00000000000000000000000000000000", "000000000000000000000000", "", "00000000000000000000000000000000",
+		 "f0fc7d86d814589a09d8ec136f95a124601e88c6010d9479b9bbed6f0ebac515"),
+		(KEY, "cafebabefacedbaddecaf888", AD, PLAIN,
+		 "d98a3cad8adeced9f76bf0ec859db8777812db80b98461c826001c7b2230aaed2a8cacf8479fe0976b6810ab3ac14783242eb78d526b7db0afeffed328905957ca1e450c83fb6af0464ace1e"),
+		(KEY, "cafebabefacedbad", AD, PLAIN,
+		 "fbb2d06625986afc2f7eb7081d24dfb0c6c9c9a4e85151918c71e5186906cfbf3a8df04590a54886cdf4fce0d6ce8ce0e19f81e021261e883e6861cba98f0b13f254466a16a3c220c51c72ee"),
+		(KEY, "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b", AD, PLAIN,
+		 "8a3d47d0492f6cb39f4b4a1e37a2928f8471dc8cf6d29237e900df7ca105fa2ab8a6057e05d2aaf95f5bac4c7b499f905aa6cd789226649279a84db3f9e8ce23b0def1cb5b03143d999ea165"),
+		("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308", "cafebabefacedbaddecaf888", AD, PLAIN,
+		 "3e93e852af45a38da1562b207f0405a0378434e7bba4ba1641b58dbfeaaf927f5df7006fb8bef45ca7207692fbd10988f859952a224f9c646f9503c5f5526a3bfafaa9801c3388f2560fa2b1"),
+	];
+
+	#[test]
+	fn test_vectors() {
+		for &(key, nonce, ad, plain, expected) in VECTORS.iter() {
+			let (nonce, ad, plain, expected) = (unhex(nonce), unhex(ad), unhex(plain), unhex(expected));
+			let tf = <Twofish as BlockCipher>::new(&unhex(key)).unwrap();
+
+			let encrypted = encrypt_gcm(&tf, &plain, &nonce, &ad, 16).unwrap();
+			assert_eq!(encrypted, expected);
+			assert_eq!(decrypt_gcm(&tf, &encrypted, &nonce, &ad, 16).unwrap(), plain);
+
+			// shorter tag is the prefix of the full one
+			let encrypted = encrypt_gcm(&tf, &plain, &nonce, &ad, 12).unwrap();
+			assert_eq!(encrypted, &expected[..(plain.len() + 12)]);
+			assert_eq!(decrypt_gcm(&tf, &encrypted, &nonce, &ad, 12).unwrap(), plain);
+		}
+	}
+
+	#[test]
+	fn test_streaming() {
+		let tf = <Twofish as BlockCipher>::new(&unhex(KEY)).unwrap();
+		let (nonce, ad, plain) = (unhex("cafebabefacedbaddecaf888"), unhex(AD), unhex(PLAIN));
+		let expected = encrypt_gcm(&tf, &plain, &nonce, &ad, 16).unwrap();
+		let (cipher_text, tag) = expected.split_at(plain.len());
+
+		for step in 1..40 {
+			let mut gcm = Gcm::new(&tf, &nonce, &ad, 16).unwrap();
+			let mut buffer = plain.clone();
+			for chunk in buffer.chunks_mut(step) {
+				gcm.encrypt(chunk).unwrap();
+			}
+			assert_eq!(buffer, cipher_text);
+			assert_eq!(gcm.finalize(), tag);
+
+			let mut gcm = Gcm::new(&tf, &nonce, &ad, 16).unwrap();
+			for chunk in buffer.chunks_mut(step) {
+				gcm.decrypt(chunk).unwrap();
+			}
+			assert_eq!(buffer, plain);
+			assert_eq!(gcm.verify(tag), Ok(()));
+		}
+	}
+
+	#[test]
+	fn test_tampering() {
+		let kuz = <Kuznyechik as BlockCipher>::new(b"12345678901234567890123456789012").unwrap();
+		let encrypted = encrypt_gcm(&kuz, b"Yamato & Musashi", b"123456789012", b"header", 16).unwrap();
+		for i in 0..encrypted.len() {
+			let mut tampered = encrypted.clone();
+			tampered[i] ^= 0x01;
+			assert_eq!(decrypt_gcm(&kuz, &tampered, b"123456789012", b"header", 16), Err(Error::AuthenticationFailed));
+		}
+		assert_eq!(decrypt_gcm(&kuz, &encrypted, b"123456789013", b"header", 16), Err(Error::AuthenticationFailed));
+		assert_eq!(decrypt_gcm(&kuz, &encrypted, b"123456789012", b"Header", 16), Err(Error::AuthenticationFailed));
+
+		let mut gcm = Gcm::new(&kuz, b"123456789012", b"header", 16).unwrap();
+		let mut buffer = encrypted[..16].to_vec();
+		gcm.decrypt(&mut buffer).unwrap();
+		assert_eq!(gcm.verify(&encrypted[17..]), Err(Error::InvalidTagLength{len: 15, min: 16, max: 16}));
+	}
+
+	#[test]
+	fn test_errors() {
+		let tf = <Twofish as BlockCipher>::new(&unhex(KEY)).unwrap();
+		assert_eq!(encrypt_gcm(&tf, b"x", b"123456789012", b"", 3), Err(Error::InvalidTagLength{len: 3, min: 4, max: 16}));
+		assert_eq!(encrypt_gcm(&tf, b"x", b"123456789012", b"", 17), Err(Error::InvalidTagLength{len: 17, min: 4, max: 16}));
+		for &size in [5, 6, 7, 9, 10, 11].iter() {
+			assert_eq!(encrypt_gcm(&tf, b"x", b"123456789012", b"", size), Err(Error::InvalidTagLength{len: size, min: 4, max: 16}));
+		}
+		for &size in [4, 8, 12, 13, 14, 15, 16].iter() {
+			let encrypted = encrypt_gcm(&tf, b"x", b"123456789012", b"", size).unwrap();
+			assert_eq!(decrypt_gcm(&tf, &encrypted, b"123456789012", b"", size).unwrap(), b"x");
+		}
+		assert_eq!(encrypt_gcm(&tf, b"x", b"", b"", 16), Err(Error::InvalidIvLength{len: 0, expected: 12}));
+		assert_eq!(decrypt_gcm(&tf, &[0u8; 15], b"123456789012", b"", 16),
+		           Err(Error::InvalidCiphertextLength{len: 15, block_size: 16}));
+	}
+
+	#[test]
+	fn test_message_length() {
+		let tf = <Twofish as BlockCipher>::new(&unhex(KEY)).unwrap();
+		let mut gcm = Gcm::new(&tf, b"123456789012", b"", 16).unwrap();
+		gcm.data_len = DATA_SIZE_MAX - 1;
+		let mut buffer = [0u8; 2];
+		assert_eq!(gcm.encrypt(&mut buffer), Err(Error::MessageTooLong{len: DATA_SIZE_MAX + 1, max: DATA_SIZE_MAX}));
+		assert_eq!(buffer, [0u8; 2]);
+		assert_eq!(gcm.decrypt(&mut buffer), Err(Error::MessageTooLong{len: DATA_SIZE_MAX + 1, max: DATA_SIZE_MAX}));
+		assert_eq!(gcm.encrypt(&mut buffer[..1]), Ok(()));
+		assert_eq!(gcm.encrypt(&mut buffer[..1]), Err(Error::MessageTooLong{len: DATA_SIZE_MAX + 1, max: DATA_SIZE_MAX}));
+	}
+
+	#[test]
+	fn test_block_size() {
+		let bf = <Blowfish as BlockCipher>::new(b"TESTKEY").unwrap();
+		assert_eq!(encrypt_gcm(&bf, b"x", b"123456789012", b"", 16).err(), Some(Error::UnsupportedBlockSize{size: 8}));
+		assert_eq!(decrypt_gcm(&bf, &[0u8; 17], b"123456789012", b"", 16).err(), Some(Error::UnsupportedBlockSize{size: 8}));
+	}
+}
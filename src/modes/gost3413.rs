@@ -0,0 +1,180 @@
+/*
+* Module: modes::gost3413
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Modes of operation of GOST R 34.13-2015 for block ciphers with 64
+//! and 128-bit blocks (Magma, Kuznyechik).
+//! The IV of OFB, CBC and CFB may be longer than one block (z * n bits),
+//! it fills the shift register of the mode. Results don't contain the IV.
+
+use super::{block_size, unpad, xor, Ctr, Endian, MAX_BLOCK_SIZE};
+use super::cmac::{cmac, verify_cmac};
+use BlockCipher;
+use Error;
+use Padding;
+
+/// Checks that 'iv' is a whole number of blocks (at least one).
+fn check_iv(iv: &[u8], bs: usize) -> Result<(), Error> {
+	if iv.is_empty() || !iv.len().is_multiple_of(bs) {
+		return Err(Error::InvalidIvLength{len: iv.len(), expected: bs})
+	}
+	Ok(())
+}
+
+/// Shifts 'register' left by one block and appends 'block'.
+fn shift(register: &mut Vec<u8>, block: &[u8]) {
+	register.drain(..block.len());
+	register.extend_from_slice(block);
+}
+
+/// CTR : encrypts 'input' with 'cipher', 'iv' is a half of the block.
+/// The counter is 'iv' followed by zeros, incremented modulo 2^n.
+pub fn encrypt_ctr<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	if iv.len() != bs / 2 { return Err(Error::InvalidIvLength{len: iv.len(), expected: bs / 2}) }
+	if input.is_empty() { return Err(Error::EmptyInput) }
+
+	let mut counter = [0u8; MAX_BLOCK_SIZE];
+	counter[..iv.len()].copy_from_slice(iv);
+	let mut ctr = Ctr::new(cipher, &counter[..bs], bs, Endian::Big)?;
+	let mut buffer = input.to_vec();
+	ctr.apply_keystream(&mut buffer);
+	Ok(buffer)
+}
+
+/// CTR : decrypts 'input' with 'cipher', 'iv' is a half of the block.
+pub fn decrypt_ctr<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+	encrypt_ctr(cipher, input, iv)
+}
+
+/// OFB : encrypts 'input' with 'cipher' using 'iv' of one or more blocks.
+pub fn encrypt_ofb<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	check_iv(iv, bs)?;
+	if input.is_empty() { return Err(Error::EmptyInput) }
+
+	let mut register = iv.to_vec();
+	let mut buffer = input.to_vec();
+	for chunk in buffer.chunks_mut(bs) {
+		let mut block = [0u8; MAX_BLOCK_SIZE];
+		block[..bs].copy_from_slice(&register[..bs]);
+		cipher.encrypt_block(&mut block[..bs]);
+		xor(chunk, &block[..bs]);
+		shift(&mut register, &block[..bs]);
+	}
+	Ok(buffer)
+}
+
+/// OFB : decrypts 'input' with 'cipher' using 'iv' of one or more blocks.
+pub fn decrypt_ofb<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+	encrypt_ofb(cipher, input, iv)
+}
+
+/// CBC : encrypts 'input' with 'cipher' using 'iv' of one or more blocks,
+/// the last block is filled with 'padding'.
+pub fn encrypt_cbc<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	check_iv(iv, bs)?;
+	if input.is_empty() { return Err(Error::EmptyInput) }
+
+	let mut register = iv.to_vec();
+	let mut buffer = padding.pad(input, bs)?;
+	for block in buffer.chunks_mut(bs) {
+		xor(block, &register[..bs]);
+		cipher.encrypt_block(block);
+		shift(&mut register, block);
+	}
+	Ok(buffer)
+}
+
+/// CBC : decrypts 'input' with 'cipher' using 'iv' of one or more blocks
+/// and removes 'padding' from the result.
+pub fn decrypt_cbc<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	check_iv(iv, bs)?;
+	if input.is_empty() || !input.len().is_multiple_of(bs) {
+		return Err(Error::InvalidCiphertextLength{len: input.len(), block_size: bs})
+	}
+
+	let mut register = iv.to_vec();
+	let mut buffer = input.to_vec();
+	for block in buffer.chunks_mut(bs) {
+		let mut c = [0u8; MAX_BLOCK_SIZE];
+		c[..bs].copy_from_slice(block);
+		cipher.decrypt_block(block);
+		xor(block, &register[..bs]);
+		shift(&mut register, &c[..bs]);
+	}
+	unpad(buffer, bs, padding)
+}
+
+/// CFB : encrypts 'input' with 'cipher' using 'iv' of one or more blocks,
+/// the segment is one block.
+pub fn encrypt_cfb<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+	cfb(cipher, input, iv, false)
+}
+
+/// CFB : decrypts 'input' with 'cipher' using 'iv' of one or more blocks.
+pub fn decrypt_cfb<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+	cfb(cipher, input, iv, true)
+}
+
+fn cfb<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8], decrypt: bool) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	check_iv(iv, bs)?;
+	if input.is_empty() { return Err(Error::EmptyInput) }
+
+	let mut register = iv.to_vec();
+	let mut buffer = input.to_vec();
+	for chunk in buffer.chunks_mut(bs) {
+		let mut block = [0u8; MAX_BLOCK_SIZE];
+		block[..bs].copy_from_slice(&register[..bs]);
+		cipher.encrypt_block(&mut block[..bs]);
+		if decrypt {
+			shift(&mut register, chunk);
+			xor(chunk, &block[..bs]);
+		} else {
+			xor(chunk, &block[..bs]);
+			shift(&mut register, chunk);
+		}
+	}
+	Ok(buffer)
+}
+
+/// MAC : computes MAC of 'size' bytes (1..n) of 'input' with 'cipher'.
+/// The MAC of GOST R 34.13-2015 is CMAC (OMAC1) with the MSB part
+/// of the last block as the result.
+pub fn mac<C: BlockCipher>(cipher: &C, input: &[u8], size: usize) -> Result<Vec<u8>, Error> {
+	cmac(cipher, input, size)
+}
+
+/// MAC : checks in constant time if 'tag' is the MAC of 'input'.
+pub fn verify_mac<C: BlockCipher>(cipher: &C, input: &[u8], tag: &[u8]) -> Result<(), Error> {
+	verify_cmac(cipher, input, tag)
+}
@@ -0,0 +1,315 @@
+/*
+* Module: modes
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use BlockCipher;
+use Error;
+use Padding;
+use rand::Rng;
+
+mod ctr;
+mod cfb;
+mod ofb;
+mod cmac;
+mod eax;
+mod gcm;
+mod ocb;
+pub mod gost3413;
+pub mod etm;
+
+pub use self::ctr::{Ctr, Endian, encrypt_ctr, encrypt_ctr_iv, decrypt_ctr};
+pub use self::cfb::{Cfb, encrypt_cfb, encrypt_cfb_iv, decrypt_cfb};
+pub use self::ofb::{Ofb, encrypt_ofb, encrypt_ofb_iv, decrypt_ofb};
+pub use self::cmac::{Cmac, cmac, verify_cmac};
+pub use self::eax::{encrypt_eax, decrypt_eax};
+pub use self::gcm::{Gcm, encrypt_gcm, decrypt_gcm};
+pub use self::ocb::{Ocb, encrypt_ocb, decrypt_ocb};
+
+/// The largest block size (in bytes) handled by the modes.
+const MAX_BLOCK_SIZE: usize = 16;
+
+/// Returns block size of the cipher 'C'.
+/// Modes support ciphers with 8, 12 (3-Way) and 16 bytes blocks.
+fn block_size<C: BlockCipher>() -> Result<usize, Error> {
+	match C::BLOCK_SIZE {
+		8 | 12 | 16 => Ok(C::BLOCK_SIZE),
+		size => Err(Error::UnsupportedBlockSize{size}),
+	}
+}
+
+/// xors 'data' with 'other', byte by byte.
+fn xor(data: &mut [u8], other: &[u8]) {
+	for (d, o) in data.iter_mut().zip(other.iter()) {
+		*d ^= *o;
+	}
+}
+
+/// Removes 'padding' from decrypted 'plain'.
+fn unpad(mut plain: Vec<u8>, block_size: usize, padding: Padding) -> Result<Vec<u8>, Error> {
+	let n = padding.unpad(&plain, block_size)?;
+	plain.truncate(n);
+	Ok(plain)
+}
+
+/// Converts hex string 's' to bytes.
+#[cfg(test)]
+pub(crate) fn unhex(s: &str) -> Vec<u8> {
+	(0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..(i + 2)], 16).unwrap()).collect()
+}
+
+/// ECB : encrypts 'input' with 'cipher' in ECB mode,
+/// the last block is filled with 'padding'.
+pub fn encrypt_ecb<C: BlockCipher>(cipher: &C, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	if input.is_empty() { return Err(Error::EmptyInput) }
+
+	let mut buffer = padding.pad(input, bs)?;
+	for block in buffer.chunks_mut(bs) {
+		cipher.encrypt_block(block);
+	}
+	Ok(buffer)
+}
+
+/// ECB : decrypts 'input' with 'cipher' in ECB mode
+/// and removes 'padding' from the result.
+pub fn decrypt_ecb<C: BlockCipher>(cipher: &C, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	if input.is_empty() { return Err(Error::EmptyInput) }
+	if !input.len().is_multiple_of(bs) {
+		return Err(Error::InvalidCiphertextLength{len: input.len(), block_size: bs})
+	}
+
+	let mut buffer = input.to_vec();
+	for block in buffer.chunks_mut(bs) {
+		cipher.decrypt_block(block);
+	}
+	unpad(buffer, bs, padding)
+}
+
+/// CBC : encrypts 'input' with 'cipher' in CBC mode using random generated IV,
+/// the last block is filled with 'padding'.
+/// The IV is stored in the first block of the result.
+pub fn encrypt_cbc<C: BlockCipher>(cipher: &C, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+	let mut iv = [0u8; MAX_BLOCK_SIZE];
+	rand::thread_rng().fill(&mut iv);
+	encrypt_cbc_iv(cipher, input, &iv[..block_size::<C>()?], padding)
+}
+
+/// CBC : encrypts 'input' with 'cipher' in CBC mode using passed 'iv',
+/// the last block is filled with 'padding'.
+/// The IV is stored in the first block of the result.
+pub fn encrypt_cbc_iv<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	// handle caller mistakes
+	if iv.len() != bs { return Err(Error::InvalidIvLength{len: iv.len(), expected: bs}) }
+	if input.is_empty() { return Err(Error::EmptyInput) }
+
+	// cipher text buffer: 'iv' followed by padded plain text,
+	// encrypted in place block after block
+	let mut buffer = iv.to_vec();
+	buffer.extend(padding.pad(input, bs)?);
+
+	let mut i = bs;
+	while i < buffer.len() {
+		let (prev, next) = buffer.split_at_mut(i);
+		let block = &mut next[..bs];
+		xor(block, &prev[(i - bs)..]);
+		cipher.encrypt_block(block);
+		i += bs;
+	}
+	Ok(buffer)
+}
+
+/// CBC : decrypts 'input' with 'cipher' in CBC mode
+/// and removes 'padding' from the result.
+/// The first block of 'input' is the IV.
+pub fn decrypt_cbc<C: BlockCipher>(cipher: &C, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	// the IV plus at least one block
+	if input.len() <= bs || !input.len().is_multiple_of(bs) {
+		return Err(Error::InvalidCiphertextLength{len: input.len(), block_size: bs})
+	}
+
+	let mut buffer = input[bs..].to_vec();
+	for (i, block) in buffer.chunks_mut(bs).enumerate() {
+		cipher.decrypt_block(block);
+		xor(block, &input[(i * bs)..]);
+	}
+	unpad(buffer, bs, padding)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use blowfish::Blowfish;
+	use gost::Gost;
+	use way3::Way3;
+	use twofish::Twofish;
+	use magma::Magma;
+	use kuznyechik::Kuznyechik;
+
+	const PADDINGS: [Padding; 6] = [Padding::Pkcs7, Padding::Iso7816, Padding::AnsiX923,
+	                                Padding::Iso10126, Padding::Zero, Padding::Legacy];
+
+	fn check_roundtrip<C: BlockCipher>(key: &[u8]) {
+		let cipher = C::new(key).unwrap();
+		let plain = "Artur, Błazej, Jolanta, Piotr Pszczółkowski".as_bytes();
+
+		for padding in PADDINGS.iter() {
+			for n in 1..plain.len() {
+				let encrypted = encrypt_ecb(&cipher, &plain[..n], *padding).unwrap();
+				assert!(encrypted.len().is_multiple_of(C::BLOCK_SIZE));
+				assert_eq!(decrypt_ecb(&cipher, &encrypted, *padding).unwrap(), &plain[..n]);
+
+				let encrypted = encrypt_cbc(&cipher, &plain[..n], *padding).unwrap();
+				assert!(encrypted.len().is_multiple_of(C::BLOCK_SIZE));
+				assert_eq!(decrypt_cbc(&cipher, &encrypted, *padding).unwrap(), &plain[..n]);
+			}
+		}
+
+		// block aligned data ending like the legacy padding
+		let mut plain = vec![0x41u8; 2 * C::BLOCK_SIZE];
+		plain[C::BLOCK_SIZE] = 0x80;
+		for b in plain[(C::BLOCK_SIZE + 1)..].iter_mut() {
+			*b = 0;
+		}
+		for padding in &[Padding::Pkcs7, Padding::Iso7816, Padding::AnsiX923, Padding::NoPadding] {
+			let encrypted = encrypt_cbc(&cipher, &plain, *padding).unwrap();
+			assert_eq!(decrypt_cbc(&cipher, &encrypted, *padding).unwrap(), plain);
+		}
+	}
+
+	fn check_errors<C: BlockCipher>(key: &[u8]) {
+		let cipher = C::new(key).unwrap();
+		let bs = C::BLOCK_SIZE;
+		let padding = Padding::Pkcs7;
+
+		assert_eq!(encrypt_ecb(&cipher, &[], padding), Err(Error::EmptyInput));
+		assert_eq!(decrypt_ecb(&cipher, &[], padding), Err(Error::EmptyInput));
+		assert_eq!(decrypt_ecb(&cipher, &vec![0u8; bs + 1], padding),
+		           Err(Error::InvalidCiphertextLength{len: bs + 1, block_size: bs}));
+
+		assert_eq!(encrypt_cbc(&cipher, &[], padding), Err(Error::EmptyInput));
+		assert_eq!(encrypt_cbc_iv(&cipher, b"x", &vec![0u8; bs - 1], padding),
+		           Err(Error::InvalidIvLength{len: bs - 1, expected: bs}));
+		assert_eq!(decrypt_cbc(&cipher, &vec![0u8; bs], padding),
+		           Err(Error::InvalidCiphertextLength{len: bs, block_size: bs}));
+		assert_eq!(decrypt_cbc(&cipher, &vec![0u8; 2 * bs + 1], padding),
+		           Err(Error::InvalidCiphertextLength{len: 2 * bs + 1, block_size: bs}));
+
+		assert_eq!(encrypt_ecb(&cipher, b"x", Padding::NoPadding),
+		           Err(Error::InvalidPlaintextLength{len: 1, block_size: bs}));
+
+		// valid cipher text of a block with malformed padding
+		let mut plain = vec![0x41u8; bs];
+		plain[bs - 1] = 0;
+		let encrypted = encrypt_ecb(&cipher, &plain, Padding::NoPadding).unwrap();
+		assert_eq!(decrypt_ecb(&cipher, &encrypted, Padding::Pkcs7), Err(Error::InvalidPadding));
+		assert_eq!(decrypt_ecb(&cipher, &encrypted, Padding::Iso7816), Err(Error::InvalidPadding));
+		assert_eq!(decrypt_ecb(&cipher, &encrypted, Padding::AnsiX923), Err(Error::InvalidPadding));
+	}
+
+	// cipher with 32-bit blocks, which no mode supports
+	struct Tiny([u8; 4]);
+
+	impl BlockCipher for Tiny {
+		const BLOCK_SIZE: usize = 4;
+		const KEY_SIZE_MIN: usize = 4;
+		const KEY_SIZE_MAX: usize = 4;
+
+		fn new(key: &[u8]) -> Result<Tiny, Error> {
+			let mut k = [0u8; 4];
+			k.copy_from_slice(key);
+			Ok(Tiny(k))
+		}
+
+		fn encrypt_block(&self, block: &mut [u8]) {
+			xor(block, &self.0);
+		}
+
+		fn decrypt_block(&self, block: &mut [u8]) {
+			xor(block, &self.0);
+		}
+	}
+
+	#[test]
+	fn test_block_size() {
+		let tiny = Tiny::new(&unhex("01020304")).unwrap();
+		let (input, iv) = (unhex("0001020304050607"), unhex("08090a0b"));
+		let err = Some(Error::UnsupportedBlockSize{size: 4});
+
+		assert_eq!(encrypt_ecb(&tiny, &input, Padding::NoPadding).err(), err);
+		assert_eq!(decrypt_ecb(&tiny, &input, Padding::NoPadding).err(), err);
+		assert_eq!(encrypt_cbc(&tiny, &input, Padding::NoPadding).err(), err);
+		assert_eq!(encrypt_cbc_iv(&tiny, &input, &iv, Padding::NoPadding).err(), err);
+		assert_eq!(decrypt_cbc(&tiny, &input, Padding::NoPadding).err(), err);
+		assert_eq!(Ctr::new(&tiny, &iv, 4, Endian::Big).err(), err);
+		assert_eq!(encrypt_ctr(&tiny, &input).err(), err);
+		assert_eq!(decrypt_ctr(&tiny, &input).err(), err);
+		assert_eq!(Cfb::new(&tiny, &iv, 4).err(), err);
+		assert_eq!(encrypt_cfb(&tiny, &input, 4).err(), err);
+		assert_eq!(decrypt_cfb(&tiny, &input, 4).err(), err);
+		assert_eq!(Ofb::new(&tiny, &iv).err(), err);
+		assert_eq!(encrypt_ofb(&tiny, &input).err(), err);
+		assert_eq!(decrypt_ofb(&tiny, &input).err(), err);
+		assert_eq!(gost3413::encrypt_ctr(&tiny, &input, &iv).err(), err);
+		assert_eq!(gost3413::encrypt_ofb(&tiny, &input, &iv).err(), err);
+		assert_eq!(gost3413::encrypt_cbc(&tiny, &input, &iv, Padding::NoPadding).err(), err);
+		assert_eq!(gost3413::encrypt_cfb(&tiny, &input, &iv).err(), err);
+		assert_eq!(gost3413::mac(&tiny, &input, 4).err(), err);
+		assert_eq!(Cmac::new(&tiny).err(), err);
+		assert_eq!(cmac(&tiny, &input, 4).err(), err);
+		assert_eq!(encrypt_eax(&tiny, &input, &iv, b"", 4).err(), err);
+		assert_eq!(encrypt_gcm(&tiny, &input, &iv, b"", 4).err(), err);
+		assert_eq!(encrypt_ocb(&tiny, &input, &iv, b"", 4).err(), err);
+
+		let etm = etm::Etm::<Tiny>::new(&[0u8; 16], etm::Mode::Ctr).unwrap();
+		assert_eq!(etm.encrypt(&input, b"").err(), err);
+	}
+
+	#[test]
+	fn test_roundtrip() {
+		check_roundtrip::<Blowfish>(b"TESTKEY");
+		check_roundtrip::<Gost>(b"12345678901234567890123456789012");
+		check_roundtrip::<Way3>(b"123456789012");
+		check_roundtrip::<Twofish>(b"0123456789abcdef");
+		check_roundtrip::<Magma>(b"12345678901234567890123456789012");
+		check_roundtrip::<Kuznyechik>(b"12345678901234567890123456789012");
+	}
+
+	#[test]
+	fn test_errors() {
+		check_errors::<Blowfish>(b"TESTKEY");
+		check_errors::<Gost>(b"12345678901234567890123456789012");
+		check_errors::<Way3>(b"123456789012");
+		check_errors::<Twofish>(b"0123456789abcdef");
+		check_errors::<Magma>(b"12345678901234567890123456789012");
+		check_errors::<Kuznyechik>(b"12345678901234567890123456789012");
+	}
+}
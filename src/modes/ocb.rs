@@ -0,0 +1,406 @@
+/*
+* Module: modes::ocb
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use constant_time_eq;
+use BlockCipher;
+use Error;
+
+const BLOCK_SIZE: usize = 16;
+const NONCE_SIZE_MAX: usize = 15;
+const NONCE_SIZE: usize = 12;	// the recommended nonce size
+
+/// OCB3 (RFC 7253) streaming encryption or decryption with a 128-bit
+/// block cipher. The direction and the associated data are set at creation,
+/// the message can be passed in parts of any size. Complete blocks are
+/// returned at once, the last incomplete block is returned by finalize (verify).
+/// Decrypted parts are not authenticated until verify returns Ok,
+/// use decrypt_ocb to get only the authenticated plain text.
+pub struct Ocb<'a, C: 'a + BlockCipher> {
+	cipher: &'a C,
+	l_star: u128,
+	l_dollar: u128,
+	l: [u128; 64],				// L_i for i = ntz(block number)
+	offset: u128,
+	checksum: u128,
+	hash: u128,					// HASH of the associated data
+	block: [u8; BLOCK_SIZE],	// not processed input
+	nbytes: usize,				// number of bytes in 'block'
+	count: u64,					// number of processed blocks
+	decrypting: bool,			// the direction, fixed at creation
+	tag_size: usize,
+}
+
+impl<'a, C: BlockCipher> Ocb<'a, C> {
+	/// Creates OCB encryptor for 'cipher', 'nonce' (1..15 bytes, 12 recommended),
+	/// associated data 'ad' and tags of 'tag_size' bytes (1..16).
+	pub fn new_encryptor(cipher: &'a C, nonce: &[u8], ad: &[u8], tag_size: usize) -> Result<Ocb<'a, C>, Error> {
+		Ocb::new(cipher, nonce, ad, tag_size, false)
+	}
+
+	/// Creates OCB decryptor for 'cipher', 'nonce' (1..15 bytes, 12 recommended),
+	/// associated data 'ad' and tags of 'tag_size' bytes (1..16).
+	pub fn new_decryptor(cipher: &'a C, nonce: &[u8], ad: &[u8], tag_size: usize) -> Result<Ocb<'a, C>, Error> {
+		Ocb::new(cipher, nonce, ad, tag_size, true)
+	}
+
+	/// Creates OCB object, 'decrypting' sets the direction.
+	fn new(cipher: &'a C, nonce: &[u8], ad: &[u8], tag_size: usize, decrypting: bool) -> Result<Ocb<'a, C>, Error> {
+		// handle caller mistakes
+		if C::BLOCK_SIZE != BLOCK_SIZE { return Err(Error::UnsupportedBlockSize{size: C::BLOCK_SIZE}) }
+		if tag_size == 0 || tag_size > BLOCK_SIZE {
+			return Err(Error::InvalidTagLength{len: tag_size, min: 1, max: BLOCK_SIZE})
+		}
+		if nonce.is_empty() || nonce.len() > NONCE_SIZE_MAX {
+			return Err(Error::InvalidIvLength{len: nonce.len(), expected: NONCE_SIZE})
+		}
+
+		let l_star = encrypt(cipher, 0);
+		let l_dollar = double(l_star);
+		let mut l = [0u128; 64];
+		l[0] = double(l_dollar);
+		for i in 1..l.len() {
+			l[i] = double(l[i - 1]);
+		}
+
+		let mut ocb = Ocb {
+			cipher,
+			l_star,
+			l_dollar,
+			l,
+			offset: offset0(cipher, nonce, tag_size),
+			checksum: 0,
+			hash: 0,
+			block: [0u8; BLOCK_SIZE],
+			nbytes: 0,
+			count: 0,
+			decrypting,
+			tag_size,
+		};
+		ocb.hash = ocb.hash(ad);
+		Ok(ocb)
+	}
+
+	/// Returns output of the last incomplete block and the tag.
+	pub fn finalize(mut self) -> (Vec<u8>, Vec<u8>) {
+		let (output, tag) = self.last();
+		(output, tag[..self.tag_size].to_vec())
+	}
+
+	/// Checks in constant time if 'tag' is the tag of the decrypted message,
+	/// returns plain text of the last incomplete block.
+	pub fn verify(mut self, tag: &[u8]) -> Result<Vec<u8>, Error> {
+		if tag.len() != self.tag_size {
+			return Err(Error::InvalidTagLength{len: tag.len(), min: self.tag_size, max: self.tag_size})
+		}
+		let (output, expected) = self.last();
+		if constant_time_eq(&expected[..self.tag_size], tag) {
+			Ok(output)
+		} else {
+			Err(Error::AuthenticationFailed)
+		}
+	}
+
+	/// Encrypts (decrypts) next part of the message, returns output
+	/// of the completed blocks, the incomplete rest is kept in the buffer.
+	pub fn update(&mut self, mut input: &[u8]) -> Vec<u8> {
+		let mut output = Vec::with_capacity(self.nbytes + input.len());
+
+		if self.nbytes > 0 {
+			let n = (BLOCK_SIZE - self.nbytes).min(input.len());
+			self.block[self.nbytes..(self.nbytes + n)].copy_from_slice(&input[..n]);
+			self.nbytes += n;
+			input = &input[n..];
+			if self.nbytes < BLOCK_SIZE {
+				return output
+			}
+			let x = self.process(u128::from_be_bytes(self.block));
+			output.extend_from_slice(&x.to_be_bytes());
+			self.nbytes = 0;
+		}
+
+		let mut chunks = input.chunks_exact(BLOCK_SIZE);
+		for chunk in &mut chunks {
+			let x = self.process(load(chunk));
+			output.extend_from_slice(&x.to_be_bytes());
+		}
+		let rest = chunks.remainder();
+		self.block[..rest.len()].copy_from_slice(rest);
+		self.nbytes = rest.len();
+		output
+	}
+
+	/// Encrypts or decrypts the next complete block.
+	fn process(&mut self, x: u128) -> u128 {
+		self.count += 1;
+		self.offset ^= self.l[self.count.trailing_zeros() as usize];
+		if self.decrypting {
+			let mut block = (x ^ self.offset).to_be_bytes();
+			self.cipher.decrypt_block(&mut block);
+			let p = u128::from_be_bytes(block) ^ self.offset;
+			self.checksum ^= p;
+			p
+		} else {
+			self.checksum ^= x;
+			encrypt(self.cipher, x ^ self.offset) ^ self.offset
+		}
+	}
+
+	/// Processes the last incomplete block, returns its output and the full tag.
+	fn last(&mut self) -> (Vec<u8>, [u8; BLOCK_SIZE]) {
+		let mut output = self.block[..self.nbytes].to_vec();
+		if self.nbytes > 0 {
+			self.offset ^= self.l_star;
+			let pad = encrypt(self.cipher, self.offset).to_be_bytes();
+			for (o, p) in output.iter_mut().zip(pad.iter()) {
+				*o ^= *p;
+			}
+			let plain = if self.decrypting { &output } else { &self.block[..self.nbytes] };
+			self.checksum ^= pad_block(plain);
+		}
+		let tag = encrypt(self.cipher, self.checksum ^ self.offset ^ self.l_dollar) ^ self.hash;
+		(output, tag.to_be_bytes())
+	}
+
+	/// HASH of the associated data 'ad'.
+	fn hash(&self, ad: &[u8]) -> u128 {
+		let mut sum = 0u128;
+		let mut offset = 0u128;
+		let mut chunks = ad.chunks_exact(BLOCK_SIZE);
+		for (i, chunk) in (&mut chunks).enumerate() {
+			offset ^= self.l[(i as u64 + 1).trailing_zeros() as usize];
+			sum ^= encrypt(self.cipher, load(chunk) ^ offset);
+		}
+		let rest = chunks.remainder();
+		if !rest.is_empty() {
+			offset ^= self.l_star;
+			sum ^= encrypt(self.cipher, pad_block(rest) ^ offset);
+		}
+		sum
+	}
+}
+
+/// OCB : encrypts 'input' (may be empty) with 128-bit block 'cipher' using
+/// 'nonce' (1..15 bytes) and authenticates it together with associated data 'ad'.
+/// The result is the cipher text followed by the tag of 'tag_size' bytes (1..16).
+pub fn encrypt_ocb<C: BlockCipher>(cipher: &C, input: &[u8], nonce: &[u8], ad: &[u8], tag_size: usize) -> Result<Vec<u8>, Error> {
+	let mut ocb = Ocb::new_encryptor(cipher, nonce, ad, tag_size)?;
+	let mut buffer = ocb.update(input);
+	let (last, tag) = ocb.finalize();
+	buffer.extend(last);
+	buffer.extend(tag);
+	Ok(buffer)
+}
+
+/// OCB : decrypts 'input' (cipher text followed by the tag of 'tag_size' bytes)
+/// with 'cipher' using 'nonce' and checks the tag.
+/// The plain text is returned only when the tag matches.
+pub fn decrypt_ocb<C: BlockCipher>(cipher: &C, input: &[u8], nonce: &[u8], ad: &[u8], tag_size: usize) -> Result<Vec<u8>, Error> {
+	let mut ocb = Ocb::new_decryptor(cipher, nonce, ad, tag_size)?;
+	if input.len() < tag_size {
+		return Err(Error::InvalidCiphertextLength{len: input.len(), block_size: BLOCK_SIZE})
+	}
+
+	let (data, tag) = input.split_at(input.len() - tag_size);
+	let mut buffer = ocb.update(data);
+	let last = ocb.verify(tag)?;
+	buffer.extend(last);
+	Ok(buffer)
+}
+
+/// Returns Offset_0 for 'nonce' and the tag length.
+fn offset0<C: BlockCipher>(cipher: &C, nonce: &[u8], tag_size: usize) -> u128 {
+	let mut block = [0u8; BLOCK_SIZE];
+	block[0] = ((tag_size * 8 % 128) << 1) as u8;
+	block[BLOCK_SIZE - 1 - nonce.len()] |= 1;
+	block[(BLOCK_SIZE - nonce.len())..].copy_from_slice(nonce);
+	let nonce = u128::from_be_bytes(block);
+
+	let bottom = (nonce & 0x3f) as u32;
+	let ktop = encrypt(cipher, nonce & !0x3f);
+	// Stretch = Ktop || (Ktop[1..64] xor Ktop[9..72])
+	let stretch = ((ktop >> 64) ^ (ktop >> 56)) as u64;
+	if bottom == 0 {
+		ktop
+	} else {
+		(ktop << bottom) | u128::from(stretch >> (64 - bottom))
+	}
+}
+
+/// Returns encrypted block 'x'.
+fn encrypt<C: BlockCipher>(cipher: &C, x: u128) -> u128 {
+	let mut block = x.to_be_bytes();
+	cipher.encrypt_block(&mut block);
+	u128::from_be_bytes(block)
+}
+
+/// Returns u128 of the big-endian block 'data'.
+fn load(data: &[u8]) -> u128 {
+	let mut block = [0u8; BLOCK_SIZE];
+	block.copy_from_slice(data);
+	u128::from_be_bytes(block)
+}
+
+/// Returns incomplete block 'data' followed by 1 bit and zeros.
+fn pad_block(data: &[u8]) -> u128 {
+	let mut block = [0u8; BLOCK_SIZE];
+	block[..data.len()].copy_from_slice(data);
+	block[data.len()] = 0x80;
+	u128::from_be_bytes(block)
+}
+
+/// Multiplies 'x' by x in GF(2^128).
+fn double(x: u128) -> u128 {
+	(x << 1) ^ (0x87 & 0u128.wrapping_sub(x >> 127))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use modes::unhex;
+	use blowfish::Blowfish;
+	use twofish::Twofish;
+	use kuznyechik::Kuznyechik;
+
+	const KEY: &str = "000102030405060708090a0b0c0d0e0f";
+	const DATA: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f2021222324252627";
+
+	// inputs of RFC 7253, appendix A (nonce, associated data and plain text
+	// lengths, both prefixes of DATA) with Twofish, verified with libgcrypt (GCRY_CIPHER_MODE_OCB)
+	const VECTORS: [(&str, usize, usize, &str); 16] = [
+		("bbaa99887766554433221100", 0, 0, "335caccf5297b8345c2572735365faa2"),
+		("bbaa99887766554433221101", 8, 8, "44fb7ae71116fe2aa8d7e337c1811f02f4839c7817d51e75"),
+		("bbaa99887766554433221102", 8, 0, "8475520108774c95e6faf7f176e3a707"),
+		("bbaa99887766554433221103", 0, 8, "2e24a7ff2358115c44b31768fe8fefcb423065dd39757204"),
+		("bbaa99887766554433221104", 16, 16, "95434775b754945ead9cafef7e50f36e171498f25eee5da0ac15adb4f5665964"),
+		("bbaa99887766554433221105", 16, 0, "2d9a59770afa19ede47809848c095692"),
+		("bbaa99887766554433221106", 0, 16, "9c68654c6b00125e421e681ff2b47898dfae3810199b007e2efa07d7fdf33840"),
+		("bbaa99887766554433221107", 24, 24, "94c08ef681d75e739b6dfcbe6a48a99a531a1b8cc950cfe0da071605a6b37023e5798fe8b090abf1"),
+		("bbaa99887766554433221108", 24, 0, "c85657e31dba5fbf338c4076fde88381"),
+		("bbaa99887766554433221109", 0, 24, "bd6f3e40fb47c227ca372c23bc9b202a5303e04437747e8d3faf883716a38f873d8bf660f2fe1100"),
+		("bbaa9988776655443322110a", 32, 32, "6e9076cc3dc265b9482b0ffcb23c8239444ef78104d42d71f5b0d1cfa63315203815bed00ab9b81d25bb21bec0503c73"),
+		("bbaa9988776655443322110b", 32, 0, "1858bc33cf0d104fb529fe248789682d"),
+		("bbaa9988776655443322110c", 0, 32, "e4b636a9391655e417f3e179361d632c6664988786f3567ece809a1ec10793e6dc2b1c848d950dac3b2cea4462cf7f79"),
+		("bbaa9988776655443322110d", 40, 40, "d6036e6dcfb5695815ca7c8205d3efbd594c797eec579b658fcd36c28090487d3e31fbd36883b1571d6f1e6054701bfb2dd432d897bf9804"),
+		("bbaa9988776655443322110e", 40, 0, "b4714af65a66d1bf16a43443acda979a"),
+		("bbaa9988776655443322110f", 0, 40, "1391fa60481cc55061ad0bb1a813f2824bde97dc9fecdc3a3193e4cb2d83aafab9272151f2d97f3c9b03689a215802fe08eb551d94d75f69"),
+	];
+
+	#[test]
+	fn test_vectors() {
+		let tf = <Twofish as BlockCipher>::new(&unhex(KEY)).unwrap();
+		let data = unhex(DATA);
+		for &(nonce, ad_len, plain_len, expected) in VECTORS.iter() {
+			let (nonce, expected) = (unhex(nonce), unhex(expected));
+			let (ad, plain) = (&data[..ad_len], &data[..plain_len]);
+
+			let encrypted = encrypt_ocb(&tf, plain, &nonce, ad, 16).unwrap();
+			assert_eq!(encrypted, expected);
+			assert_eq!(decrypt_ocb(&tf, &encrypted, &nonce, ad, 16).unwrap(), plain);
+		}
+	}
+
+	#[test]
+	fn test_tag_size() {
+		// the inputs of the RFC 7253 sample with 96-bit tag, and 64-bit tag
+		let tf = <Twofish as BlockCipher>::new(&unhex("0f0e0d0c0b0a09080706050403020100")).unwrap();
+		let data = unhex(DATA);
+		let nonce = unhex("bbaa9988776655443322110d");
+		let vectors = [
+			(40, 12, unhex("6943819a326c10510d90966d48059ceb46c2e0cd310fa1df2aecb82d8a0a6186bcee20a838c9372dc5a7ac50cb3e995162d8c125")),
+			(17, 8, unhex("1be33fa73a25ce9da58b29fdfda2ef7ec147ac58d940e3be0f")),
+		];
+		for (plain_len, tag_size, expected) in vectors.iter() {
+			let encrypted = encrypt_ocb(&tf, &data[..*plain_len], &nonce, &data, *tag_size).unwrap();
+			assert_eq!(&encrypted, expected);
+			assert_eq!(decrypt_ocb(&tf, &encrypted, &nonce, &data, *tag_size).unwrap(), &data[..*plain_len]);
+		}
+	}
+
+	#[test]
+	fn test_streaming() {
+		let tf = <Twofish as BlockCipher>::new(&unhex(KEY)).unwrap();
+		let plain: Vec<u8> = (0..100u8).collect();
+		let expected = encrypt_ocb(&tf, &plain, b"nonce", b"header", 16).unwrap();
+		let (cipher_text, tag) = expected.split_at(plain.len());
+
+		for step in 1..40 {
+			let mut ocb = Ocb::new_encryptor(&tf, b"nonce", b"header", 16).unwrap();
+			let mut encrypted = Vec::new();
+			for chunk in plain.chunks(step) {
+				let output = ocb.update(chunk);
+				assert_eq!(output.len() % 16, 0);
+				encrypted.extend(output);
+			}
+			let (last, t) = ocb.finalize();
+			encrypted.extend(last);
+			assert_eq!(encrypted, cipher_text);
+			assert_eq!(t, tag);
+
+			let mut ocb = Ocb::new_decryptor(&tf, b"nonce", b"header", 16).unwrap();
+			let mut decrypted = Vec::new();
+			for chunk in cipher_text.chunks(step) {
+				decrypted.extend(ocb.update(chunk));
+			}
+			decrypted.extend(ocb.verify(tag).unwrap());
+			assert_eq!(decrypted, plain);
+		}
+	}
+
+	#[test]
+	fn test_tampering() {
+		let kuz = <Kuznyechik as BlockCipher>::new(b"12345678901234567890123456789012").unwrap();
+		let encrypted = encrypt_ocb(&kuz, b"Yamato & Musashi, 1941", b"123456789012", b"header", 16).unwrap();
+		for i in 0..encrypted.len() {
+			let mut tampered = encrypted.clone();
+			tampered[i] ^= 0x01;
+			assert_eq!(decrypt_ocb(&kuz, &tampered, b"123456789012", b"header", 16), Err(Error::AuthenticationFailed));
+		}
+		assert_eq!(decrypt_ocb(&kuz, &encrypted, b"123456789013", b"header", 16), Err(Error::AuthenticationFailed));
+		assert_eq!(decrypt_ocb(&kuz, &encrypted, b"123456789012", b"Header", 16), Err(Error::AuthenticationFailed));
+	}
+
+	#[test]
+	fn test_errors() {
+		let tf = <Twofish as BlockCipher>::new(&unhex(KEY)).unwrap();
+		assert_eq!(encrypt_ocb(&tf, b"x", b"nonce", b"", 0), Err(Error::InvalidTagLength{len: 0, min: 1, max: 16}));
+		assert_eq!(encrypt_ocb(&tf, b"x", b"nonce", b"", 17), Err(Error::InvalidTagLength{len: 17, min: 1, max: 16}));
+		assert_eq!(encrypt_ocb(&tf, b"x", b"", b"", 16), Err(Error::InvalidIvLength{len: 0, expected: 12}));
+		assert_eq!(encrypt_ocb(&tf, b"x", &[0u8; 16], b"", 16), Err(Error::InvalidIvLength{len: 16, expected: 12}));
+		assert_eq!(decrypt_ocb(&tf, &[0u8; 15], b"nonce", b"", 16),
+		           Err(Error::InvalidCiphertextLength{len: 15, block_size: 16}));
+	}
+
+	#[test]
+	fn test_block_size() {
+		let bf = <Blowfish as BlockCipher>::new(b"TESTKEY").unwrap();
+		assert_eq!(encrypt_ocb(&bf, b"x", b"nonce", b"", 16).err(), Some(Error::UnsupportedBlockSize{size: 8}));
+		assert_eq!(decrypt_ocb(&bf, &[0u8; 17], b"nonce", b"", 16).err(), Some(Error::UnsupportedBlockSize{size: 8}));
+	}
+}
@@ -0,0 +1,214 @@
+/*
+* Module: modes::ofb
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use super::{block_size, xor, MAX_BLOCK_SIZE};
+use BlockCipher;
+use Error;
+use rand::Rng;
+
+/// OFB mode keystream generator of a block cipher.
+/// The keystream is the sequence of encryptions of the IV: E(IV), E(E(IV)), ...
+/// It may be read as raw bytes (fill) or xored with data (apply_keystream),
+/// in pieces of any length.
+pub struct Ofb<'a, C: 'a + BlockCipher> {
+	cipher: &'a C,
+	block: [u8; MAX_BLOCK_SIZE],
+	pos: usize,
+}
+
+impl<'a, C: BlockCipher> Ofb<'a, C> {
+	/// Creates OFB keystream generator for 'cipher' starting with 'iv'.
+	pub fn new(cipher: &'a C, iv: &[u8]) -> Result<Ofb<'a, C>, Error> {
+		let bs = block_size::<C>()?;
+		if iv.len() != bs { return Err(Error::InvalidIvLength{len: iv.len(), expected: bs}) }
+
+		let mut ofb = Ofb {
+			cipher,
+			block: [0u8; MAX_BLOCK_SIZE],
+			pos: bs,
+		};
+		ofb.block[..bs].copy_from_slice(iv);
+		Ok(ofb)
+	}
+
+	/// Fills 'buffer' with next bytes of the keystream.
+	pub fn fill(&mut self, buffer: &mut [u8]) {
+		for b in buffer.iter_mut() {
+			*b = 0;
+		}
+		self.apply_keystream(buffer);
+	}
+
+	/// xors 'data' in place with next bytes of the keystream (encrypts or decrypts).
+	pub fn apply_keystream(&mut self, data: &mut [u8]) {
+		let bs = C::BLOCK_SIZE;
+		let mut i = 0;
+		while i < data.len() {
+			if self.pos == bs {
+				self.cipher.encrypt_block(&mut self.block[..bs]);
+				self.pos = 0;
+			}
+			let n = (bs - self.pos).min(data.len() - i);
+			xor(&mut data[i..(i + n)], &self.block[self.pos..(self.pos + n)]);
+			self.pos += n;
+			i += n;
+		}
+	}
+}
+
+/// OFB : encrypts 'input' with 'cipher' in OFB mode using random generated IV.
+/// The IV is stored in the first block of the result.
+pub fn encrypt_ofb<C: BlockCipher>(cipher: &C, input: &[u8]) -> Result<Vec<u8>, Error> {
+	let mut iv = [0u8; MAX_BLOCK_SIZE];
+	rand::thread_rng().fill(&mut iv);
+	encrypt_ofb_iv(cipher, input, &iv[..block_size::<C>()?])
+}
+
+/// OFB : encrypts 'input' with 'cipher' in OFB mode using passed 'iv'.
+/// The IV is stored in the first block of the result, no padding is used.
+pub fn encrypt_ofb_iv<C: BlockCipher>(cipher: &C, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	let mut ofb = Ofb::new(cipher, iv)?;
+	if input.is_empty() { return Err(Error::EmptyInput) }
+
+	let mut buffer = iv.to_vec();
+	buffer.extend_from_slice(input);
+	ofb.apply_keystream(&mut buffer[bs..]);
+	Ok(buffer)
+}
+
+/// OFB : decrypts 'input' with 'cipher' in OFB mode.
+/// The first block of 'input' is the IV.
+pub fn decrypt_ofb<C: BlockCipher>(cipher: &C, input: &[u8]) -> Result<Vec<u8>, Error> {
+	let bs = block_size::<C>()?;
+	// the IV plus at least one byte
+	if input.len() <= bs {
+		return Err(Error::InvalidCiphertextLength{len: input.len(), block_size: bs})
+	}
+
+	let mut ofb = Ofb::new(cipher, &input[..bs])?;
+	let mut buffer = input[bs..].to_vec();
+	ofb.apply_keystream(&mut buffer);
+	Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use blowfish;
+	use blowfish::{Blowfish, ByteOrder};
+	use gost::Gost;
+	use way3::Way3;
+	use twofish::Twofish;
+
+	#[test]
+	fn test_bf_ofb() {
+		// openssl enc -bf-ofb -K 0123456789abcdeff0e1d2c3b4a59687 -iv fedcba9876543210
+		let key = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xf0, 0xe1, 0xd2, 0xc3, 0xb4, 0xa5, 0x96, 0x87];
+		let iv = [0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10];
+		let plain = b"7654321 Now is the time for \0";
+		let expected = [
+			0xe7, 0x32, 0x14, 0xa2, 0x82, 0x21, 0x39, 0xca, 0x62, 0xb3, 0x43, 0xcc, 0x5b, 0x65, 0x58, 0x73,
+			0x10, 0xdd, 0x90, 0x8d, 0x0c, 0x24, 0x1b, 0x22, 0x63, 0xc2, 0xcf, 0x80, 0xda,
+		];
+		let cipher = blowfish::with_byte_order(&key, ByteOrder::Big).unwrap();
+
+		let encrypted = encrypt_ofb_iv(&cipher, plain, &iv).unwrap();
+		assert_eq!(&encrypted[..8], &iv);
+		assert_eq!(&encrypted[8..], &expected[..]);
+		assert_eq!(decrypt_ofb(&cipher, &encrypted).unwrap(), &plain[..]);
+	}
+
+	#[test]
+	fn test_twofish_ofb() {
+		// verified with libgcrypt (GCRY_CIPHER_MODE_OFB)
+		let expected = [
+			0xd0, 0x8b, 0x4d, 0x06, 0xfc, 0x92, 0xc3, 0x2b, 0x71, 0x92, 0x4d, 0x50, 0x31, 0xed, 0x44, 0x4b,
+			0x9a, 0x33, 0x6f, 0x57, 0xab, 0x44, 0xeb, 0x98, 0xc3, 0x57, 0xd1, 0x00, 0xda, 0x76, 0x34, 0x0d,
+			0x65, 0x61, 0x97, 0x86, 0x4f, 0x3a, 0x5c, 0x2b, 0x8d, 0xae, 0xe0, 0x38, 0x11, 0x21,
+		];
+		let cipher = Twofish::new(b"0123456789abcdef").unwrap();
+		let iv: Vec<u8> = (0xf0..=0xff).collect();
+		let plain = "Artur, Błazej, Jolanta, Piotr Pszczółkowski".as_bytes();
+
+		let encrypted = encrypt_ofb_iv(&cipher, plain, &iv).unwrap();
+		assert_eq!(&encrypted[16..], &expected[..]);
+		assert_eq!(decrypt_ofb(&cipher, &encrypted).unwrap(), plain);
+	}
+
+	fn check_keystream<C: BlockCipher>(key: &[u8]) {
+		let cipher = C::new(key).unwrap();
+		let bs = C::BLOCK_SIZE;
+		let iv = vec![0xa5u8; bs];
+
+		// keystream blocks are successive encryptions of the IV
+		let mut keystream = vec![0u8; 3 * bs + 1];
+		Ofb::new(&cipher, &iv).unwrap().fill(&mut keystream);
+		let mut block = iv.clone();
+		for chunk in keystream.chunks(bs) {
+			cipher.encrypt_block(&mut block);
+			assert_eq!(chunk, &block[..chunk.len()]);
+		}
+
+		// xoring in pieces gives the same stream
+		for piece in 1..=(bs + 1) {
+			let mut data = vec![0xffu8; keystream.len()];
+			let mut ofb = Ofb::new(&cipher, &iv).unwrap();
+			for chunk in data.chunks_mut(piece) {
+				ofb.apply_keystream(chunk);
+			}
+			for (d, k) in data.iter().zip(keystream.iter()) {
+				assert_eq!(*d, !*k);
+			}
+		}
+
+		let plain = b"Artur, Jolanta";
+		let encrypted = encrypt_ofb(&cipher, plain).unwrap();
+		assert_eq!(decrypt_ofb(&cipher, &encrypted).unwrap(), &plain[..]);
+	}
+
+	#[test]
+	fn test_keystream() {
+		check_keystream::<Blowfish>(b"TESTKEY");
+		check_keystream::<Gost>(b"12345678901234567890123456789012");
+		check_keystream::<Way3>(b"123456789012");
+		check_keystream::<Twofish>(b"0123456789abcdef");
+	}
+
+	#[test]
+	fn test_errors() {
+		let cipher = Blowfish::new(b"TESTKEY").unwrap();
+
+		assert_eq!(Ofb::new(&cipher, &[0u8; 7]).err(), Some(Error::InvalidIvLength{len: 7, expected: 8}));
+		assert_eq!(encrypt_ofb(&cipher, &[]), Err(Error::EmptyInput));
+		assert_eq!(decrypt_ofb(&cipher, &[0u8; 8]),
+		           Err(Error::InvalidCiphertextLength{len: 8, block_size: 8}));
+	}
+}
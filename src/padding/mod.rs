@@ -0,0 +1,236 @@
+/*
+* Module: padding
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use padding;
+use Error;
+use rand::Rng;
+
+/// Padding schemes used to fill the last block of data
+/// encrypted in ECB and CBC modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+	/// PKCS#7: N bytes of value N, always added (1..=block size bytes).
+	Pkcs7,
+	/// ISO/IEC 7816-4: byte 0x80 followed by zeros, always added.
+	Iso7816,
+	/// ANSI X9.23: zeros followed by the number of added bytes, always added.
+	AnsiX923,
+	/// ISO 10126: random bytes followed by the number of added bytes, always added.
+	Iso10126,
+	/// Zeros added only when data is not block aligned.
+	/// Trailing zeros of the data itself are removed on decryption.
+	Zero,
+	/// No padding, data must be block aligned.
+	NoPadding,
+	/// Byte 0x80 followed by zeros, added only when data is not block aligned,
+	/// removed from the last 0x80 on decryption. This is the scheme used by
+	/// earlier versions of this crate and it is ambiguous: block aligned data
+	/// ending with 0x80 and zeros is truncated. Use it only to decrypt old data.
+	Legacy,
+}
+
+impl Padding {
+
+	/// Returns copy of 'input' with padding appended,
+	/// the result is a multiple of 'block_size' bytes.
+	pub fn pad(self, input: &[u8], block_size: usize) -> Result<Vec<u8>, Error> {
+		let n = block_size - input.len() % block_size;	// 1..=block_size
+		let mut buffer = Vec::with_capacity(input.len() + n);
+		buffer.extend_from_slice(input);
+
+		match self {
+			Padding::Pkcs7 => {
+				buffer.resize(input.len() + n, n as u8);
+			},
+			Padding::Iso7816 => {
+				buffer.push(0x80);
+				buffer.resize(input.len() + n, 0);
+			},
+			Padding::AnsiX923 => {
+				buffer.resize(input.len() + n - 1, 0);
+				buffer.push(n as u8);
+			},
+			Padding::Iso10126 => {
+				let mut random = vec![0u8; n - 1];
+				rand::thread_rng().fill(&mut random[..]);
+				buffer.extend(random);
+				buffer.push(n as u8);
+			},
+			Padding::Zero => {
+				if n != block_size {
+					buffer.resize(input.len() + n, 0);
+				}
+			},
+			Padding::NoPadding => {
+				if n != block_size {
+					return Err(Error::InvalidPlaintextLength{len: input.len(), block_size})
+				}
+			},
+			Padding::Legacy => {
+				if n != block_size {
+					buffer.extend(padding(n));
+				}
+			},
+		}
+		Ok(buffer)
+	}
+
+	/// Returns length of 'data' without padding.
+	/// 'data' is decrypted text, a multiple of 'block_size' bytes.
+	pub fn unpad(self, data: &[u8], block_size: usize) -> Result<usize, Error> {
+		let len = data.len();
+		if len == 0 || !len.is_multiple_of(block_size) {
+			return Err(Error::InvalidPadding)
+		}
+		let last = &data[(len - block_size)..];
+
+		match self {
+			Padding::Pkcs7 | Padding::AnsiX923 | Padding::Iso10126 => {
+				let n = last[block_size - 1] as usize;
+				if n == 0 || n > block_size {
+					return Err(Error::InvalidPadding)
+				}
+				// check all padding bytes without early exit
+				let expected = match self {
+					Padding::Pkcs7 => Some(n as u8),
+					Padding::AnsiX923 => Some(0),
+					_ => None,
+				};
+				if let Some(v) = expected {
+					let mut diff = 0u8;
+					for x in &last[(block_size - n)..(block_size - 1)] {
+						diff |= x ^ v;
+					}
+					if diff != 0 {
+						return Err(Error::InvalidPadding)
+					}
+				}
+				Ok(len - n)
+			},
+			Padding::Iso7816 => {
+				match last.iter().rposition(|x| *x != 0) {
+					Some(idx) if last[idx] == 0x80 => Ok(len - block_size + idx),
+					_ => Err(Error::InvalidPadding)
+				}
+			},
+			Padding::Zero => {
+				match last.iter().rposition(|x| *x != 0) {
+					Some(idx) => Ok(len - block_size + idx + 1),
+					_ => Ok(len - block_size)
+				}
+			},
+			Padding::NoPadding => Ok(len),
+			Padding::Legacy => {
+				match data.iter().rposition(|x| *x != 0) {
+					Some(idx) if data[idx] == 0x80 => Ok(idx),
+					_ => Ok(len)
+				}
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn check(padding: Padding, input: &[u8], expected: &[u8]) {
+		let padded = padding.pad(input, 8).unwrap();
+		assert_eq!(padded, expected);
+		assert_eq!(padding.unpad(&padded, 8), Ok(input.len()));
+	}
+
+	#[test]
+	fn test_pkcs7() {
+		check(Padding::Pkcs7, b"abc", b"abc\x05\x05\x05\x05\x05");
+		check(Padding::Pkcs7, b"abcdefgh", b"abcdefgh\x08\x08\x08\x08\x08\x08\x08\x08");
+		check(Padding::Pkcs7, b"", b"\x08\x08\x08\x08\x08\x08\x08\x08");
+
+		assert_eq!(Padding::Pkcs7.unpad(b"abcdefg\x00", 8), Err(Error::InvalidPadding));
+		assert_eq!(Padding::Pkcs7.unpad(b"abcdefg\x09", 8), Err(Error::InvalidPadding));
+		assert_eq!(Padding::Pkcs7.unpad(b"abcde\x02\x03\x03", 8), Err(Error::InvalidPadding));
+		assert_eq!(Padding::Pkcs7.unpad(b"abcdefg", 8), Err(Error::InvalidPadding));
+	}
+
+	#[test]
+	fn test_iso7816() {
+		check(Padding::Iso7816, b"abc", b"abc\x80\x00\x00\x00\x00");
+		check(Padding::Iso7816, b"abcdefg", b"abcdefg\x80");
+		check(Padding::Iso7816, b"abcdef\x80\x00", b"abcdef\x80\x00\x80\x00\x00\x00\x00\x00\x00\x00");
+
+		assert_eq!(Padding::Iso7816.unpad(b"abcdefgh", 8), Err(Error::InvalidPadding));
+		assert_eq!(Padding::Iso7816.unpad(b"abc\x80\x00\x00\x01\x00", 8), Err(Error::InvalidPadding));
+		assert_eq!(Padding::Iso7816.unpad(&[0u8; 8], 8), Err(Error::InvalidPadding));
+	}
+
+	#[test]
+	fn test_ansi_x923() {
+		check(Padding::AnsiX923, b"abc", b"abc\x00\x00\x00\x00\x05");
+		check(Padding::AnsiX923, b"abcdefg", b"abcdefg\x01");
+
+		assert_eq!(Padding::AnsiX923.unpad(b"abc\x00\x01\x00\x00\x05", 8), Err(Error::InvalidPadding));
+		assert_eq!(Padding::AnsiX923.unpad(b"abcdefg\x00", 8), Err(Error::InvalidPadding));
+	}
+
+	#[test]
+	fn test_iso10126() {
+		let padded = Padding::Iso10126.pad(b"abc", 8).unwrap();
+		assert_eq!(padded.len(), 8);
+		assert_eq!(&padded[..3], b"abc");
+		assert_eq!(padded[7], 5);
+		assert_eq!(Padding::Iso10126.unpad(&padded, 8), Ok(3));
+
+		assert_eq!(Padding::Iso10126.unpad(b"abcdefg\x10", 8), Err(Error::InvalidPadding));
+	}
+
+	#[test]
+	fn test_zero() {
+		check(Padding::Zero, b"abc", b"abc\x00\x00\x00\x00\x00");
+		check(Padding::Zero, b"abcdefgh", b"abcdefgh");
+		assert_eq!(Padding::Zero.unpad(b"abcdefgh\x00\x00\x00\x00\x00\x00\x00\x00", 8), Ok(8));
+	}
+
+	#[test]
+	fn test_no_padding() {
+		check(Padding::NoPadding, b"abcdefgh", b"abcdefgh");
+		assert_eq!(Padding::NoPadding.pad(b"abc", 8), Err(Error::InvalidPlaintextLength{len: 3, block_size: 8}));
+	}
+
+	#[test]
+	fn test_legacy() {
+		check(Padding::Legacy, b"abc", b"abc\x80\x00\x00\x00\x00");
+		check(Padding::Legacy, b"abcdefgh", b"abcdefgh");
+		// the ambiguity kept for compatibility
+		assert_eq!(Padding::Legacy.unpad(b"abcdef\x80\x00", 8), Ok(6));
+		// block aligned data without 0x80 is returned unchanged
+		check(Padding::Legacy, &[0u8; 8], &[0u8; 8]);
+		assert_eq!(Padding::Legacy.unpad(b"abcdefg\x00", 8), Ok(8));
+	}
+}
@@ -0,0 +1,123 @@
+/*
+* Module: sha512::hmac
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+use super::{new, Sha512, BLOCK_SIZE, DIGEST_SIZE};
+
+/// HMAC-SHA-512 (RFC 2104, RFC 4231).
+pub struct Hmac {
+	inner: Sha512,
+	okey: [u8; BLOCK_SIZE],			// key xored with the outer pad
+}
+
+impl Hmac {
+	/// Creates new HMAC object for 'key' of any length,
+	/// keys longer than the block are hashed first.
+	pub fn new(key: &[u8]) -> Hmac {
+		let mut k = [0u8; BLOCK_SIZE];
+		if key.len() > BLOCK_SIZE {
+			k[..DIGEST_SIZE].copy_from_slice(&super::digest(key));
+		} else {
+			k[..key.len()].copy_from_slice(key);
+		}
+
+		let mut ikey = [0x36u8; BLOCK_SIZE];
+		let mut okey = [0x5cu8; BLOCK_SIZE];
+		for i in 0..BLOCK_SIZE {
+			ikey[i] ^= k[i];
+			okey[i] ^= k[i];
+		}
+		let mut inner = new();
+		inner.update(&ikey);
+		Hmac{inner, okey}
+	}
+
+	/// Adds 'data' to the authenticated message.
+	pub fn update(&mut self, data: &[u8]) {
+		self.inner.update(data);
+	}
+
+	/// Returns MAC of the message.
+	pub fn finalize(self) -> [u8; DIGEST_SIZE] {
+		let mut outer = new();
+		outer.update(&self.okey);
+		outer.update(&self.inner.finalize());
+		outer.finalize()
+	}
+}
+
+/// Returns HMAC-SHA-512 of 'data' with 'key'.
+pub fn hmac(key: &[u8], data: &[u8]) -> [u8; DIGEST_SIZE] {
+	let mut mac = Hmac::new(key);
+	mac.update(data);
+	mac.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn hex(data: &[u8]) -> String {
+		data.iter().map(|b| format!("{:02x}", b)).collect()
+	}
+
+	#[test]
+	fn test_vectors() {
+		// RFC 4231 test cases 1, 2, 3 and 6
+		let key_3 = [0xaau8; 20];
+		let key_6 = [0xaau8; 131];
+		let vectors: [(&[u8], &[u8], &str); 4] = [
+			(&[0x0b; 20], b"Hi There",
+			 "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854"),
+			(b"Jefe", b"what do ya want for nothing?",
+			 "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"),
+			(&key_3, &[0xdd; 50],
+			 "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb"),
+			(&key_6, b"Test Using Larger Than Block-Size Key - Hash Key First",
+			 "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598"),
+		];
+		for &(key, data, expected) in vectors.iter() {
+			assert_eq!(hex(&hmac(key, data)), expected);
+		}
+	}
+
+	#[test]
+	fn test_update() {
+		let data: Vec<u8> = (0..300u32).map(|x| (x * 7) as u8).collect();
+		let expected = hmac(b"key", &data);
+
+		for step in 1..140 {
+			let mut mac = Hmac::new(b"key");
+			for chunk in data.chunks(step) {
+				mac.update(chunk);
+			}
+			assert_eq!(&mac.finalize()[..], &expected[..]);
+		}
+	}
+}
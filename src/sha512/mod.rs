@@ -0,0 +1,225 @@
+/*
+* Module: sha512
+* Autor: Piotr Pszczółkowski (piotr@beesoft.pl)
+* Date: 17/10/2026
+*
+* Copyright (c) 2019, Piotr Pszczółkowski
+* All rights reserved.
+* 
+* Redistribution and use in source and binary forms, with or without
+* modification, are permitted provided that the following conditions are met:
+*
+* 1. Redistributions of source code must retain the above copyright notice, this
+*    list of conditions and the following disclaimer.
+*
+* 2. Redistributions in binary form must reproduce the above copyright notice,
+*    this list of conditions and the following disclaimer in the documentation
+*    and/or other materials provided with the distribution.
+*
+* THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+* AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+* IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+* DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+* FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+* DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+* SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+* CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+* OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+* OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+mod hmac;
+
+pub use self::hmac::{Hmac, hmac};
+
+/// Size of the hash in bytes.
+pub const DIGEST_SIZE: usize = 64;
+const BLOCK_SIZE: usize = 128;	// 128 bytes, 16 u64, 1024 bit
+
+/// SHA-512 hash function (FIPS 180-4).
+pub struct Sha512 {
+	h: [u64; 8],					// current hash value
+	length: u128,					// number of hashed bytes
+	block: [u8; BLOCK_SIZE],
+	nbytes: usize,					// number of bytes in 'block'
+}
+
+/// Creates new hash object.
+pub fn new() -> Sha512 {
+	Sha512{h: H0, length: 0, block: [0u8; BLOCK_SIZE], nbytes: 0}
+}
+
+/// Returns hash of 'data'.
+pub fn digest(data: &[u8]) -> [u8; DIGEST_SIZE] {
+	let mut hash = new();
+	hash.update(data);
+	hash.finalize()
+}
+
+impl Sha512 {
+	/// Adds 'data' to the hashed message.
+	pub fn update(&mut self, mut data: &[u8]) {
+		self.length = self.length.wrapping_add(data.len() as u128);
+
+		if self.nbytes > 0 {
+			let n = (BLOCK_SIZE - self.nbytes).min(data.len());
+			self.block[self.nbytes..(self.nbytes + n)].copy_from_slice(&data[..n]);
+			self.nbytes += n;
+			data = &data[n..];
+			if self.nbytes < BLOCK_SIZE {
+				return
+			}
+			let block = self.block;
+			self.process(&block);
+			self.nbytes = 0;
+		}
+
+		let mut chunks = data.chunks_exact(BLOCK_SIZE);
+		for chunk in &mut chunks {
+			self.process(chunk);
+		}
+		let rest = chunks.remainder();
+		self.block[..rest.len()].copy_from_slice(rest);
+		self.nbytes = rest.len();
+	}
+
+	/// Returns hash of the message.
+	/// The message is padded with 0x80, zeros and its length in bits.
+	pub fn finalize(mut self) -> [u8; DIGEST_SIZE] {
+		let bits = self.length.wrapping_mul(8);
+		let mut padding = [0u8; 2 * BLOCK_SIZE];
+		padding[0] = 0x80;
+		let n = if self.nbytes < BLOCK_SIZE - 16 { BLOCK_SIZE - self.nbytes } else { 2 * BLOCK_SIZE - self.nbytes };
+		padding[(n - 16)..n].copy_from_slice(&bits.to_be_bytes());
+		self.update(&padding[..n]);
+
+		let mut retv = [0u8; DIGEST_SIZE];
+		for (bytes, word) in retv.chunks_mut(8).zip(self.h.iter()) {
+			bytes.copy_from_slice(&word.to_be_bytes());
+		}
+		retv
+	}
+
+	/// Processes one block of the message.
+	fn process(&mut self, block: &[u8]) {
+		let mut w = [0u64; 80];
+		for (i, bytes) in block.chunks(8).enumerate() {
+			let mut word = [0u8; 8];
+			word.copy_from_slice(bytes);
+			w[i] = u64::from_be_bytes(word);
+		}
+		for i in 16..80 {
+			let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+			let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+			w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+		}
+
+		let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.h;
+		for i in 0..80 {
+			let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+			let ch = (e & f) ^ (!e & g);
+			let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+			let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+			let maj = (a & b) ^ (a & c) ^ (b & c);
+			let t2 = s0.wrapping_add(maj);
+
+			h = g;
+			g = f;
+			f = e;
+			e = d.wrapping_add(t1);
+			d = c;
+			c = b;
+			b = a;
+			a = t1.wrapping_add(t2);
+		}
+
+		for (x, y) in self.h.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+			*x = x.wrapping_add(*y);
+		}
+	}
+}
+
+// initial hash value: fractional parts of the square roots of the first 8 primes
+const H0: [u64; 8] = [
+	0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+	0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+// round constants: fractional parts of the cube roots of the first 80 primes
+const K: [u64; 80] = [
+	0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+	0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+	0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+	0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+	0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+	0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+	0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+	0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+	0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+	0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+	0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+	0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+	0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+	0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+	0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+	0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+	0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+	0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+	0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+	0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn hex(data: &[u8]) -> String {
+		data.iter().map(|b| format!("{:02x}", b)).collect()
+	}
+
+	#[test]
+	fn test_vectors() {
+		// FIPS 180-4 examples
+		let vectors = [
+			("", "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"),
+			("abc", "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"),
+			("abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+			 "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"),
+		];
+		for &(message, expected) in vectors.iter() {
+			assert_eq!(hex(&digest(message.as_bytes())), expected);
+		}
+
+		let data = vec![b'a'; 1_000_000];
+		assert_eq!(hex(&digest(&data)),
+		           "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973ebde0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b");
+	}
+
+	#[test]
+	fn test_padding() {
+		// lengths around the block boundary, verified with Python's hashlib
+		let data: Vec<u8> = (0..300u32).map(|x| (x * 7) as u8).collect();
+		let vectors = [
+			(111, "d2026b9857418e96"), (112, "4dc754b8985c03b8"), (127, "78de13cc28717e3e"),
+			(128, "6e7f10bc87eacc3e"), (129, "cdc5b3e2f22ed039"), (239, "d29cefaac62d3e8a"),
+			(240, "44f1b40dcf17562f"),
+		];
+		for &(n, expected) in vectors.iter() {
+			assert_eq!(hex(&digest(&data[..n])[..8]), expected, "{}", n);
+		}
+	}
+
+	#[test]
+	fn test_update() {
+		let data: Vec<u8> = (0..300u32).map(|x| (x * 7) as u8).collect();
+		let expected = digest(&data);
+
+		for step in 1..140 {
+			let mut hash = new();
+			for chunk in data.chunks(step) {
+				hash.update(chunk);
+			}
+			assert_eq!(&hash.finalize()[..], &expected[..]);
+		}
+	}
+}
@@ -27,12 +27,250 @@
 * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
-#![allow(dead_code)]
 
+use BlockCipher;
+use Error;
+use Padding;
+use modes;
+
+const BLOCK_SIZE: usize = 16;	// 16 bytes, 4 u32, 128 bit
+const KEY_SIZE_MIN: usize = 1;
+const KEY_SIZE_MAX: usize = 32;	// 32 bytes, 8 u32, 256 bit
+const ROUNDS: usize = 16;
 
 const MDS_POLYNOMIAL: u32 = 0x169; // x^8 + x^6 + x^5 + x^3 + 1, see [TWOFISH] 4.2
 const RS_POLYNOMIAL: u32  = 0x14d;  // x^8 + x^6 + x^3 + x^2 + 1, see [TWOFISH] 4.3
 
+pub struct Twofish {
+	s: [[u32; 256]; 4],	// key-dependent S-boxes combined with the MDS matrix
+	k: [u32; 40],			// expanded key words
+}
+
+/// Creates new Twofish object initialised with passed 'key'.
+/// Keys of any length up to 256 bits are accepted. Shorter keys are
+/// padded with zeros to the next supported size (128, 192 or 256 bits),
+/// see [TWOFISH] 4.3.
+pub fn new(key: &[u8]) -> Result<Twofish, Error> {
+	let key_len = key.len();
+	if !(KEY_SIZE_MIN..=KEY_SIZE_MAX).contains(&key_len) {
+		return Err(Error::InvalidKeyLength{len: key_len, min: KEY_SIZE_MIN, max: KEY_SIZE_MAX});
+	}
+
+	let key = {
+		let n = if key_len <= 16 { 16 } else if key_len <= 24 { 24 } else { 32 };
+		let mut buffer = vec![0u8; n];
+		buffer[..key_len].copy_from_slice(key);
+		buffer
+	};
+	let k = key.len() / 8;
+
+	// S vector: [s0 s1 s2 s3] = RS · [m0 ... m7], see [TWOFISH] 4.3
+	let mut s = [0u8; 16];
+	for i in 0..k {
+		for (j, rs_row) in RS.iter().enumerate() {
+			for (n, rs_val) in rs_row.iter().enumerate() {
+				s[4 * i + j] ^= gf_mult(key[8 * i + n], *rs_val, RS_POLYNOMIAL);
+			}
+		}
+	}
+
+	let mut tf = Twofish{s: [[0u32; 256]; 4], k: [0u32; 40]};
+
+	// expanded key words, see [TWOFISH] 4.3.2
+	let mut tmp = [0u8; 4];
+	for i in 0..20u8 {
+		tmp.iter_mut().for_each(|x| *x = 2 * i);
+		let a = h(&tmp, &key, 0);
+		tmp.iter_mut().for_each(|x| *x = 2 * i + 1);
+		let b = rol(h(&tmp, &key, 1), 8);
+
+		tf.k[2 * i as usize] = a.wrapping_add(b);
+		tf.k[2 * i as usize + 1] = rol(a.wrapping_add(b.wrapping_mul(2)), 9);
+	}
+
+	// key-dependent S-boxes, see [TWOFISH] 4.3.5
+	for i in 0..256 {
+		let x = i as u8;
+		let y = match k {
+			2 => [
+				SBOX[1][(SBOX[0][(SBOX[0][x as usize] ^ s[0]) as usize] ^ s[4]) as usize],
+				SBOX[0][(SBOX[0][(SBOX[1][x as usize] ^ s[1]) as usize] ^ s[5]) as usize],
+				SBOX[1][(SBOX[1][(SBOX[0][x as usize] ^ s[2]) as usize] ^ s[6]) as usize],
+				SBOX[0][(SBOX[1][(SBOX[1][x as usize] ^ s[3]) as usize] ^ s[7]) as usize],
+			],
+			3 => [
+				SBOX[1][(SBOX[0][(SBOX[0][(SBOX[1][x as usize] ^ s[0]) as usize] ^ s[4]) as usize] ^ s[8]) as usize],
+				SBOX[0][(SBOX[0][(SBOX[1][(SBOX[1][x as usize] ^ s[1]) as usize] ^ s[5]) as usize] ^ s[9]) as usize],
+				SBOX[1][(SBOX[1][(SBOX[0][(SBOX[0][x as usize] ^ s[2]) as usize] ^ s[6]) as usize] ^ s[10]) as usize],
+				SBOX[0][(SBOX[1][(SBOX[1][(SBOX[0][x as usize] ^ s[3]) as usize] ^ s[7]) as usize] ^ s[11]) as usize],
+			],
+			_ => [
+				SBOX[1][(SBOX[0][(SBOX[0][(SBOX[1][(SBOX[1][x as usize] ^ s[0]) as usize] ^ s[4]) as usize] ^ s[8]) as usize] ^ s[12]) as usize],
+				SBOX[0][(SBOX[0][(SBOX[1][(SBOX[1][(SBOX[0][x as usize] ^ s[1]) as usize] ^ s[5]) as usize] ^ s[9]) as usize] ^ s[13]) as usize],
+				SBOX[1][(SBOX[1][(SBOX[0][(SBOX[0][(SBOX[0][x as usize] ^ s[2]) as usize] ^ s[6]) as usize] ^ s[10]) as usize] ^ s[14]) as usize],
+				SBOX[0][(SBOX[1][(SBOX[1][(SBOX[0][(SBOX[1][x as usize] ^ s[3]) as usize] ^ s[7]) as usize] ^ s[11]) as usize] ^ s[15]) as usize],
+			],
+		};
+		for (j, v) in y.iter().enumerate() {
+			tf.s[j][i] = mds_column_mult(*v, j);
+		}
+	}
+
+	Ok(tf)
+}
+
+impl Twofish {
+
+	/// encrypts 'input' in CBC mode random generated iv.
+	pub fn encrypt_cbc(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc(self, input, padding)
+	}
+
+	/// encrypts 'input' in CBC mode using 'iv'.
+	pub fn encrypt_cbc_iv(&self, input: &[u8], iv: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc_iv(self, input, iv, padding)
+	}
+
+	/// decrypts 'cipher' in CBC mode
+	pub fn decrypt_cbc(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_cbc(self, cipher, padding)
+	}
+
+	/// encrypts 'input' in ECB mode
+	pub fn encrypt_ecb(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ecb(self, input, padding)
+	}
+
+	/// decrypts 'cipher' in ECB mode
+	pub fn decrypt_ecb(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ecb(self, cipher, padding)
+	}
+
+	/// CTR : encrypts vector of bytes in CTR mode
+	/// using passed IV vector (initial counter block).
+	pub fn encrypt_ctr_iv(&self, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr_iv(self, input, iv)
+	}
+
+	pub fn encrypt_ctr(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr(self, input)
+	}
+
+	/// CTR : decrypts vector of bytes in CTR mode
+	pub fn decrypt_ctr(&self, cipher: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ctr(self, cipher)
+	}
+
+	/// Encrypts one block (four u32 words)
+	pub fn encrypt(&self, x: (u32, u32, u32, u32)) -> (u32, u32, u32, u32) {
+		// input whitening
+		let mut a = x.0 ^ self.k[0];
+		let mut b = x.1 ^ self.k[1];
+		let mut c = x.2 ^ self.k[2];
+		let mut d = x.3 ^ self.k[3];
+
+		let mut i = 0usize;
+		while i < ROUNDS {
+			let k = &self.k[(8 + 2 * i)..(12 + 2 * i)];
+
+			let t1 = self.g1(b);
+			let t0 = self.g0(a).wrapping_add(t1);
+			c = ror(c ^ t0.wrapping_add(k[0]), 1);
+			d = rol(d, 1) ^ t1.wrapping_add(t0).wrapping_add(k[1]);
+
+			let t1 = self.g1(d);
+			let t0 = self.g0(c).wrapping_add(t1);
+			a = ror(a ^ t0.wrapping_add(k[2]), 1);
+			b = rol(b, 1) ^ t1.wrapping_add(t0).wrapping_add(k[3]);
+
+			i += 2;
+		}
+
+		// output whitening with undo of the last swap
+		(c ^ self.k[4], d ^ self.k[5], a ^ self.k[6], b ^ self.k[7])
+	}
+
+	/// Decrypts one block (four u32 words)
+	pub fn decrypt(&self, x: (u32, u32, u32, u32)) -> (u32, u32, u32, u32) {
+		// undo output whitening
+		let mut a = x.2 ^ self.k[6];
+		let mut b = x.3 ^ self.k[7];
+		let mut c = x.0 ^ self.k[4];
+		let mut d = x.1 ^ self.k[5];
+
+		let mut i = ROUNDS;
+		while i > 0 {
+			let k = &self.k[(4 + 2 * i)..(8 + 2 * i)];
+
+			let t1 = self.g1(d);
+			let t0 = self.g0(c).wrapping_add(t1);
+			a = rol(a, 1) ^ t0.wrapping_add(k[2]);
+			b = ror(b ^ t1.wrapping_add(t0).wrapping_add(k[3]), 1);
+
+			let t1 = self.g1(b);
+			let t0 = self.g0(a).wrapping_add(t1);
+			c = rol(c, 1) ^ t0.wrapping_add(k[0]);
+			d = ror(d ^ t1.wrapping_add(t0).wrapping_add(k[1]), 1);
+
+			i -= 2;
+		}
+
+		// undo input whitening
+		(a ^ self.k[0], b ^ self.k[1], c ^ self.k[2], d ^ self.k[3])
+	}
+
+	// g function for the first word of the round, see [TWOFISH] 4.3.2
+	#[inline]
+	fn g0(&self, x: u32) -> u32 {
+		self.s[0][(x & 0xff) as usize] ^
+		self.s[1][(x.wrapping_shr(8) & 0xff) as usize] ^
+		self.s[2][(x.wrapping_shr(16) & 0xff) as usize] ^
+		self.s[3][x.wrapping_shr(24) as usize]
+	}
+
+	// g function for the second word of the round (rotated left by 8 bits)
+	#[inline]
+	fn g1(&self, x: u32) -> u32 {
+		self.s[1][(x & 0xff) as usize] ^
+		self.s[2][(x.wrapping_shr(8) & 0xff) as usize] ^
+		self.s[3][(x.wrapping_shr(16) & 0xff) as usize] ^
+		self.s[0][x.wrapping_shr(24) as usize]
+	}
+}
+
+impl BlockCipher for Twofish {
+	const BLOCK_SIZE: usize = BLOCK_SIZE;
+	const KEY_SIZE_MIN: usize = KEY_SIZE_MIN;
+	const KEY_SIZE_MAX: usize = KEY_SIZE_MAX;
+
+	fn new(key: &[u8]) -> Result<Twofish, Error> {
+		new(key)
+	}
+
+	fn encrypt_block(&self, block: &mut [u8]) {
+		let x = bytes4block(block);
+		block4bytes(self.encrypt(x), block);
+	}
+
+	fn decrypt_block(&self, block: &mut [u8]) {
+		let x = bytes4block(block);
+		block4bytes(self.decrypt(x), block);
+	}
+}
+
+/// Reads a block of four little-endian u32 words from bytes in 'data'.
+fn bytes4block(data: &[u8]) -> (u32, u32, u32, u32) {
+	(load32(&data[0..]), load32(&data[4..]), load32(&data[8..]), load32(&data[12..]))
+}
+
+/// Stores a block of four u32 words as bytes in 'data' in little-endian form.
+fn block4bytes(x: (u32, u32, u32, u32), data: &mut [u8]) {
+	store32(&mut data[0..], x.0);
+	store32(&mut data[4..], x.1);
+	store32(&mut data[8..], x.2);
+	store32(&mut data[12..], x.3);
+}
+
 /// Stores 'x' as bytes in 'data' in little-endian form.
 fn store32(data: &mut [u8], x: u32) {
 	data[0] = x as u8;
@@ -47,24 +285,18 @@ fn load32(data: &[u8]) -> u32 {
 	let x1 = (data[1] as u32).wrapping_shl(8);
 	let x2 = (data[2] as u32).wrapping_shl(16);
 	let x3 = (data[3] as u32).wrapping_shl(24);
-	
+
 	x0|x1|x2|x3
 }
 
 /// Returns 'x' after a left circular rotation of 'n' bits.
 fn rol(x: u32, n: u32) -> u32 {
-	let x0 = x.wrapping_shl(n & 31);
-	let x1 = x.wrapping_shr(32 - (n & 31));
-	
-	x0|x1
+	x.rotate_left(n & 31)
 }
 
 /// Returns 'x' after a right circular rotation of 'n' bits.
 fn ror(x: u32, n: u32) -> u32 {
-	let x0 = x.wrapping_shr(n & 32);
-	let x1 = x.wrapping_shl(32 - (n & 31));
-	
-	x0|x1
+	x.rotate_right(n & 31)
 }
 
 /// gf_mult returns a·b in GF(2^8)/p
@@ -72,13 +304,14 @@ fn gf_mult(mut a: u8, b: u8, p: u32) -> u8 {
 	let mut b32 = [0u32, b as u32];
 	let p32 = [0u32, p];
 	let mut retv = 0u32;
-	
+
 	for _i in 0..7 {
 		retv ^= b32[(a & 1) as usize];
 		a = a.wrapping_shr(1);
 		b32[1] = p32[b32[1].wrapping_shr(7) as usize] ^ b32[1].wrapping_shl(1);
 	}
-	 
+	retv ^= b32[(a & 1) as usize];
+
 	retv as u8
 }
 
@@ -88,7 +321,7 @@ fn mds_column_mult(i: u8, col: usize) -> u32 {
 	let mul01 = i;
 	let mul5B = gf_mult(i, 0x5B, MDS_POLYNOMIAL);
 	let mulEF = gf_mult(i, 0xEF, MDS_POLYNOMIAL);
-	
+
 	match col {
 		0 => {
 			let x0 = mul01 as u32;
@@ -102,7 +335,7 @@ fn mds_column_mult(i: u8, col: usize) -> u32 {
 			let x1 = (mulEF as u32).wrapping_shl(8);
 			let x2 = (mul5B as u32).wrapping_shl(16);
 			let x3 = (mul01 as u32).wrapping_shl(24);
-			x0|x1|x2|x3			
+			x0|x1|x2|x3
 		}
 		2 => {
 			let x0 = mul5B as u32;
@@ -125,55 +358,35 @@ fn mds_column_mult(i: u8, col: usize) -> u32 {
 }
 
 // h implements the S-box generation function. See [TWOFISH] 4.3.5
+// 'key' is the (padded) key of 16, 24 or 32 bytes.
 fn h(i: &[u8], key: &[u8], offset: usize) -> u32 {
 	let mut y = [i[0], i[1], i[2], i[3]];
-	let mut n = key.len();
-	
+	let mut n = key.len() / 8;
+
 	if n == 4 {
-		y[0] = SBOX[1][y[0] as usize] ^ key[4 * (6 + offset) + 0];
+		y[0] = SBOX[1][y[0] as usize] ^ key[4 * (6 + offset)];
 		y[1] = SBOX[0][y[1] as usize] ^ key[4 * (6 + offset) + 1];
 		y[2] = SBOX[0][y[2] as usize] ^ key[4 * (6 + offset) + 2];
 		y[3] = SBOX[1][y[3] as usize] ^ key[4 * (6 + offset) + 3];
 		n -= 1;
 	}
 	if n == 3 {
-		y[0] = SBOX[1][y[0] as usize] ^ key[4 * (4 + offset) +0];
-		y[1] = SBOX[1][y[1] as usize] ^ key[4 * (4 + offset) +1];
-		y[2] = SBOX[0][y[2] as usize] ^ key[4 * (4 + offset) +2];
-		y[3] = SBOX[0][y[3] as usize] ^ key[4 * (4 + offset) +3];
+		y[0] = SBOX[1][y[0] as usize] ^ key[4 * (4 + offset)];
+		y[1] = SBOX[1][y[1] as usize] ^ key[4 * (4 + offset) + 1];
+		y[2] = SBOX[0][y[2] as usize] ^ key[4 * (4 + offset) + 2];
+		y[3] = SBOX[0][y[3] as usize] ^ key[4 * (4 + offset) + 3];
 		n -= 1;
 	}
 	if n == 2 {
-		{
-			let sv = SBOX[0][SBOX[0][y[0] as usize] as usize];
-			let k0 = key[4 * (2 + offset) + 0];
-			let k1 = key[4 * (0 + offset) + 0];
-			y[0] = SBOX[1][(sv ^ k0 ^ k1) as usize];
-		}
-		{
-			let sv = SBOX[0][SBOX[1][y[1] as usize] as usize];
-			let k0 = key[4 * (2 + offset) + 1];
-			let k1 = key[4 * (0 + offset) + 1];
-			y[1] = SBOX[0][(sv ^ k0 ^ k1) as usize];
-			
-		}
-		{
-			let sv = SBOX[1][SBOX[0][y[2] as usize] as usize];
-			let k0 = key[4 * (2 + offset) + 2];
-			let k1 = key[4 * (0 + offset) + 2];
-			y[2] = SBOX[1][(sv ^ k0 ^ k1) as usize];
-		}
-		{
-			let sv = SBOX[1][SBOX[1][y[3] as usize] as usize];
-			let k0 = key[4 * (2 + offset) + 3];
-			let k1 = key[4 * (0 + offset) + 3];
-			y[3] = SBOX[0][(sv ^ k0 ^ k1) as usize];
-		}
+		y[0] = SBOX[1][(SBOX[0][(SBOX[0][y[0] as usize] ^ key[4 * (2 + offset)]) as usize] ^ key[4 * offset]) as usize];
+		y[1] = SBOX[0][(SBOX[0][(SBOX[1][y[1] as usize] ^ key[4 * (2 + offset) + 1]) as usize] ^ key[4 * offset + 1]) as usize];
+		y[2] = SBOX[1][(SBOX[1][(SBOX[0][y[2] as usize] ^ key[4 * (2 + offset) + 2]) as usize] ^ key[4 * offset + 2]) as usize];
+		y[3] = SBOX[0][(SBOX[1][(SBOX[1][y[3] as usize] ^ key[4 * (2 + offset) + 3]) as usize] ^ key[4 * offset + 3]) as usize];
 	}
-	
+
 	let mut retv = 0u32;
-	for i in 0..4 {
-		retv ^= mds_column_mult(y[i], i);
+	for (i, v) in y.iter().enumerate() {
+		retv ^= mds_column_mult(*v, i);
 	}
 	retv
 }
@@ -225,3 +438,177 @@ const SBOX: [[u8; 256]; 2] = [
 		0xd7, 0x61, 0x1e, 0xb4, 0x50, 0x04, 0xf6, 0xc2, 0x16, 0x25, 0x86, 0x56, 0x55, 0x09, 0xbe, 0x91,
 	],
 ];
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn check(key: &[u8], plain: &[u8], expected: &[u8]) {
+		let tf = new(key).unwrap();
+
+		let mut encrypted = [0u8; BLOCK_SIZE];
+		block4bytes(tf.encrypt(bytes4block(plain)), &mut encrypted);
+		assert_eq!(&encrypted[..], expected);
+
+		let mut decrypted = [0u8; BLOCK_SIZE];
+		block4bytes(tf.decrypt(bytes4block(&encrypted)), &mut decrypted);
+		assert_eq!(&decrypted[..], plain);
+	}
+
+	#[test]
+	fn test_block_128() {
+		check(&[0u8; 16], &[0u8; 16],
+		      &[0x9f, 0x58, 0x9f, 0x5c, 0xf6, 0x12, 0x2c, 0x32, 0xb6, 0xbf, 0xec, 0x2f, 0x2a, 0xe8, 0xc3, 0x5a]);
+		check(&[0x9f, 0x58, 0x9f, 0x5c, 0xf6, 0x12, 0x2c, 0x32, 0xb6, 0xbf, 0xec, 0x2f, 0x2a, 0xe8, 0xc3, 0x5a],
+		      &[0xd4, 0x91, 0xdb, 0x16, 0xe7, 0xb1, 0xc3, 0x9e, 0x86, 0xcb, 0x08, 0x6b, 0x78, 0x9f, 0x54, 0x19],
+		      &[0x01, 0x9f, 0x98, 0x09, 0xde, 0x17, 0x11, 0x85, 0x8f, 0xaa, 0xc3, 0xa3, 0xba, 0x20, 0xfb, 0xc3]);
+	}
+
+	#[test]
+	fn test_block_192() {
+		check(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10,
+		        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77],
+		      &[0u8; 16],
+		      &[0xcf, 0xd1, 0xd2, 0xe5, 0xa9, 0xbe, 0x9c, 0xdf, 0x50, 0x1f, 0x13, 0xb8, 0x92, 0xbd, 0x22, 0x48]);
+		check(&[0x88, 0xb2, 0xb2, 0x70, 0x6b, 0x10, 0x5e, 0x36, 0xb4, 0x46, 0xbb, 0x6d, 0x73, 0x1a, 0x1e, 0x88,
+		        0xef, 0xa7, 0x1f, 0x78, 0x89, 0x65, 0xbd, 0x44],
+		      &[0x39, 0xda, 0x69, 0xd6, 0xba, 0x49, 0x97, 0xd5, 0x85, 0xb6, 0xdc, 0x07, 0x3c, 0xa3, 0x41, 0xb2],
+		      &[0x18, 0x2b, 0x02, 0xd8, 0x14, 0x97, 0xea, 0x45, 0xf9, 0xda, 0xac, 0xdc, 0x29, 0x19, 0x3a, 0x65]);
+	}
+
+	#[test]
+	fn test_block_256() {
+		check(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10,
+		        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff],
+		      &[0u8; 16],
+		      &[0x37, 0x52, 0x7b, 0xe0, 0x05, 0x23, 0x34, 0xb8, 0x9f, 0x0c, 0xfc, 0xca, 0xe8, 0x7c, 0xfa, 0x20]);
+		check(&[0xd4, 0x3b, 0xb7, 0x55, 0x6e, 0xa3, 0x2e, 0x46, 0xf2, 0xa2, 0x82, 0xb7, 0xd4, 0x5b, 0x4e, 0x0d,
+		        0x57, 0xff, 0x73, 0x9d, 0x4d, 0xc9, 0x2c, 0x1b, 0xd7, 0xfc, 0x01, 0x70, 0x0c, 0xc8, 0x21, 0x6f],
+		      &[0x90, 0xaf, 0xe9, 0x1b, 0xb2, 0x88, 0x54, 0x4f, 0x2c, 0x32, 0xdc, 0x23, 0x9b, 0x26, 0x35, 0xe6],
+		      &[0x6c, 0xb4, 0x56, 0x1c, 0x40, 0xbf, 0x0a, 0x97, 0x05, 0x93, 0x1c, 0xb6, 0xd4, 0x08, 0xe7, 0xfa]);
+	}
+
+	// ecb_tbl.txt: 49 iterations where the next key is built from
+	// the previous plain text and key, and the next plain text is
+	// the previous cipher text.
+	fn check_table(key_len: usize, expected: &[u8]) {
+		let mut key = vec![0u8; key_len];
+		let mut plain = [0u8; BLOCK_SIZE];
+		let mut cipher = [0u8; BLOCK_SIZE];
+
+		for _i in 0..49 {
+			let tf = new(&key).unwrap();
+			block4bytes(tf.encrypt(bytes4block(&plain)), &mut cipher);
+
+			let mut next = plain.to_vec();
+			next.extend_from_slice(&key[..(key_len - BLOCK_SIZE)]);
+			key = next;
+			plain = cipher;
+		}
+		assert_eq!(&cipher[..], expected);
+	}
+
+	#[test]
+	fn test_table() {
+		check_table(16, &[0x5d, 0x9d, 0x4e, 0xef, 0xfa, 0x91, 0x51, 0x57, 0x55, 0x24, 0xf1, 0x15, 0x81, 0x5a, 0x12, 0xe0]);
+		check_table(24, &[0xe7, 0x54, 0x49, 0x21, 0x2b, 0xee, 0xf9, 0xf4, 0xa3, 0x90, 0xbd, 0x86, 0x0a, 0x64, 0x09, 0x41]);
+		check_table(32, &[0x37, 0xfe, 0x26, 0xff, 0x1c, 0xf6, 0x61, 0x75, 0xf5, 0xdd, 0xf4, 0xc3, 0x3b, 0x97, 0xa2, 0x05]);
+	}
+
+	#[test]
+	fn test_short_key() {
+		let key = b"Yamato";
+		let padded = {
+			let mut buffer = key.to_vec();
+			buffer.resize(16, 0);
+			buffer
+		};
+		let plain = (1u32, 2u32, 3u32, 4u32);
+
+		let tf = new(&key[..]).unwrap();
+		let tp = new(&padded).unwrap();
+		assert_eq!(tf.encrypt(plain), tp.encrypt(plain));
+
+		assert!(new(&[]).is_err());
+		assert!(new(&[0u8; 33]).is_err());
+	}
+
+	#[test]
+	fn test_ecb() {
+		let key = b"0123456789abcdef";
+		let tf = new(&key[..]).unwrap();
+
+		let plain = "Artur, Błazej, Jolanta, Piotr Pszczółkowski".as_bytes().to_vec();
+
+		let encrypted = match tf.encrypt_ecb(&plain, Padding::Legacy) {
+			Ok(x) => x,
+			Err(err) => panic!("{}", err)
+		};
+		assert_eq!(encrypted.len(), 48);
+
+		let decrypted = match tf.decrypt_ecb(&encrypted, Padding::Legacy) {
+			Ok(x) => x,
+			Err(err) => panic!("{}", err)
+		};
+		assert_eq!(decrypted, plain);
+	}
+
+	#[test]
+	fn test_cbc_iv() {
+		let key = b"0123456789abcdef";
+		let iv = b"fedcba9876543210".to_vec();
+		let plain = b"Yamato & MusashiYamato & Musashi".to_vec();
+		let expected = vec![0x66, 0x65, 0x64, 0x63, 0x62, 0x61, 0x39, 0x38, 0x37, 0x36, 0x35, 0x34, 0x33, 0x32, 0x31, 0x30,
+		                    0x65, 0x7d, 0x49, 0xe5, 0xb9, 0x06, 0x7d, 0x25, 0x12, 0xaa, 0x41, 0xdb, 0x78, 0x64, 0xdd, 0x99,
+		                    0xfb, 0x44, 0x70, 0xe9, 0x69, 0x3b, 0x19, 0x4a, 0xd9, 0x33, 0x73, 0x58, 0xa5, 0xcf, 0xdc, 0xb2];
+
+		let tf = new(&key[..]).unwrap();
+
+		let encrypted = match tf.encrypt_cbc_iv(&plain, &iv, Padding::Legacy) {
+			Ok(x) => x,
+			Err(err) => panic!("{}", err)
+		};
+		assert_eq!(encrypted, expected);
+
+		let decrypted = match tf.decrypt_cbc(&encrypted, Padding::Legacy) {
+			Ok(x) => x,
+			Err(err) => panic!("{}", err)
+		};
+		assert_eq!(decrypted, plain);
+	}
+
+	#[test]
+	fn test_cbc() {
+		let key = b"0123456789abcdef0123456789abcdef";
+		let tf = new(&key[..]).unwrap();
+		let plain = b"Yamato & Musashi".to_vec();
+
+		match tf.encrypt_cbc(&plain, Padding::Legacy) {
+			Ok(encrypted) => {
+				match tf.decrypt_cbc(&encrypted, Padding::Legacy) {
+					Ok(decrypted) => {
+						assert_eq!(decrypted, plain);
+					},
+					Err(err) => panic!("{}", err)
+				}
+			},
+			Err(err) => panic!("{}", err)
+		};
+	}
+
+	#[test]
+	fn test_block_cipher() {
+		let plain = [0u8; 16];
+		let expected = [0x9fu8, 0x58, 0x9f, 0x5c, 0xf6, 0x12, 0x2c, 0x32, 0xb6, 0xbf, 0xec, 0x2f, 0x2a, 0xe8, 0xc3, 0x5a];
+
+		let tf = <Twofish as BlockCipher>::new(&[0u8; 16]).unwrap();
+		let mut block = plain;
+		tf.encrypt_block(&mut block);
+		assert_eq!(block, expected);
+		tf.decrypt_block(&mut block);
+		assert_eq!(block, plain);
+
+		assert_eq!(Twofish::BLOCK_SIZE, 16);
+		assert!(<Twofish as BlockCipher>::new(&[0u8; Twofish::KEY_SIZE_MAX + 1]).is_err());
+	}
+}
@@ -28,9 +28,10 @@
 * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
-use padding;
-use padding_index;
-use rand::Rng;
+use BlockCipher;
+use Error;
+use Padding;
+use modes;
 
 const KEY_SIZE: usize = 12;
 const BLOCK_SIZE: usize = 12;
@@ -41,18 +42,37 @@ const DRCON: [u32; 12] = [0xb1b1, 0x7373, 0xe6e6, 0xdddd, 0xabab, 0x4747, 0x8e8e
 pub struct Way3 {
 	k:  (u32, u32, u32),
 	ki: (u32, u32, u32),
+	order: ByteOrder,
 }
 
-/// Creates new Way3 object initialised with passed 'key'.
-pub fn new(key: &[u8]) -> Result<Way3, String> {
+/// Byte order of the three u32 words (a[0], a[1], a[2]) of the key and the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+	/// a[0], a[1], a[2] as little-endian words, the byte order used by this crate so far.
+	Little,
+	/// The block as one big-endian 96-bit number a[2]:a[1]:a[0], the order
+	/// in which J. Daemen's reference implementation prints its vectors
+	/// (Applied Cryptography, Crypto++).
+	Big,
+}
+
+/// Creates new Way3 object initialised with passed 'key'
+/// with little-endian words in the key and the block.
+pub fn new(key: &[u8]) -> Result<Way3, Error> {
+	with_byte_order(key, ByteOrder::Little)
+}
+
+/// Creates new Way3 object for 'key' reading and writing
+/// words of the key and the blocks in byte order 'order'.
+pub fn with_byte_order(key: &[u8], order: ByteOrder) -> Result<Way3, Error> {
 	if key.len() != KEY_SIZE {
-		return Err("invalid key size".to_string())
+		return Err(Error::InvalidKeyLength{len: key.len(), min: KEY_SIZE, max: KEY_SIZE})
 	}
 
-	let k = bytes3block(key);
+	let k = load(key, order);
 	let ki = mu(theta(k));
 	
-	let w3 = Way3{k, ki};
+	let w3 = Way3{k, ki, order};
 	Ok(w3)
 }
 
@@ -60,136 +80,43 @@ pub fn new(key: &[u8]) -> Result<Way3, String> {
 impl Way3 {
 	
 	/// encrypts 'input' in CBC mode random generated iv.
-	pub fn encrypt_cbc(&self, input: &Vec<u8>) -> Result<Vec<u8>, String> {
-		self.encrypt_cbc_iv(input, {
-			let mut buffer = [0u8; BLOCK_SIZE];
-			rand::thread_rng().fill(&mut buffer);
-			&buffer.to_vec()
-		})
+	pub fn encrypt_cbc(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc(self, input, padding)
 	}
 	
 	/// encrypts 'input' in CBC mode using 'iv'.
-	pub fn encrypt_cbc_iv(&self, input: &Vec<u8>, iv: &Vec<u8>) -> Result<Vec<u8>, String> {
-		// handle caller mistakes
-		if iv.len() != BLOCK_SIZE { return Err("invalid iv size".to_string()) }
-		if input.len() == 0 { return Err("nothing to encrypt".to_string()) }
-		
-		// create plain text buffer from 'input',
-		// with padding if needed
-		let plain = {
-         let mut buffer: Vec<u8> = Vec::new();
-         buffer.extend(input);
-         let n = buffer.len() % BLOCK_SIZE;
-         if n != 0 {
-            buffer.extend(padding(BLOCK_SIZE - n));
-         }
-         buffer
-		};
-      let nbytes = plain.len();
-		
-		// create cipher text buffer
-		// the first block initialised with 'iv' content
-		let mut cipher = {
-			let mut buffer = Vec::new();
-			buffer.resize(nbytes + BLOCK_SIZE, 0);
-         buffer[0..BLOCK_SIZE].copy_from_slice(iv);
-			buffer
-		};
-		
-		let mut i = 0usize;
-		let mut c = bytes3block(iv);
-		while i < nbytes {
-			let t = bytes3block(&plain[i..]);
-			c = self.encrypt((t.0 ^ c.0, t.1 ^ c.1, t.2 ^ c.2));
-			block3bytes(c, &mut cipher[(i + BLOCK_SIZE)..]);
-			i += BLOCK_SIZE;
-		}
-		
-		Ok(cipher)
+	pub fn encrypt_cbc_iv(&self, input: &[u8], iv: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_cbc_iv(self, input, iv, padding)
 	}
 	
 	/// decrypts 'cipher' in CBC mode
-	pub fn decrypt_cbc(&self, cipher: &Vec<u8>) -> Result<Vec<u8>, String> {
- 		let nbytes = cipher.len();
-		if nbytes <= BLOCK_SIZE { return  Err("cipher data size is to short".to_string()) }
-		if (nbytes % BLOCK_SIZE) != 0 { return Err("cipher data size is wrong".to_string()) }
-		
-		let mut plain: Vec<u8> = Vec::new();
-      plain.resize(nbytes - BLOCK_SIZE, 0);
-		
-		
-		let mut p = bytes3block(&cipher[..]);
-		let mut i = BLOCK_SIZE;
-		while i < nbytes {
-			let a = bytes3block(&cipher[i..]);
-			let t = a;
-			let c = self.decrypt(a);
-			block3bytes((c.0 ^ p.0, c.1 ^ p.1, c.2 ^ p.2), &mut plain[(i - BLOCK_SIZE)..]);
-			p = t;
-			i += BLOCK_SIZE;
-		}
-		
-		match padding_index(&plain) {
-			Some(idx) => {
-            let retv = plain[..idx].to_vec();
-				Ok(retv)
-			},
-			_ => {
-				Ok(plain)
-			}
-		}
+	pub fn decrypt_cbc(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_cbc(self, cipher, padding)
 	}
 	
 	
 	
-	pub fn encrypt_ecb(&self, input: &Vec<u8>) -> Result<Vec<u8>, String> {
-		if input.len() == 0 { return Err("plain text size is 0".to_string()) }
-		
-		let plain = {
-			let mut buffer = Vec::new();
-			buffer.extend(input);
-			let n = buffer.len() % BLOCK_SIZE;
-			if n != 0 {
-				buffer.extend(padding(BLOCK_SIZE - n));
-			}
-			buffer
-		};
-		let nbytes = plain.len();
-		
-		let mut cipher = Vec::with_capacity(nbytes);
-		cipher.resize(nbytes, 0);
-		
-		let mut i = 0usize;
-		while i < nbytes {
-			let p = bytes3block(&plain[i..]);
-			let c = self.encrypt(p);
-			block3bytes(c, &mut cipher[i..]);
-			i += BLOCK_SIZE;
-		}
-		
-		Ok(cipher)
+	pub fn encrypt_ecb(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ecb(self, input, padding)
 	}
 	
-	pub fn decrypt_ecb(&self, cipher: &Vec<u8>) -> Result<Vec<u8>, String> {
-		let nbytes = cipher.len();
-		if nbytes == 0 { return Err("cipher text size is 0".to_string()) }
-		if (nbytes % BLOCK_SIZE) != 0 { return Err("invalid cipher text size".to_string()) }
-		
-		let mut plain = Vec::with_capacity(nbytes);
-		plain.resize(nbytes, 0);
-		
-		let mut i = 0usize;
-		while i < nbytes {
-			let c = bytes3block(&cipher[i..]);
-			let p = self.decrypt(c);
-			block3bytes(p, &mut plain[i..]);
-			i += BLOCK_SIZE;
-		}
-		
-		match padding_index(&plain) {
-			Some(idx) => Ok(plain[..idx].to_vec()),
-			_ => Ok(plain)
-		}
+	pub fn decrypt_ecb(&self, cipher: &[u8], padding: Padding) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ecb(self, cipher, padding)
+	}
+
+	/// CTR : encrypts vector of bytes in CTR mode
+	/// using passed IV vector (initial counter block).
+	pub fn encrypt_ctr_iv(&self, input: &[u8], iv: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr_iv(self, input, iv)
+	}
+
+	pub fn encrypt_ctr(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::encrypt_ctr(self, input)
+	}
+
+	/// CTR : decrypts vector of bytes in CTR mode
+	pub fn decrypt_ctr(&self, cipher: &[u8]) -> Result<Vec<u8>, Error> {
+		modes::decrypt_ctr(self, cipher)
 	}
 	
 	pub fn encrypt(&self, mut x: (u32, u32, u32)) -> (u32, u32, u32) {
@@ -232,7 +159,29 @@ impl Way3 {
 	
 }
 
+
+impl BlockCipher for Way3 {
+	const BLOCK_SIZE: usize = BLOCK_SIZE;
+	const KEY_SIZE_MIN: usize = KEY_SIZE;
+	const KEY_SIZE_MAX: usize = KEY_SIZE;
+
+	fn new(key: &[u8]) -> Result<Way3, Error> {
+		new(key)
+	}
+
+	fn encrypt_block(&self, block: &mut [u8]) {
+		let x = load(block, self.order);
+		store(self.encrypt(x), self.order, block);
+	}
+
+	fn decrypt_block(&self, block: &mut [u8]) {
+		let x = load(block, self.order);
+		store(self.decrypt(x), self.order, block);
+	}
+}
+
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
 	use super::*;
 	
@@ -288,14 +237,14 @@ mod tests {
 		
 		let plain = "Artur, Błazej, Jolanta, Piotr Pszczółkowski".as_bytes().to_vec();
 		
-		let encrypted = match w3.encrypt_ecb(&plain) {
+		let encrypted = match w3.encrypt_ecb(&plain, Padding::Legacy) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 
-		let decrypted = match w3.decrypt_ecb(&encrypted) {
+		let decrypted = match w3.decrypt_ecb(&encrypted, Padding::Legacy) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		assert_eq!(decrypted, plain);	
 		
@@ -316,15 +265,15 @@ mod tests {
 		
 		let plain = "Artur, Błazej, Jolanta, Piotr Pszczółkowski".as_bytes().to_vec();
 		
-		let encrypted = match w3.encrypt_cbc_iv(&plain, &iv) {
+		let encrypted = match w3.encrypt_cbc_iv(&plain, &iv, Padding::Legacy) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		assert_eq!(encrypted, expected);
 
-		let decrypted = match w3.decrypt_cbc(&encrypted) {
+		let decrypted = match w3.decrypt_cbc(&encrypted, Padding::Legacy) {
 			Ok(x) => x,
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		assert_eq!(decrypted, plain);	
 	}
@@ -335,19 +284,107 @@ mod tests {
 		let w3 = new(&key).unwrap();
 		let plain = b"Yamato & Musashi".to_vec();
 
-		match w3.encrypt_cbc(&plain) {
+		match w3.encrypt_cbc(&plain, Padding::Legacy) {
 			Ok(encrypted) => {
-				match w3.decrypt_cbc(&encrypted) {
+				match w3.decrypt_cbc(&encrypted, Padding::Legacy) {
 					Ok(decrypted) => {
 						assert_eq!(decrypted, plain);
 					},
-					Err(err) => panic!(err)
+					Err(err) => panic!("{}", err)
 				}
 			},
-			Err(err) => panic!(err)
+			Err(err) => panic!("{}", err)
 		};
 		
 	}
+
+	#[test]
+	fn test_block_cipher() {
+		let key = vec![0x34u8, 0x12, 0xf0, 0xde, 0xab, 0x89, 0x67, 0x45, 0x12, 0xf0, 0xde, 0xbc];
+		let plain = [0x89u8, 0x67, 0x45, 0x23, 0xf0, 0xde, 0xbc, 0x9a, 0x67, 0x45, 0x23, 0x01];
+		let expected = [0xbbu8, 0x5d, 0xa5, 0x0a, 0x6d, 0xdb, 0xdd, 0x9c, 0xb2, 0x76, 0xdb, 0x7c];
+
+		let w3 = <Way3 as BlockCipher>::new(&key).unwrap();
+		let mut block = plain;
+		w3.encrypt_block(&mut block);
+		assert_eq!(block, expected);
+		w3.decrypt_block(&mut block);
+		assert_eq!(block, plain);
+
+		assert_eq!(Way3::BLOCK_SIZE, 12);
+		assert!(<Way3 as BlockCipher>::new(&key[1..]).is_err());
+	}
+
+	// J. Daemen's reference vectors (key, plain text, cipher text)
+	// as big-endian 96-bit numbers a[2]:a[1]:a[0]
+	const REFERENCE_VECTORS: [([u8; 12], [u8; 12], [u8; 12]); 3] = [
+		(
+			[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+			[0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01],
+			[0xad, 0x21, 0xec, 0xf7, 0x83, 0xae, 0x9d, 0xc4, 0x40, 0x59, 0xc7, 0x6e],
+		),
+		(
+			[0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x06],
+			[0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03],
+			[0xca, 0xb9, 0x20, 0xcd, 0xd6, 0x14, 0x41, 0x38, 0xd2, 0xf0, 0x5b, 0x5e],
+		),
+		(
+			[0xbc, 0xde, 0xf0, 0x12, 0x45, 0x67, 0x89, 0xab, 0xde, 0xf0, 0x12, 0x34],
+			[0x01, 0x23, 0x45, 0x67, 0x9a, 0xbc, 0xde, 0xf0, 0x23, 0x45, 0x67, 0x89],
+			[0x7c, 0xdb, 0x76, 0xb2, 0x9c, 0xdd, 0xdb, 0x6d, 0x0a, 0xa5, 0x5d, 0xbb],
+		),
+	];
+
+	#[test]
+	fn test_reference_vectors() {
+		for (key, plain, expected) in REFERENCE_VECTORS.iter() {
+			let w3 = with_byte_order(key, ByteOrder::Big).unwrap();
+			let mut block = *plain;
+			w3.encrypt_block(&mut block);
+			assert_eq!(&block, expected);
+			w3.decrypt_block(&mut block);
+			assert_eq!(&block, plain);
+		}
+
+		// the same words as in test_block_321, only written in the reference order
+		let w3 = with_byte_order(&REFERENCE_VECTORS[1].0, ByteOrder::Big).unwrap();
+		assert_eq!(w3.encrypt((3, 2, 1)), (0xd2f05b5e, 0xd6144138, 0xcab920cd));
+	}
+
+	// byte-oriented ECB over the reference vectors, three blocks per key
+	#[test]
+	fn test_ecb_kat() {
+		for (key, plain, expected) in REFERENCE_VECTORS.iter() {
+			let w3 = with_byte_order(key, ByteOrder::Big).unwrap();
+			let input = plain.repeat(3);
+
+			let encrypted = w3.encrypt_ecb(&input, Padding::NoPadding).unwrap();
+			assert_eq!(encrypted, expected.repeat(3));
+			assert_eq!(w3.decrypt_ecb(&encrypted, Padding::NoPadding).unwrap(), input);
+		}
+	}
+
+	// byte-oriented CBC over the reference vectors: the plain text blocks
+	// are the reference plain text xored with the IV or the previous cipher
+	// text block, so every cipher text block is the reference cipher text
+	#[test]
+	fn test_cbc_kat() {
+		let iv: Vec<u8> = (0xf0u8..0xfc).collect();
+		for (key, plain, expected) in REFERENCE_VECTORS.iter() {
+			let w3 = with_byte_order(key, ByteOrder::Big).unwrap();
+			let mut input = Vec::new();
+			let mut prev = &iv[..];
+			for _ in 0..3 {
+				input.extend(plain.iter().zip(prev).map(|(p, c)| p ^ c));
+				prev = &expected[..];
+			}
+
+			let encrypted = w3.encrypt_cbc_iv(&input, &iv, Padding::NoPadding).unwrap();
+			assert_eq!(&encrypted[..12], &iv[..]);
+			assert_eq!(&encrypted[12..], &expected.repeat(3)[..]);
+			assert_eq!(w3.decrypt_cbc(&encrypted, Padding::NoPadding).unwrap(), input);
+		}
+	}
 }
 
 fn mu(mut x: (u32, u32, u32)) -> (u32, u32, u32) {
@@ -427,6 +464,29 @@ fn rho(x: (u32, u32, u32)) -> (u32, u32, u32) {
 	pi_2(gamma(pi_1(theta(x))))
 }
 
+/// Converts 12 bytes to three u32 words in byte order 'order'.
+fn load(data: &[u8], order: ByteOrder) -> (u32, u32, u32) {
+	match order {
+		ByteOrder::Little => bytes3block(data),
+		ByteOrder::Big => (
+			u32::from_be_bytes([data[8], data[9], data[10], data[11]]),
+			u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+			u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+		),
+	}
+}
+
+/// Stores three u32 words as 12 bytes in byte order 'order'.
+fn store(x: (u32, u32, u32), order: ByteOrder, data: &mut [u8]) {
+	match order {
+		ByteOrder::Little => block3bytes(x, data),
+		ByteOrder::Big => {
+			data[..4].copy_from_slice(&x.2.to_be_bytes());
+			data[4..8].copy_from_slice(&x.1.to_be_bytes());
+			data[8..12].copy_from_slice(&x.0.to_be_bytes());
+		},
+	}
+}
 
 fn bytes3block(data: &[u8]) -> (u32, u32, u32) {
 	let w0 = (data[3] as u32).wrapping_shl(24) |
@@ -461,4 +521,4 @@ fn block3bytes(x: (u32, u32, u32), data: &mut [u8]) {
 	data[10] = (x.2.wrapping_shr(16) & 0xff) as u8;
 	data[9]  = (x.2.wrapping_shr(8) & 0xff) as u8;
 	data[8]  = (x.2 & 0xff) as u8;
-}
\ No newline at end of file
+}